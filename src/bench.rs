@@ -0,0 +1,123 @@
+//! `mux --bench PANES` load generator.
+//!
+//! Spawns `PANES` copies of a small embedded workload (scrolling output, an SGR color storm, and
+//! a couple of alt-screen round trips, the three things the `Storage`/`Cell` performance work is
+//! meant to help with) and reports wall-clock time, per-pane CPU/memory usage, and time to first
+//! byte, so a performance change can be checked against a consistent load without a person
+//! driving real panes by hand.
+//!
+//! This intentionally doesn't run the interactive TUI at all: a soak test's point is the report
+//! at the end, not something to watch live, so it skips straight from spawning to
+//! `sys::proc_stats` polling instead of going through `ui::Ui`.
+
+use std::time;
+
+use futures::future::Future;
+use futures::stream::Stream;
+
+/// Alternates plain scrolling text, an SGR color storm (heavy attribute churn per write), and an
+/// alt-screen flicker, so the bench exercises the same rendering paths a human session would.
+const WORKLOAD: &str = r#"
+for i in $(seq 1 500); do
+    printf '\033[3%dmline %d: the quick brown fox jumps over the lazy dog\033[0m\n' "$((i % 8))" "$i"
+done
+tput smcup
+for i in $(seq 1 50); do
+    printf '\033[%d;1Hoverwrite %d' "$((i % 24 + 1))" "$i"
+done
+tput rmcup
+"#;
+
+struct PaneReport {
+    first_byte: Option<time::Duration>,
+    bytes_received: u64,
+    cpu_ticks: u64,
+    rss_bytes: u64,
+}
+
+/// Run the bench and print its report to stdout.
+pub async fn run(panes: usize) -> Result<(), failure::Error> {
+    use crate::process::Process;
+    use crate::sys::proc_stats;
+    use std::sync::{Arc, Mutex};
+
+    let started = time::Instant::now();
+
+    let args = ["-c".to_owned(), WORKLOAD.to_owned()];
+    let processes = (0..panes)
+        .map(|_| {
+            Process::spawn(crate::process::SpawnOptions {
+                command: std::ffi::OsStr::new("sh"),
+                args: &args,
+                cwd: None,
+                envs: &[],
+                rlimits: crate::rlimits::Rlimits::default(),
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let pids: Vec<u32> = processes.iter().map(Process::pid).collect();
+    let (_writes, reads): (Vec<_>, Vec<_>) = processes.into_iter().map(Process::split).unzip();
+
+    let bytes_received = Arc::new(Mutex::new(vec![0u64; panes]));
+    let first_byte = Arc::new(Mutex::new(vec![None; panes]));
+    let usages = Arc::new(Mutex::new(vec![proc_stats::Usage::default(); panes]));
+    let exits = reads.into_iter().enumerate().map(|(index, read)| {
+        let crate::process::Read { output, exit } = read;
+
+        let bytes_received = Arc::clone(&bytes_received);
+        let first_byte = Arc::clone(&first_byte);
+        let usages = Arc::clone(&usages);
+        let pid = pids[index];
+        let drain = output
+            .for_each(move |chunk| {
+                bytes_received.lock().unwrap()[index] += chunk.len() as u64;
+                first_byte.lock().unwrap()[index].get_or_insert_with(|| started.elapsed());
+                // Sampled while the pane is still running: once it has exited and been reaped,
+                // its `/proc` entries are gone and a sample would read as all zeros.
+                let sample = proc_stats::tree_usage(pid).unwrap_or_default();
+                if sample.rss_bytes > 0 {
+                    usages.lock().unwrap()[index] = sample;
+                }
+                Ok(())
+            })
+            .map_err(|err| debug!("bench pane {} output error: {}", index, err));
+
+        drain.then(move |_| exit.map(|_| ()).map_err(|_| ()))
+    });
+
+    await!(futures::future::join_all(exits)).ok();
+
+    let wall_clock = started.elapsed();
+    let bytes_received = bytes_received.lock().unwrap();
+    let first_byte = first_byte.lock().unwrap();
+    let usages = usages.lock().unwrap();
+
+    let reports: Vec<PaneReport> = pids
+        .iter()
+        .enumerate()
+        .map(|(index, _)| {
+            let usage = usages[index];
+            PaneReport {
+                first_byte: first_byte[index],
+                bytes_received: bytes_received[index],
+                cpu_ticks: usage.cpu_ticks,
+                rss_bytes: usage.rss_bytes,
+            }
+        })
+        .collect();
+
+    println!("mux bench: {} panes, {:?} wall clock", panes, wall_clock);
+    for (index, report) in reports.iter().enumerate() {
+        println!(
+            "  pane {}: first byte {:?}, {} bytes, {} cpu ticks, {} bytes rss",
+            index,
+            report.first_byte,
+            report.bytes_received,
+            report.cpu_ticks,
+            report.rss_bytes,
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,73 @@
+//! Asciicast v2 recording (<https://docs.asciinema.org/manual/asciicast/v2/>), written alongside
+//! `--log-dir`'s raw per-pane logs when `--record-cast DIR` is given.
+//!
+//! Unlike `--log-dir`'s plain byte dump, asciicast v2 timestamps each chunk of output relative to
+//! the start of the recording, which is what lets `mux --play` (or any other asciicast player)
+//! reproduce the original pacing rather than just dumping the bytes as fast as they can be read.
+
+use std::fs;
+use std::io::Write;
+use std::time::Instant;
+
+pub struct CastWriter {
+    file: fs::File,
+    started: Instant,
+}
+
+impl CastWriter {
+    /// Create `path` and write its asciicast v2 header, sized to `width`x`height`.
+    pub fn create(path: &std::path::Path, width: u16, height: u16) -> Result<Self, failure::Error> {
+        let mut file = fs::File::create(path)?;
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+
+        writeln!(
+            file,
+            r#"{{"version":2,"width":{},"height":{},"timestamp":{}}}"#,
+            width, height, timestamp,
+        )?;
+
+        Ok(Self {
+            file,
+            started: Instant::now(),
+        })
+    }
+
+    /// Append an `"o"` (output) event for `data`, timestamped relative to [`create`].
+    pub fn write_output(&mut self, data: &[u8]) -> Result<(), failure::Error> {
+        let elapsed = self.started.elapsed().as_secs_f64();
+        writeln!(
+            self.file,
+            "[{:.6},\"o\",{}]",
+            elapsed,
+            json_escape(&String::from_utf8_lossy(data)),
+        )?;
+        Ok(())
+    }
+}
+
+/// Encode `s` as a JSON string literal; asciicast events otherwise have no dependency on a JSON
+/// library, so this hand-rolls the handful of escapes a terminal's raw output can contain.
+///
+/// `pub(crate)` since `event_bus` and `notify` reuse it for the same reason: their payloads can
+/// contain arbitrary pane output/titles, and `Debug`'s `\u{...}`-braced escapes for control bytes
+/// aren't valid JSON.
+pub(crate) fn json_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
@@ -0,0 +1,190 @@
+//! User configuration loaded from `~/.config/mux/config.toml`
+//!
+//! Configuration is optional; if no file is present `Config::default()` is used. The file is
+//! polled for changes so that a subset of settings (currently just `scrollback_lines`, since
+//! it's the only one that can be changed without restarting the already-spawned processes) can
+//! be picked up without restarting `mux`.
+
+use crate::event_bus::EventBus;
+use crate::hooks::Hooks;
+use crate::keybindings::Keybindings;
+use crate::locale::Locale;
+use crate::notify::NotificationBackend;
+use crate::palette::{ColorDepth, ColorMode};
+use crate::rlimits::Rlimits;
+use std::fs;
+use std::path;
+use std::time;
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Number of scrollback lines to keep per pane.
+    pub scrollback_lines: usize,
+
+    /// Shell to fall back to when no `COMMAND` is given on the command line.
+    ///
+    /// Currently unused, since `COMMAND` is a required positional argument; kept here so that
+    /// config files written against this schema stay forward-compatible.
+    pub default_shell: Option<String>,
+
+    pub keybindings: Keybindings,
+
+    /// Export `MUX_SHELL_INTEGRATION` to each spawned command (`MUX_PANE` is exported whether or
+    /// not this is set; see `pane_envs` in `main.rs`).
+    ///
+    /// This only sets environment variables; it's up to the command (or a shell rc file that
+    /// checks for `MUX_SHELL_INTEGRATION`) to do anything with them, e.g. emit prompt markers.
+    pub shell_integration: bool,
+
+    /// Color remapping applied to rendered output, for high-contrast or colorblind-safe viewing.
+    pub color_mode: ColorMode,
+
+    /// Quantize truecolor (`Rgb`) output down to a 256- or 16-color palette, for outer terminals
+    /// that don't support 24-bit color. `true-color` (the default) leaves colors untouched.
+    pub color_depth: ColorDepth,
+
+    /// Minimum WCAG contrast ratio to enforce between a cell's foreground and background, e.g.
+    /// `4.5` for WCAG AA. `1.0` (the default) disables enforcement, since every pair of colors
+    /// already has a contrast ratio of at least 1.0.
+    pub min_contrast: f64,
+
+    /// Shell commands to run on pane lifecycle events.
+    pub hooks: Hooks,
+
+    /// Desktop notification backend used for OSC 9/777 requests and for bell/silence/pane-exit
+    /// events, alongside (not instead of) `hooks`.
+    pub notification_backend: NotificationBackend,
+
+    /// Where to publish structured JSON pane-exit/bell events for external automation, alongside
+    /// (not instead of) `hooks` and `notification_backend`.
+    pub event_bus: EventBus,
+
+    /// Flag a pane as silent in the tab list (and run `hooks.on_silence`) after this many
+    /// seconds without output, tmux `monitor-silence` style. `None` disables silence monitoring.
+    pub monitor_silence_secs: Option<u64>,
+
+    /// Extra columns beyond the visible pane width that a pane's terminal grid can hold when
+    /// line wrap (DECAWM) is off, for panning through wide output with `pan_left`/`pan_right`.
+    /// `0` (the default) disables panning entirely, keeping the grid exactly as wide as the pane.
+    pub horizontal_overscan: usize,
+
+    /// Number of lines the selected pane's viewport scrolls per mouse wheel tick.
+    ///
+    /// The grid is line-based (see `terminal-emulator`'s `Grid`), so there's no sub-line position
+    /// to report back for smooth/animated scrolling; this only controls how coarse or fine a
+    /// single wheel tick feels.
+    pub scroll_lines: usize,
+
+    /// Render East Asian "ambiguous width" characters (see UAX #11) as double-width.
+    ///
+    /// Whether these characters should take one cell or two depends on the locale of whatever's
+    /// running in the pane, not on `mux` itself, so this is a config toggle rather than something
+    /// `terminal-emulator` can infer from the character alone.
+    pub ambiguous_wide_chars: bool,
+
+    /// Maximum number of times per second `mux` repaints the tty in response to pty output.
+    /// Output on the selected pane still renders right away, since that's the one thing actually
+    /// on screen; bursts of output on a pane that isn't selected are coalesced to this rate
+    /// instead of triggering a full redraw for every chunk that arrives, which is what made a
+    /// background pane running something noisy (`yes`, a busy build) burn CPU on draws nobody was
+    /// watching. `0` disables the cap, restoring the old "redraw on every event" behavior.
+    pub max_fps: u32,
+
+    /// Characters that stop a semantic (double-click) selection from expanding further, e.g.
+    /// `" \t,;:()[]{}\"'"` to stop at whitespace and common punctuation. Empty (the default)
+    /// keeps the historical behavior of running a semantic selection to the end of the visible
+    /// line, so existing configs aren't affected by adding this option.
+    pub word_separators: String,
+
+    /// Resource limits applied to every spawned pane via `setrlimit(2)`, to contain a runaway
+    /// job. Unset fields (the default) leave that resource unlimited, same as not spawning
+    /// through `mux` at all.
+    pub rlimits: Rlimits,
+
+    /// `TZ`/`LANG`/`LC_*` overrides exported into every spawned pane's environment. Unset fields
+    /// (the default) leave the corresponding variable unset, so the pane inherits whatever `mux`
+    /// itself was started with, same as not spawning through `mux` at all.
+    pub locale: Locale,
+
+    /// Publish debounced `LineChanged`/`CursorMoved`/`ScreenCleared` events to `event_bus` for
+    /// every pane, for a screen reader or logging integration that wants a structured view of
+    /// what changed instead of diffing cell grids itself. Off by default: every pane's full
+    /// screen is diffed once a second while this is on (see
+    /// `ui::ProcessState::accessibility_changes`), which is wasted work for the common case of
+    /// nobody listening on `event_bus`.
+    pub accessibility_events: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            scrollback_lines: 1024,
+            default_shell: None,
+            keybindings: Keybindings::default(),
+            shell_integration: false,
+            color_mode: ColorMode::default(),
+            color_depth: ColorDepth::default(),
+            min_contrast: 1.0,
+            hooks: Hooks::default(),
+            notification_backend: NotificationBackend::default(),
+            event_bus: EventBus::default(),
+            monitor_silence_secs: None,
+            horizontal_overscan: 0,
+            scroll_lines: 3,
+            ambiguous_wide_chars: false,
+            max_fps: 60,
+            word_separators: String::new(),
+            rlimits: Rlimits::default(),
+            accessibility_events: false,
+            locale: Locale::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Load the config file, falling back to defaults if it doesn't exist.
+    pub fn load() -> Result<Self, failure::Error> {
+        match path() {
+            Some(path) if path.exists() => {
+                let contents = fs::read_to_string(&path)?;
+                Ok(toml::from_str(&contents)?)
+            }
+            _ => Ok(Self::default()),
+        }
+    }
+}
+
+/// Poll the config file for changes, yielding a freshly loaded `Config` each time its contents
+/// change.
+///
+/// This is a plain content poll (reload and compare) rather than an OS file watch, consistent with how this
+/// crate already polls for terminal resizes (see `run_gui`'s use of `tokio::timer::Interval`).
+pub fn watch(
+    interval: time::Duration,
+) -> impl futures::stream::Stream<Item = Config, Error = failure::Error> {
+    use futures::stream::Stream;
+
+    let mut last = None;
+
+    tokio::timer::Interval::new_interval(interval)
+        .map_err(failure::Error::from)
+        .filter_map(move |_| match Config::load() {
+            Ok(config) if Some(&config) != last.as_ref() => {
+                last = Some(config.clone());
+                Some(config)
+            }
+            Ok(_) => None,
+            Err(err) => {
+                debug!("failed to reload config: {}", err);
+                None
+            }
+        })
+}
+
+fn path() -> Option<path::PathBuf> {
+    let mut path = dirs::config_dir()?;
+    path.push("mux");
+    path.push("config.toml");
+    Some(path)
+}
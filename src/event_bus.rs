@@ -0,0 +1,124 @@
+//! Publishes structured JSON events to an external subscriber, for automation that wants to react
+//! to what's happening in `mux` without polling it or grepping `--log-dir` output.
+//!
+//! [`Event::PaneExit`] and [`Event::PaneBell`] are the two pane lifecycle events `Hooks` and
+//! `notify::NotificationBackend` already observe and fire shell commands/notifications for, so
+//! publishing them here is a matter of also serializing them as JSON rather than adding new
+//! instrumentation. [`Event::LineChanged`], [`Event::CursorMoved`], and [`Event::ScreenCleared`]
+//! are different: they're `Config::accessibility_events`'s debounced, line-level view of a pane's
+//! screen contents, for a screen reader or logging integration that wants to know what changed
+//! without diffing cell grids itself (see `ui::ProcessState::accessibility_changes`). "Watcher
+//! matches" (output pattern matching) and "client attach" (multiple UIs sharing one session) both
+//! describe features `mux` doesn't have: there's no output-watching subsystem anywhere in this
+//! codebase, and no daemon or session for a second client to attach to, since `mux` is a single
+//! short-lived TUI process per invocation (see README's "Out of scope" section). Either would
+//! need to exist as its own feature before there'd be an event here worth publishing.
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
+#[serde(tag = "sink", rename_all = "kebab-case")]
+pub enum EventBus {
+    /// Don't publish events.
+    None,
+    /// POST each event as a JSON object to a webhook URL. See `http::post_json` for the
+    /// `http://`-only caveat.
+    Webhook { url: String },
+    /// Connect to a listening Unix socket and write one JSON object per line, newline-delimited,
+    /// reconnecting for each event rather than holding the socket open - simpler, and more
+    /// resilient to a subscriber that restarts, at the cost of a little latency per event.
+    UnixSocket { path: std::path::PathBuf },
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        EventBus::None
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    PaneExit {
+        pane: usize,
+        exit_code: Option<i32>,
+        /// Signal that killed the pane's process, if it didn't exit normally (see
+        /// `std::os::unix::process::ExitStatusExt::signal`).
+        signal: Option<i32>,
+    },
+    PaneBell {
+        pane: usize,
+    },
+    /// A visible line's text differs from what it was at the previous once-a-second sample of
+    /// the pane's screen (see `accessibility_changes`'s debouncing).
+    LineChanged {
+        pane: usize,
+        line: usize,
+        text: String,
+    },
+    /// The cursor settled on a different position than it last reported.
+    CursorMoved {
+        pane: usize,
+        line: usize,
+        col: usize,
+    },
+    /// Every visible line went blank at once, e.g. from `clear` or an alt-screen switch -
+    /// reported instead of a wall of individual `LineChanged` events for every row.
+    ScreenCleared {
+        pane: usize,
+    },
+}
+
+impl Event {
+    fn to_json(&self) -> String {
+        match self {
+            Event::PaneExit {
+                pane,
+                exit_code,
+                signal,
+            } => format!(
+                r#"{{"event":"pane_exit","pane":{},"exit_code":{},"signal":{}}}"#,
+                pane,
+                exit_code.map_or("null".to_owned(), |code| code.to_string()),
+                signal.map_or("null".to_owned(), |signal| signal.to_string()),
+            ),
+            Event::PaneBell { pane } => format!(r#"{{"event":"bell","pane":{}}}"#, pane),
+            Event::LineChanged { pane, line, text } => format!(
+                r#"{{"event":"line_changed","pane":{},"line":{},"text":{}}}"#,
+                pane,
+                line,
+                crate::cast::json_escape(text),
+            ),
+            Event::CursorMoved { pane, line, col } => format!(
+                r#"{{"event":"cursor_moved","pane":{},"line":{},"col":{}}}"#,
+                pane, line, col,
+            ),
+            Event::ScreenCleared { pane } => {
+                format!(r#"{{"event":"screen_cleared","pane":{}}}"#, pane)
+            }
+        }
+    }
+}
+
+impl EventBus {
+    /// Publish `event`, logging (rather than propagating) any failure - same fire-and-forget
+    /// contract as `Hooks` and `notify::NotificationBackend`.
+    pub fn publish(&self, event: Event) {
+        let json = event.to_json();
+        let result = match self {
+            EventBus::None => Ok(()),
+            EventBus::Webhook { url } => crate::http::post_json(url, &json),
+            EventBus::UnixSocket { path } => publish_unix_socket(path, &json),
+        };
+
+        if let Err(err) = result {
+            debug!("failed to publish event {:?}: {}", event, err);
+        }
+    }
+}
+
+fn publish_unix_socket(path: &std::path::Path, json: &str) -> Result<(), failure::Error> {
+    use std::io::Write;
+    use std::os::unix::net::UnixStream;
+
+    let mut stream = UnixStream::connect(path)?;
+    writeln!(stream, "{}", json)?;
+    Ok(())
+}
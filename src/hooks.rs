@@ -0,0 +1,92 @@
+//! Shell hooks run on `mux` events, tmux-hooks style but scoped to the handful of things this
+//! tool can actually observe about its own panes.
+
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize)]
+#[serde(default)]
+pub struct Hooks {
+    /// Shell command run (via `sh -c`) when a pane's command exits. `MUX_PANE`, `MUX_EXIT_CODE`,
+    /// and (if the process was killed by a signal rather than exiting normally) `MUX_EXIT_SIGNAL`
+    /// are set in its environment.
+    pub on_pane_exit: Option<String>,
+
+    /// Shell command run when a pane rings the terminal bell. `MUX_PANE` is set in its
+    /// environment.
+    pub on_bell: Option<String>,
+
+    /// Shell command run when a pane has produced no output for `monitor_silence_secs`.
+    /// `MUX_PANE` is set in its environment.
+    pub on_silence: Option<String>,
+
+    /// Shell command run when a pane asks for a desktop notification via OSC 9 or OSC
+    /// 777;notify (see `Term::take_notification`), alongside (not instead of)
+    /// `notification_backend`. `MUX_PANE` is set in its environment, along with `MUX_TITLE` if
+    /// the request carried one (OSC 777 does; OSC 9 never does).
+    pub on_notify: Option<String>,
+}
+
+impl Hooks {
+    pub fn run_on_pane_exit(&self, index: usize, status: std::process::ExitStatus) {
+        use std::os::unix::process::ExitStatusExt;
+
+        let command = match self.on_pane_exit {
+            Some(ref command) => command,
+            None => return,
+        };
+
+        let exit_code = status
+            .code()
+            .map_or_else(String::new, |code| code.to_string());
+        let mut extra_envs = vec![("MUX_EXIT_CODE", exit_code)];
+        if let Some(signal) = status.signal() {
+            extra_envs.push(("MUX_EXIT_SIGNAL", signal.to_string()));
+        }
+
+        self.run(command, index, &extra_envs);
+    }
+
+    pub fn run_on_bell(&self, index: usize) {
+        let command = match self.on_bell {
+            Some(ref command) => command,
+            None => return,
+        };
+
+        self.run(command, index, &[]);
+    }
+
+    pub fn run_on_silence(&self, index: usize) {
+        let command = match self.on_silence {
+            Some(ref command) => command,
+            None => return,
+        };
+
+        self.run(command, index, &[]);
+    }
+
+    pub fn run_on_notify(&self, index: usize, title: Option<&str>) {
+        let command = match self.on_notify {
+            Some(ref command) => command,
+            None => return,
+        };
+
+        let extra_envs: Vec<(&str, String)> = title
+            .map(|title| vec![("MUX_TITLE", title.to_owned())])
+            .unwrap_or_default();
+
+        self.run(command, index, &extra_envs);
+    }
+
+    fn run(&self, command: &str, index: usize, extra_envs: &[(&str, String)]) {
+        let mut cmd = std::process::Command::new("sh");
+        cmd.arg("-c")
+            .arg(command)
+            .env("MUX_PANE", index.to_string());
+
+        for (key, value) in extra_envs {
+            cmd.env(key, value);
+        }
+
+        if let Err(err) = cmd.spawn() {
+            debug!("failed to run hook {:?}: {}", command, err);
+        }
+    }
+}
@@ -0,0 +1,86 @@
+//! A minimal synchronous `POST` used by the handful of places that need to reach an HTTP webhook
+//! (`notify::NotificationBackend::Webhook`, `event_bus::EventBus::Webhook`) without pulling in an
+//! HTTP client crate for it. Only plain `http://` is supported: this crate has no TLS dependency,
+//! so `https://` URLs are rejected rather than silently sent in the clear.
+
+use std::io::{BufRead, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// How long to wait on the connect, write, and read-status-line steps before giving up. A slow
+/// or unreachable webhook host shouldn't be able to stall a pane's caller for longer than this.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// POST `body` (assumed to already be JSON) to `url` on a background thread, logging the
+/// response's status line (or any failure) but not otherwise inspecting it - same fire-and-forget
+/// contract as the rest of this crate's outbound notifications, since nothing here is in a
+/// position to retry or surface a failure to the user.
+///
+/// Callers (`notify::notify_webhook`, `event_bus::EventBus::publish`) run on the tokio reactor
+/// that also drives every pane's I/O, so the actual connect/write/read happens off-thread: a slow
+/// or unreachable host must not freeze every other pane waiting on the same reactor.
+pub fn post_json(url: &str, body: &str) -> Result<(), failure::Error> {
+    let (host, port, path) = parse_http_url(url)
+        .ok_or_else(|| failure::err_msg(format!("not a supported http:// URL: {}", url)))?;
+    let body = body.to_owned();
+
+    std::thread::spawn(move || {
+        if let Err(err) = send(&host, port, &path, &body) {
+            debug!("failed to POST to {}:{}{}: {}", host, port, path, err);
+        }
+    });
+
+    Ok(())
+}
+
+fn send(host: &str, port: u16, path: &str, body: &str) -> Result<(), failure::Error> {
+    let request = format!(
+        "POST {path} HTTP/1.1\r\n\
+         Host: {host}\r\n\
+         Content-Type: application/json\r\n\
+         Content-Length: {len}\r\n\
+         Connection: close\r\n\
+         \r\n\
+         {body}",
+        path = path,
+        host = host,
+        len = body.len(),
+        body = body,
+    );
+
+    let addr = (host, port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| failure::err_msg(format!("couldn't resolve {}:{}", host, port)))?;
+    let mut stream = TcpStream::connect_timeout(&addr, TIMEOUT)?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    stream.write_all(request.as_bytes())?;
+
+    let mut status_line = String::new();
+    std::io::BufReader::new(&stream).read_line(&mut status_line)?;
+    debug!("webhook {}:{}{} responded: {}", host, port, path, status_line.trim_end());
+
+    Ok(())
+}
+
+/// Split a `http://host[:port][/path]` URL into its connection parts. Returns `None` for
+/// anything else, including `https://`.
+fn parse_http_url(url: &str) -> Option<(String, u16, String)> {
+    let rest = url.strip_prefix("http://")?;
+    let (authority, path) = match rest.find('/') {
+        Some(index) => (&rest[..index], &rest[index..]),
+        None => (rest, "/"),
+    };
+
+    let (host, port) = match authority.find(':') {
+        Some(index) => (&authority[..index], authority[index + 1..].parse().ok()?),
+        None => (authority, 80),
+    };
+
+    if host.is_empty() {
+        return None;
+    }
+
+    Some((host.to_owned(), port, path.to_owned()))
+}
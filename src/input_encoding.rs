@@ -0,0 +1,50 @@
+//! Translate a key press into the escape sequence a pane's foreground program actually expects,
+//! honoring DECCKM (application cursor keys) and xterm's `modifyOtherKeys` levels 1/2, so callers
+//! don't have to hand-roll a `CSI`/`SS3` sequence per key the way `State::scroll_or_send_key` used
+//! to.
+//!
+//! DECKPAM (application keypad mode) isn't covered here: `termion::event::Key` has no variants
+//! for the numeric keypad's digits or operators, so there's nothing to distinguish a keypad `7`
+//! from a main-keyboard `7` to re-encode in the first place.
+
+use terminal_emulator::term::TermMode;
+
+/// Encode `key` for the foreground program described by `mode` and `modify_other_keys`, or
+/// `None` if this key isn't affected by either and should be forwarded as the raw bytes the
+/// user's own terminal already produced for it.
+pub fn encode_key(
+    key: termion::event::Key,
+    mode: &TermMode,
+    modify_other_keys: u8,
+) -> Option<Vec<u8>> {
+    use termion::event::Key;
+
+    match key {
+        Key::Up | Key::Down | Key::Right | Key::Left | Key::Home | Key::End => {
+            let letter = match key {
+                Key::Up => b'A',
+                Key::Down => b'B',
+                Key::Right => b'C',
+                Key::Left => b'D',
+                Key::Home => b'H',
+                Key::End => b'F',
+                _ => unreachable!(),
+            };
+            let introducer: u8 = if mode.contains(TermMode::APP_CURSOR) {
+                b'O'
+            } else {
+                b'['
+            };
+            Some(vec![0x1b, introducer, letter])
+        }
+        Key::Ctrl(c) | Key::Alt(c) if modify_other_keys > 0 => {
+            // `modifyOtherKeys` level 2's `CSI codepoint ; modifier u` form; level 1 only asks
+            // for this on keys that would otherwise be ambiguous or lost, which for the subset of
+            // keys `termion` can report here (see the module doc comment on its other gaps) is
+            // every `Ctrl`/`Alt`-modified character, so both levels are handled identically.
+            let modifier = if matches!(key, Key::Ctrl(_)) { 5 } else { 3 };
+            Some(format!("\x1b[{};{}u", c as u32, modifier).into_bytes())
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,140 @@
+//! A small, user-configurable keybinding table.
+//!
+//! `mux` doesn't have tmux's notion of a prefix key or nested key tables, since it has no modal
+//! command mode; what it does have is a fixed set of internal commands (quit, switch to the next
+//! or previous tab, pan the selected pane's viewport) that used to be hardcoded to
+//! `Ctrl+T`/`Ctrl+N`/`Ctrl+P`. This module lets those be remapped from `config.toml`, e.g.:
+//!
+//! ```toml
+//! [keybindings]
+//! quit = "ctrl+t"
+//! next_pane = "ctrl+n"
+//! prev_pane = "ctrl+p"
+//! pan_left = "alt+h"
+//! pan_right = "alt+l"
+//! ```
+//!
+//! Running `mux` inside another `mux` pane (`ssh`ing into a box and starting one there, say)
+//! means the outer instance's bindings intercept the same keys before the inner one ever sees
+//! them, since both default to the same table. There's no single prefix chord to double up on
+//! the way tmux's `send-prefix` does; instead `passthrough` binds a chord that forwards the very
+//! next keypress straight to the pane, bypassing the table entirely for one key, so `Ctrl+T` can
+//! still reach the inner `mux` to quit it. `mux` can tell it's nested this way in the first
+//! place because it always exports `MUX` into a pane's environment (see `pane_envs` in
+//! `main.rs`), so an inner instance inherits the outer one's `MUX` and knows to mention
+//! `passthrough` in its startup status message.
+
+use termion::event::Key;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Command {
+    Quit,
+    NextPane,
+    PrevPane,
+    /// Pan the selected pane's viewport left, revealing columns written past the visible edge
+    /// while DECAWM is off (see `Config::horizontal_overscan`).
+    PanLeft,
+    PanRight,
+    /// Paste the top of the paste buffer stack into the selected pane.
+    PasteBuffer,
+    /// Open the `choose-buffer` overlay to pick a paste buffer by name.
+    ChooseBuffer,
+    /// Open the `choose-pane` overlay to find and jump to a pane by filtering.
+    ChoosePane,
+    /// Forward the next keypress straight to the selected pane, even if it's bound to one of the
+    /// commands above. The nested-`mux` analog of tmux's `send-prefix`.
+    Passthrough,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
+#[serde(default)]
+pub struct Keybindings {
+    pub quit: String,
+    pub next_pane: String,
+    pub prev_pane: String,
+    pub pan_left: String,
+    pub pan_right: String,
+    pub paste_buffer: String,
+    pub choose_buffer: String,
+    pub choose_pane: String,
+    pub passthrough: String,
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        Self {
+            quit: "ctrl+t".to_owned(),
+            next_pane: "ctrl+n".to_owned(),
+            prev_pane: "ctrl+p".to_owned(),
+            pan_left: "alt+h".to_owned(),
+            pan_right: "alt+l".to_owned(),
+            paste_buffer: "ctrl+y".to_owned(),
+            choose_buffer: "alt+y".to_owned(),
+            choose_pane: "alt+w".to_owned(),
+            passthrough: "alt+t".to_owned(),
+        }
+    }
+}
+
+impl Keybindings {
+    /// Resolve a key press to an internal command, if it's bound to one.
+    pub fn resolve(&self, key: Key) -> Option<Command> {
+        if Some(key) == parse_key(&self.quit) {
+            Some(Command::Quit)
+        } else if Some(key) == parse_key(&self.next_pane) {
+            Some(Command::NextPane)
+        } else if Some(key) == parse_key(&self.prev_pane) {
+            Some(Command::PrevPane)
+        } else if Some(key) == parse_key(&self.pan_left) {
+            Some(Command::PanLeft)
+        } else if Some(key) == parse_key(&self.pan_right) {
+            Some(Command::PanRight)
+        } else if Some(key) == parse_key(&self.paste_buffer) {
+            Some(Command::PasteBuffer)
+        } else if Some(key) == parse_key(&self.choose_buffer) {
+            Some(Command::ChooseBuffer)
+        } else if Some(key) == parse_key(&self.choose_pane) {
+            Some(Command::ChoosePane)
+        } else if Some(key) == parse_key(&self.passthrough) {
+            Some(Command::Passthrough)
+        } else {
+            None
+        }
+    }
+}
+
+/// Parse a binding string like `"ctrl+t"` or `"alt+x"` into a `termion` key. Only `ctrl` and
+/// `alt` are recognized as modifiers.
+///
+/// Invalid bindings silently resolve to `None` (i.e. unbound) rather than failing startup, since
+/// a typo in a keybinding shouldn't prevent `mux` from running.
+fn parse_key(spec: &str) -> Option<Key> {
+    let mut parts = spec.split('+').collect::<Vec<_>>();
+    let base = parts.pop()?;
+
+    let mut ctrl = false;
+    let mut alt = false;
+    for modifier in parts {
+        match modifier {
+            "ctrl" => ctrl = true,
+            "alt" => alt = true,
+            _ => return None,
+        }
+    }
+
+    let mut chars = base.chars();
+    let base_char = match (chars.next(), chars.next()) {
+        (Some(c), None) => c,
+        _ => return None,
+    };
+
+    let key = if ctrl {
+        Key::Ctrl(base_char)
+    } else if alt {
+        Key::Alt(base_char)
+    } else {
+        Key::Char(base_char)
+    };
+
+    Some(key)
+}
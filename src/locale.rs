@@ -0,0 +1,34 @@
+//! `TZ`/`LANG`/`LC_*` overrides exported into every spawned pane's environment, for mirroring a
+//! remote production environment's timezone and locale locally without changing your own shell's.
+
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize)]
+#[serde(default)]
+pub struct Locale {
+    /// Overrides `TZ`, e.g. `"America/New_York"`.
+    pub tz: Option<String>,
+
+    /// Overrides `LANG`, e.g. `"en_US.UTF-8"`.
+    pub lang: Option<String>,
+
+    /// Overrides for individual `LC_*` categories, keyed by the part after `LC_` (`"TIME"` for
+    /// `LC_TIME`, `"ALL"` for `LC_ALL`, and so on) rather than one field per category, since a
+    /// real override is usually just one or two categories rather than all of them at once.
+    pub lc: std::collections::BTreeMap<String, String>,
+}
+
+impl Locale {
+    /// `(name, value)` pairs to export into a spawned pane's environment.
+    pub fn envs(&self) -> Vec<(String, String)> {
+        let mut envs = Vec::new();
+        if let Some(ref tz) = self.tz {
+            envs.push(("TZ".to_owned(), tz.clone()));
+        }
+        if let Some(ref lang) = self.lang {
+            envs.push(("LANG".to_owned(), lang.clone()));
+        }
+        for (category, value) in &self.lc {
+            envs.push((format!("LC_{}", category), value.clone()));
+        }
+        envs
+    }
+}
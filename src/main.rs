@@ -13,8 +13,23 @@ extern crate tokio;
 mod sys;
 
 mod args;
+mod bench;
+mod cast;
+mod config;
+mod event_bus;
+mod hooks;
+mod http;
+mod input_encoding;
+mod keybindings;
+mod locale;
+mod notify;
 mod options;
+mod palette;
+mod paste_buffers;
+mod play;
 mod process;
+mod replay;
+mod rlimits;
 mod sinks;
 mod streams;
 mod tty;
@@ -38,6 +53,34 @@ fn run() -> Result<(), failure::Error> {
 
     log_panics::init();
 
+    if let Some(shell) = options::completions_shell(std::env::args()) {
+        options::Options::clap().gen_completions_to("mux", shell, &mut std::io::stdout());
+        return Ok(());
+    }
+
+    if let Some(path) = options::replay_log_path(std::env::args()) {
+        return replay::run(&path);
+    }
+
+    if let Some((path, speed)) = options::play_options(std::env::args()) {
+        return play::run(&path, speed);
+    }
+
+    if let Some(panes) = options::bench_pane_count(std::env::args()) {
+        let result = sync::Arc::new(sync::Mutex::new(None));
+        let result_clone = sync::Arc::clone(&result);
+        tokio::run(
+            tokio_async_await::compat::backward::Compat::new(bench::run(panes))
+                .then(move |r| futures::future::ok(*result_clone.lock().unwrap() = Some(r))),
+        );
+
+        return result.lock().unwrap().take().unwrap_or_else(|| {
+            Err(failure::err_msg(
+                "an async panic occurred (check log file for more info)",
+            ))
+        });
+    }
+
     let options = options::Options::from_args();
 
     if let Some(mut log) = dirs::cache_dir() {
@@ -89,17 +132,48 @@ async fn run_with_options(mut options: options::Options) -> Result<(), failure::
     use futures::future::Future;
     use futures::stream::Stream;
 
+    let profile_startup = options.profile_startup;
+    let mut phase_start = std::time::Instant::now();
+
+    let config = config::Config::load()?;
+
+    if profile_startup {
+        info!("profile-startup: config load took {:?}", phase_start.elapsed());
+        phase_start = std::time::Instant::now();
+    }
+
     let template_placeholder = options.replace.clone().unwrap_or_else(|| "{}".to_owned());
+    let log_dir = options.log_dir.clone();
+    let record_cast_dir = options.record_cast_dir.clone();
     let args = await!(args::read(&mut options))?;
     let command = options.command;
 
     let processes = args
         .iter()
-        .map(|args| process::Process::spawn(&command, &args.all))
+        .enumerate()
+        .map(|(index, args)| {
+            process::Process::spawn(process::SpawnOptions {
+                command: std::ffi::OsStr::new(&command),
+                args: &args.all,
+                cwd: None,
+                envs: &pane_envs(&config, index),
+                rlimits: config.rlimits,
+            })
+        })
         .collect::<Result<Vec<_>, _>>()?;
 
     debug!("spawned {} processes", processes.len());
 
+    if profile_startup {
+        info!(
+            "profile-startup: spawning {} processes took {:?}",
+            args.len(),
+            phase_start.elapsed()
+        );
+        phase_start = std::time::Instant::now();
+    }
+
+    let pids: Vec<u32> = processes.iter().map(process::Process::pid).collect();
     let (process_writes, process_reads): (Vec<_>, Vec<_>) =
         processes.into_iter().map(|p| p.split()).unzip();
 
@@ -113,7 +187,11 @@ async fn run_with_options(mut options: options::Options) -> Result<(), failure::
 
     debug!("created terminal");
 
-    let events = read_events(tty_input);
+    if profile_startup {
+        info!("profile-startup: tty setup took {:?}", phase_start.elapsed());
+    }
+
+    let events = read_events(tty_input, config.keybindings.clone());
     let input = await!(run_gui(
         process_reads,
         terminal,
@@ -122,6 +200,25 @@ async fn run_with_options(mut options: options::Options) -> Result<(), failure::
             .map(|args| args.specific)
             .collect::<Vec<_>>(),
         template_placeholder,
+        config.scrollback_lines,
+        pids,
+        config.keybindings,
+        config.color_mode,
+        config.color_depth,
+        config.min_contrast,
+        config.hooks,
+        config.notification_backend,
+        config.event_bus,
+        config.monitor_silence_secs,
+        config.horizontal_overscan,
+        config.scroll_lines,
+        config.ambiguous_wide_chars,
+        config.max_fps,
+        config.word_separators,
+        config.accessibility_events,
+        log_dir,
+        record_cast_dir,
+        profile_startup,
     ))?;
 
     let rest = await!(forward_stdin(process_writes, input))?;
@@ -137,27 +234,88 @@ async fn run_with_options(mut options: options::Options) -> Result<(), failure::
 
 async fn run_gui(
     process_reads: Vec<process::Read>,
-    terminal: tui::Terminal<impl tui::backend::Backend + 'static>,
+    terminal: tui::Terminal<impl tui::backend::Backend + std::io::Write + 'static>,
     user_input: impl futures::stream::Stream<Item = ui::Event, Error = failure::Error>,
     args: Vec<String>,
     template_placeholder: String,
+    scrollback_lines: usize,
+    pids: Vec<u32>,
+    keybindings: keybindings::Keybindings,
+    color_mode: palette::ColorMode,
+    color_depth: palette::ColorDepth,
+    min_contrast: f64,
+    hooks: hooks::Hooks,
+    notification_backend: notify::NotificationBackend,
+    event_bus: event_bus::EventBus,
+    monitor_silence_secs: Option<u64>,
+    horizontal_overscan: usize,
+    scroll_lines: usize,
+    ambiguous_wide_chars: bool,
+    max_fps: u32,
+    word_separators: String,
+    accessibility_events: bool,
+    log_dir: Option<std::path::PathBuf>,
+    record_cast_dir: Option<std::path::PathBuf>,
+    profile_startup: bool,
 ) -> Result<impl futures::Stream<Item = ui::Action, Error = failure::Error>, failure::Error> {
+    use crate::sys;
     use futures::future::Future;
     use futures::stream::Stream;
+    use std::fs;
     use std::sync;
     use std::time;
 
+    let phase_start = time::Instant::now();
+
+    if let Some(ref dir) = log_dir {
+        fs::create_dir_all(dir)?;
+    }
+
+    let cast_size = terminal.size()?;
+    if let Some(ref dir) = record_cast_dir {
+        fs::create_dir_all(dir)?;
+    }
+
     let (outputs, exits): (Vec<_>, Vec<_>) = process_reads
         .into_iter()
         .map(|p| (p.output, p.exit))
         .unzip();
 
-    let output = streams::select_all(
-        outputs
-            .into_iter()
-            .enumerate()
-            .map(|(i, o)| o.map(move |b| ui::Event::ProcessOutput(i, b.freeze()))),
-    );
+    let output = streams::select_all(outputs.into_iter().enumerate().map(|(i, o)| {
+        let mut log_file = log_dir.as_ref().and_then(|dir| {
+            fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(dir.join(format!("{}.log", i)))
+                .map_err(|err| debug!("failed to open pane log file: {}", err))
+                .ok()
+        });
+
+        let mut cast_writer = record_cast_dir.as_ref().and_then(|dir| {
+            cast::CastWriter::create(
+                &dir.join(format!("{}.cast", i)),
+                cast_size.width,
+                cast_size.height,
+            )
+            .map_err(|err| debug!("failed to create pane cast file: {}", err))
+            .ok()
+        });
+
+        o.map(move |b| {
+            if let Some(ref mut file) = log_file {
+                use std::io::Write;
+                if let Err(err) = file.write_all(&b) {
+                    debug!("failed to write pane log: {}", err);
+                }
+            }
+            if let Some(ref mut writer) = cast_writer {
+                if let Err(err) = writer.write_output(&b) {
+                    debug!("failed to write pane cast event: {}", err);
+                }
+            }
+            ui::Event::ProcessOutput(i, b.freeze())
+        })
+    }));
 
     let exit = futures::stream::futures_unordered(
         exits
@@ -166,16 +324,46 @@ async fn run_gui(
             .map(|(i, e)| e.map(move |e| ui::Event::ProcessExit(i, e))),
     );
 
+    let usage_pids = pids.clone();
+    let kill_pids = pids;
     let processes = args.into_iter().map(|arg| ui::ProcessSettings {
         initial_title: format!("{}={}", template_placeholder, arg),
+        scrollback_lines,
+        color_mode,
+        color_depth,
+        min_contrast,
+        horizontal_overscan,
+        scroll_lines,
+        ambiguous_wide_chars,
+        word_separators: word_separators.clone(),
     });
 
-    let mut ui = ui::Ui::new(terminal, processes)?;
+    let silence = monitor_silence_secs.map(time::Duration::from_secs);
+    let mut ui = ui::Ui::new(
+        terminal,
+        processes,
+        keybindings,
+        hooks,
+        notification_backend,
+        event_bus,
+        silence,
+        max_fps,
+        accessibility_events,
+    )?;
+
+    if profile_startup {
+        info!("profile-startup: ui construction took {:?}", phase_start.elapsed());
+    }
+    let phase_start = time::Instant::now();
 
     await!(futures::future::poll_fn(|| tokio_threadpool::blocking(
         || ui.draw()
     )))??;
 
+    if profile_startup {
+        info!("profile-startup: first frame took {:?}", phase_start.elapsed());
+    }
+
     let ui = sync::Arc::new(sync::Mutex::new(ui));
 
     let resize_ui = sync::Arc::clone(&ui);
@@ -189,21 +377,86 @@ async fn run_gui(
         })
         .map_err(failure::Error::from);
 
+    let usage = tokio::timer::Interval::new_interval(time::Duration::from_secs(1))
+        .map_err(failure::Error::from)
+        .and_then(move |_| {
+            let pids = usage_pids.clone();
+            futures::future::poll_fn(move || {
+                tokio_threadpool::blocking(|| {
+                    pids.iter()
+                        .enumerate()
+                        .map(|(index, &pid)| {
+                            let usage = sys::proc_stats::tree_usage(pid).unwrap_or_default();
+                            let has_descendants =
+                                sys::proc_stats::has_descendants(pid).unwrap_or(false);
+                            let foreground_command =
+                                sys::proc_stats::foreground_command(pid).unwrap_or(None);
+                            (index, usage, has_descendants, foreground_command)
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .map_err(failure::Error::from)
+        })
+        .map(|usages| {
+            futures::stream::iter_ok(usages.into_iter().map(
+                |(index, usage, has_descendants, foreground_command)| ui::Event::ProcessUsage {
+                    index,
+                    cpu_ticks: usage.cpu_ticks,
+                    rss_bytes: usage.rss_bytes,
+                    read_bytes: usage.read_bytes,
+                    write_bytes: usage.write_bytes,
+                    has_descendants,
+                    foreground_command,
+                },
+            ))
+        })
+        .flatten();
+
+    let ticks = tokio::timer::Interval::new_interval(time::Duration::from_secs(1))
+        .map_err(failure::Error::from)
+        .map(|_| ui::Event::Tick);
+
+    let config_reloads = config::watch(time::Duration::from_secs(2)).map(|config| {
+        ui::Event::ConfigReloaded {
+            scrollback_lines: config.scrollback_lines,
+        }
+    });
+
     let events = user_input
         .chain(futures::stream::once(Ok(ui::Event::EndOfUserInput)))
         .select(output)
         .select(exit)
         .select(resizes)
+        .select(usage)
+        .select(ticks)
+        .select(config_reloads)
         .take_while(|e| futures::future::ok(*e != ui::Event::EndOfUserInput));
 
     Ok(events
         .and_then(move |event| {
             let event = sync::Arc::new(event);
             let ui = sync::Arc::clone(&ui);
+            let kill_pids = kill_pids.clone();
             futures::future::poll_fn(move || {
                 let event = sync::Arc::clone(&event);
                 let ui = sync::Arc::clone(&ui);
-                tokio_threadpool::blocking(move || ui.lock().unwrap().on_event(&event))
+                let kill_pids = kill_pids.clone();
+                tokio_threadpool::blocking(move || {
+                    let mut actions = ui.lock().unwrap().on_event(&event)?;
+                    actions.retain(|action| match action {
+                        ui::Action::KillPane { index, signal } => {
+                            kill_pane(&kill_pids, *index, *signal);
+                            false
+                        }
+                        ui::Action::OpenUrl { url } => {
+                            open_url(url);
+                            false
+                        }
+                        _ => true,
+                    });
+                    Ok(actions)
+                })
             })
             .map_err(failure::Error::from)
             .and_then(|r| r)
@@ -212,8 +465,43 @@ async fn run_gui(
         .flatten())
 }
 
+/// Open `url` in the user's browser, preferring `$BROWSER` if it's set and falling back to
+/// `xdg-open`.
+fn open_url(url: &str) {
+    let opener = std::env::var("BROWSER").unwrap_or_else(|_| "xdg-open".to_owned());
+    if let Err(err) = std::process::Command::new(&opener).arg(url).spawn() {
+        debug!("failed to open url {} with {}: {}", url, opener, err);
+    }
+}
+
+/// Signal every pid in the process tree rooted at `pids[index]`, not just the direct child.
+fn kill_pane(pids: &[u32], index: usize, signal: libc::c_int) {
+    let pid = match pids.get(index) {
+        Some(&pid) => pid,
+        None => return,
+    };
+
+    match crate::sys::proc_stats::tree_pids(pid) {
+        Ok(tree) => {
+            for pid in tree {
+                #[allow(clippy::cast_possible_wrap)]
+                let ret = unsafe { libc::kill(pid as libc::pid_t, signal) };
+                if ret != 0 {
+                    debug!(
+                        "failed to signal pid {}: {}",
+                        pid,
+                        std::io::Error::last_os_error()
+                    );
+                }
+            }
+        }
+        Err(err) => debug!("failed to enumerate process tree for pid {}: {}", pid, err),
+    }
+}
+
 fn read_events(
     read: impl std::io::Read + Send + 'static,
+    keybindings: keybindings::Keybindings,
 ) -> impl futures::stream::Stream<Item = ui::Event, Error = failure::Error> + Send + 'static {
     use futures::stream::Stream;
     use termion::input::TermReadEventsAndRaw;
@@ -223,9 +511,26 @@ fn read_events(
     let raw_events_stream = streams::blocking_iter_to_stream(
         event_iterator
             .inspect(|e| debug!("received tty event: {:?}", e))
-            .take_while(|e| match e {
-                Ok((termion::event::Event::Key(termion::event::Key::Ctrl('t')), _)) => false,
-                _ => true,
+            .take_while({
+                // The key right after `passthrough` goes to the pane, even the quit binding.
+                let mut passthrough_pending = false;
+                move |e| match e {
+                    Ok((termion::event::Event::Key(key), _)) => {
+                        if passthrough_pending {
+                            passthrough_pending = false;
+                            return true;
+                        }
+                        match keybindings.resolve(*key) {
+                            Some(keybindings::Command::Passthrough) => {
+                                passthrough_pending = true;
+                                true
+                            }
+                            Some(keybindings::Command::Quit) => false,
+                            _ => true,
+                        }
+                    }
+                    _ => true,
+                }
             }),
     )
     .map_err(failure::Error::from);
@@ -252,7 +557,6 @@ async fn forward_stdin(
                 p.input
                     .with_flat_map(move |data| {
                         futures::stream::iter_ok(match data {
-                            ui::Action::ProcessInputAll { data, .. } => Some(data),
                             ui::Action::ProcessInput { data, .. } => Some(data),
                             // TODO: find a way to process other events
                             _ => None,
@@ -272,9 +576,25 @@ async fn forward_stdin(
     Ok(rest.map(|_| ()))
 }
 
+/// Environment variables to export to a spawned pane's command: `MUX` and `MUX_PANE`
+/// unconditionally, the same way tmux always sets `TMUX`/`TMUX_PANE` so a program can tell it's
+/// running inside a pane and which one, plus `MUX_SHELL_INTEGRATION` when
+/// `config::Config::shell_integration` is enabled, plus any `config::Config::locale` overrides.
+fn pane_envs(config: &config::Config, index: usize) -> Vec<(String, String)> {
+    let mut envs = vec![
+        ("MUX".to_owned(), std::process::id().to_string()),
+        ("MUX_PANE".to_owned(), index.to_string()),
+    ];
+    if config.shell_integration {
+        envs.push(("MUX_SHELL_INTEGRATION".to_owned(), "1".to_owned()));
+    }
+    envs.extend(config.locale.envs());
+    envs
+}
+
 async fn create_terminal(
     output: impl std::io::Write,
-) -> Result<tui::Terminal<impl tui::backend::Backend>, failure::Error> {
+) -> Result<tui::Terminal<impl tui::backend::Backend + std::io::Write>, failure::Error> {
     let mouse_terminal = termion::input::MouseTerminal::from(output);
     let alternate_screen_terminal = termion::screen::AlternateScreen::from(mouse_terminal);
     let backend = tui::backend::TermionBackend::new(alternate_screen_terminal);
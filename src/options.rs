@@ -87,6 +87,90 @@ pub struct Options {
     /// Log more verbose messages to the log (defaulting to errors only).
     #[structopt(short = "v", long = "log-verbose", parse(from_occurrences))]
     pub log_verbose: u8,
+
+    /// Write each pane's raw output to DIR/<pane-index>.log, tmux pipe-pane style.
+    #[structopt(long = "log-dir", value_name = "DIR")]
+    pub log_dir: Option<path::PathBuf>,
+
+    /// Also record each pane's timestamped output to DIR/<pane-index>.cast, asciicast v2 format,
+    /// for sharing or replaying with `mux --play` or any other asciicast-compatible player.
+    #[structopt(long = "record-cast", value_name = "DIR")]
+    pub record_cast_dir: Option<path::PathBuf>,
+
+    /// Time each startup phase (config load, process spawn, tty setup, first frame) and log the
+    /// breakdown at info level, for tracking regressions in startup latency. Needs `--log-verbose`
+    /// at least twice (or an existing `info`-or-lower config) to actually show up in the log file,
+    /// same as any other `info!` message - see `run()`'s `fern::Dispatch` setup.
+    #[structopt(long = "profile-startup")]
+    pub profile_startup: bool,
+}
+
+/// Shell named by a `--completions SHELL` flag in `args`, if any, for generating a completion
+/// script.
+///
+/// This is checked against the raw argv instead of being a normal `#[structopt]` field: `command`
+/// above is a required positional argument, so `mux --completions bash` (which has no `COMMAND`
+/// to give it) would otherwise fail to parse before `run()` ever gets a chance to handle the flag
+/// and exit. `--completions` is intentionally left out of `--help`'s usage line for the same
+/// reason - it isn't a normal option of the `COMMAND` mux runs, it's a request to not run one.
+pub fn completions_shell(
+    mut args: impl Iterator<Item = String>,
+) -> Option<structopt::clap::Shell> {
+    while let Some(arg) = args.next() {
+        if arg == "--completions" {
+            return args.next().and_then(|shell| shell.parse().ok());
+        }
+    }
+    None
+}
+
+/// Path passed via `--replay-log PATH`, if any, checked against the raw argv the same way as
+/// `--completions`: replaying a log is a request to not run `COMMAND` at all, so it has to be
+/// found before the required `command` positional would otherwise fail to parse.
+pub fn replay_log_path(mut args: impl Iterator<Item = String>) -> Option<path::PathBuf> {
+    while let Some(arg) = args.next() {
+        if arg == "--replay-log" {
+            return args.next().map(path::PathBuf::from);
+        }
+    }
+    None
+}
+
+/// Pane count passed via `--bench PANES`, if any, checked against the raw argv the same way as
+/// `--completions`/`--replay-log`: running the built-in load generator is a request to not run
+/// `COMMAND` at all, so it has to be found before the required `command` positional would
+/// otherwise fail to parse.
+pub fn bench_pane_count(mut args: impl Iterator<Item = String>) -> Option<usize> {
+    while let Some(arg) = args.next() {
+        if arg == "--bench" {
+            return args.next().and_then(|panes| panes.parse().ok());
+        }
+    }
+    None
+}
+
+/// `--play FILE [--speed N]`, checked against the raw argv the same way as `--completions`/
+/// `--replay-log`/`--bench`: playing back a recording is a request to not run `COMMAND` at all,
+/// so it has to be found before the required `command` positional would otherwise fail to parse.
+///
+/// This is a flag rather than the `mux play FILE` subcommand form other asciicast players use,
+/// to stay consistent with how every other alternate mode in this file is spelled - `command` is
+/// a required positional, so a real subcommand would need restructuring the whole `Options`
+/// parse, not just adding one more pre-scanned flag.
+pub fn play_options(args: impl Iterator<Item = String>) -> Option<(path::PathBuf, f64)> {
+    let args: Vec<String> = args.collect();
+    let path = args
+        .iter()
+        .position(|arg| arg == "--play")
+        .and_then(|i| args.get(i + 1))
+        .map(path::PathBuf::from)?;
+    let speed = args
+        .iter()
+        .position(|arg| arg == "--speed")
+        .and_then(|i| args.get(i + 1))
+        .and_then(|speed| speed.parse().ok())
+        .unwrap_or(1.0);
+    Some((path, speed))
 }
 
 fn parse_delimiter(delimiter: &str) -> Result<u8, failure::Error> {
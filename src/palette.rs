@@ -0,0 +1,233 @@
+//! Optional color remapping for accessibility, applied after the normal ANSI-to-`tui` color
+//! conversion so it only has to deal with final colors, not the ANSI name that produced them.
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ColorMode {
+    Normal,
+    HighContrast,
+    ColorblindSafe,
+}
+
+impl Default for ColorMode {
+    fn default() -> Self {
+        ColorMode::Normal
+    }
+}
+
+impl ColorMode {
+    pub fn remap(self, color: tui::style::Color) -> tui::style::Color {
+        match self {
+            ColorMode::Normal => color,
+            ColorMode::HighContrast => high_contrast(color),
+            ColorMode::ColorblindSafe => colorblind_safe(color),
+        }
+    }
+}
+
+/// Collapse the dim/gray midtones that are hardest to read against either background.
+fn high_contrast(color: tui::style::Color) -> tui::style::Color {
+    use tui::style::Color;
+
+    match color {
+        Color::DarkGray | Color::Gray => Color::White,
+        other => other,
+    }
+}
+
+/// Okabe-Ito palette substitutions for the red/green pair most commonly confused.
+fn colorblind_safe(color: tui::style::Color) -> tui::style::Color {
+    use tui::style::Color;
+
+    match color {
+        Color::Red => Color::Rgb(0xD5, 0x5E, 0x00),
+        Color::LightRed => Color::Rgb(0xE6, 0x9F, 0x00),
+        Color::Green => Color::Rgb(0x00, 0x9E, 0x73),
+        Color::LightGreen => Color::Rgb(0x56, 0xB4, 0xE9),
+        other => other,
+    }
+}
+
+/// Nudge `fg` to black or white (whichever contrasts more) when its WCAG contrast ratio against
+/// `bg` falls below `min_ratio`, kitty/wezterm `min_contrast` style.
+///
+/// Only `Color::Rgb` cells carry enough information to compute a contrast ratio; indexed and
+/// named colors are passed through unchanged, since reproducing their actual on-screen RGB would
+/// mean modeling the terminal's active 256-color palette here too.
+pub fn enforce_min_contrast(
+    min_ratio: f64,
+    fg: tui::style::Color,
+    bg: tui::style::Color,
+) -> tui::style::Color {
+    use tui::style::Color;
+
+    let (fg_rgb, bg_rgb) = match (fg, bg) {
+        (Color::Rgb(fr, fg_, fb), Color::Rgb(br, bg, bb)) => ((fr, fg_, fb), (br, bg, bb)),
+        _ => return fg,
+    };
+
+    if contrast_ratio(fg_rgb, bg_rgb) >= min_ratio {
+        return fg;
+    }
+
+    if contrast_ratio((0xFF, 0xFF, 0xFF), bg_rgb) >= contrast_ratio((0, 0, 0), bg_rgb) {
+        Color::Rgb(0xFF, 0xFF, 0xFF)
+    } else {
+        Color::Rgb(0, 0, 0)
+    }
+}
+
+/// How many distinct colors the outer terminal can actually display, for quantizing the
+/// emulator's truecolor (`Color::Rgb`) cells down to something legacy terminals render
+/// correctly instead of falling back to whatever approximation their own driver picks.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ColorDepth {
+    TrueColor,
+    Palette256,
+    Palette16,
+}
+
+impl Default for ColorDepth {
+    fn default() -> Self {
+        ColorDepth::TrueColor
+    }
+}
+
+impl ColorDepth {
+    /// Quantize `color` to the nearest entry this depth can display. A no-op for `TrueColor` and
+    /// for colors that are already indexed/named rather than `Rgb`, since those already are
+    /// within whatever palette the outer terminal has.
+    pub fn quantize(self, color: tui::style::Color) -> tui::style::Color {
+        let (r, g, b) = match color {
+            tui::style::Color::Rgb(r, g, b) => (r, g, b),
+            other => return other,
+        };
+
+        match self {
+            ColorDepth::TrueColor => color,
+            ColorDepth::Palette256 => quantize_cached(self, r, g, b, quantize_256),
+            ColorDepth::Palette16 => quantize_cached(self, r, g, b, quantize_16),
+        }
+    }
+}
+
+/// Process-wide cache of `(depth, r, g, b) -> quantized color`, since the same handful of colors
+/// (a shell's prompt palette, a compiler's error-red) recur on every redraw of every pane and the
+/// nearest-match search below is a linear scan.
+fn quantize_cached(
+    depth: ColorDepth,
+    r: u8,
+    g: u8,
+    b: u8,
+    quantize: fn(u8, u8, u8) -> tui::style::Color,
+) -> tui::style::Color {
+    use std::sync::{Mutex, OnceLock};
+
+    static CACHE: OnceLock<Mutex<std::collections::HashMap<(ColorDepth, u8, u8, u8), tui::style::Color>>> =
+        OnceLock::new();
+    let cache = CACHE.get_or_init(|| Mutex::new(std::collections::HashMap::new()));
+
+    let mut cache = cache.lock().unwrap();
+    *cache
+        .entry((depth, r, g, b))
+        .or_insert_with(|| quantize(r, g, b))
+}
+
+/// Nearest color in the standard 256-color xterm palette's 6x6x6 color cube and 24-step
+/// grayscale ramp, as an `Indexed` color. The 16 basic colors (indices 0-15) aren't considered.
+fn quantize_256(r: u8, g: u8, b: u8) -> tui::style::Color {
+    fn cube_step(c: u8) -> u8 {
+        // Inverse of the standard xterm cube axis values (0, 95, 135, 175, 215, 255): find the
+        // step whose value is closest to `c`.
+        const STEPS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+        STEPS
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, &v)| (i32::from(v) - i32::from(c)).abs())
+            .map(|(i, _)| i as u8)
+            .unwrap_or(0)
+    }
+
+    let (cr, cg, cb) = (cube_step(r), cube_step(g), cube_step(b));
+    const CUBE_VALUES: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    let cube_color = (
+        CUBE_VALUES[cr as usize],
+        CUBE_VALUES[cg as usize],
+        CUBE_VALUES[cb as usize],
+    );
+    let cube_index = 16 + 36 * cr + 6 * cg + cb;
+
+    let gray_level = ((u32::from(r) + u32::from(g) + u32::from(b)) / 3) as u8;
+    let gray_step = ((i32::from(gray_level) - 3).max(0) / 10).min(23) as u8;
+    let gray_value = 8 + 10 * gray_step;
+    let gray_index = 232 + gray_step;
+
+    let cube_distance = color_distance((r, g, b), cube_color);
+    let gray_distance = color_distance((r, g, b), (gray_value, gray_value, gray_value));
+
+    let index = if gray_distance < cube_distance {
+        gray_index
+    } else {
+        cube_index
+    };
+    tui::style::Color::Indexed(index)
+}
+
+/// Nearest of the 16 basic ANSI colors, by squared Euclidean distance against each color's
+/// standard (non-bold) xterm RGB value.
+fn quantize_16(r: u8, g: u8, b: u8) -> tui::style::Color {
+    use tui::style::Color;
+
+    const BASIC_COLORS: [(Color, (u8, u8, u8)); 16] = [
+        (Color::Black, (0, 0, 0)),
+        (Color::Red, (205, 0, 0)),
+        (Color::Green, (0, 205, 0)),
+        (Color::Yellow, (205, 205, 0)),
+        (Color::Blue, (0, 0, 238)),
+        (Color::Magenta, (205, 0, 205)),
+        (Color::Cyan, (0, 205, 205)),
+        (Color::Gray, (229, 229, 229)),
+        (Color::DarkGray, (127, 127, 127)),
+        (Color::LightRed, (255, 0, 0)),
+        (Color::LightGreen, (0, 255, 0)),
+        (Color::LightYellow, (255, 255, 0)),
+        (Color::LightBlue, (92, 92, 255)),
+        (Color::LightMagenta, (255, 0, 255)),
+        (Color::LightCyan, (0, 255, 255)),
+        (Color::White, (255, 255, 255)),
+    ];
+
+    BASIC_COLORS
+        .iter()
+        .min_by_key(|(_, rgb)| color_distance((r, g, b), *rgb))
+        .map(|(color, _)| *color)
+        .unwrap_or(Color::White)
+}
+
+fn color_distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> i32 {
+    let dr = i32::from(a.0) - i32::from(b.0);
+    let dg = i32::from(a.1) - i32::from(b.1);
+    let db = i32::from(a.2) - i32::from(b.2);
+    dr * dr + dg * dg + db * db
+}
+
+/// WCAG 2.0 contrast ratio between two colors, in `[1.0, 21.0]`.
+fn contrast_ratio(a: (u8, u8, u8), b: (u8, u8, u8)) -> f64 {
+    let (la, lb) = (relative_luminance(a), relative_luminance(b));
+    let (lighter, darker) = if la > lb { (la, lb) } else { (lb, la) };
+    (lighter + 0.05) / (darker + 0.05)
+}
+
+fn relative_luminance((r, g, b): (u8, u8, u8)) -> f64 {
+    fn channel(c: u8) -> f64 {
+        let c = f64::from(c) / 255.0;
+        if c <= 0.03928 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    }
+
+    0.2126 * channel(r) + 0.7152 * channel(g) + 0.0722 * channel(b)
+}
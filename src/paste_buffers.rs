@@ -0,0 +1,68 @@
+//! A stack of named copy/paste buffers, tmux `paste-buffer`/`choose-buffer` style.
+//!
+//! Buffers come from copying a pane's selection, an OSC 52 clipboard write from the program
+//! running in a pane, or an explicit `:set-buffer`. The most recently added one is always "the
+//! top" for a plain paste, but older ones stay around by name or index until evicted.
+
+/// Upper bound on how many buffers are kept at once; oldest is evicted first, same eviction
+/// policy as `Term::keyboard_mode_stack`.
+const MAX_BUFFERS: usize = 32;
+
+pub struct Buffer {
+    pub name: String,
+    pub contents: String,
+}
+
+#[derive(Default)]
+pub struct PasteBuffers {
+    /// Most recently added buffer last, so `top` is just `.last()`.
+    buffers: Vec<Buffer>,
+    /// Source of the next auto-generated name (`buffer0`, `buffer1`, ...); kept separate from
+    /// `buffers.len()` so names stay unique even as old buffers are evicted.
+    next_auto_name: usize,
+}
+
+impl PasteBuffers {
+    /// Add a buffer, naming it `name` or (if `None`) the next `bufferN` in sequence, evicting the
+    /// oldest buffer first if this would grow past `MAX_BUFFERS`.
+    pub fn push(&mut self, name: Option<String>, contents: String) {
+        let name = name.unwrap_or_else(|| {
+            let name = format!("buffer{}", self.next_auto_name);
+            self.next_auto_name += 1;
+            name
+        });
+
+        if self.buffers.len() >= MAX_BUFFERS {
+            self.buffers.remove(0);
+        }
+        self.buffers.push(Buffer { name, contents });
+    }
+
+    /// Most recently added buffer, if any.
+    pub fn top(&self) -> Option<&Buffer> {
+        self.buffers.last()
+    }
+
+    /// Buffer at `index`, oldest first (so `0` is the oldest surviving buffer, not the top).
+    pub fn get(&self, index: usize) -> Option<&Buffer> {
+        self.buffers.get(index)
+    }
+
+    /// Most recently added buffer named `name`, if any.
+    pub fn named(&self, name: &str) -> Option<&Buffer> {
+        self.buffers.iter().rev().find(|buffer| buffer.name == name)
+    }
+
+    /// All buffers, oldest first, for the `choose-buffer` overlay to list.
+    pub fn iter(&self) -> impl Iterator<Item = &Buffer> {
+        self.buffers.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.buffers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffers.is_empty()
+    }
+}
@@ -0,0 +1,92 @@
+//! `mux --play FILE.cast [--speed N]` replays an asciicast v2 recording (see `cast.rs`) by
+//! writing its recorded output straight to stdout, paced to match the original timing (scaled by
+//! `--speed`, default `1.0`) - the same approach asciinema's own player uses, letting whatever
+//! terminal `mux --play` is run in do the actual rendering rather than reimplementing one here.
+//! This mirrors `replay.rs`'s choice not to drive `ui::Ui` for a similar reason: `Ui` is built
+//! around live panes with running processes, not a static recording.
+
+use std::io::{BufRead, Write};
+use std::thread;
+use std::time::Duration;
+
+pub fn run(path: &std::path::Path, speed: f64) -> Result<(), failure::Error> {
+    let file = std::fs::File::open(path)?;
+    let mut lines = std::io::BufReader::new(file).lines();
+
+    // First line is the asciicast header; nothing here needs its width/height/timestamp, since
+    // stdout is already sized the way the user's own terminal is.
+    lines
+        .next()
+        .ok_or_else(|| failure::err_msg("empty cast file"))??;
+
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    let mut previous = 0.0;
+
+    for line in lines {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let (time, kind, data) = parse_event(&line)
+            .ok_or_else(|| failure::err_msg(format!("malformed cast event: {}", line)))?;
+
+        if kind == "o" {
+            let delay = (time - previous).max(0.0) / speed;
+            if delay > 0.0 {
+                thread::sleep(Duration::from_secs_f64(delay));
+            }
+            out.write_all(data.as_bytes())?;
+            out.flush()?;
+        }
+
+        previous = time;
+    }
+
+    Ok(())
+}
+
+/// Parse one `[time, "type", "data"]` asciicast event line, without a JSON dependency.
+fn parse_event(line: &str) -> Option<(f64, String, String)> {
+    let inner = line.trim().strip_prefix('[')?.strip_suffix(']')?;
+
+    let comma = inner.find(',')?;
+    let time: f64 = inner[..comma].trim().parse().ok()?;
+
+    let (kind, rest) = parse_json_string(inner[comma + 1..].trim_start())?;
+    let (data, _) = parse_json_string(rest.trim_start().strip_prefix(',')?.trim_start())?;
+
+    Some((time, kind, data))
+}
+
+/// Parse a JSON string literal at the start of `s`, returning the unescaped value and whatever
+/// follows the closing quote.
+fn parse_json_string(s: &str) -> Option<(String, &str)> {
+    let s = s.strip_prefix('"')?;
+    let mut out = String::new();
+    let mut chars = s.char_indices();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((out, &s[i + 1..])),
+            '\\' => match chars.next()?.1 {
+                'n' => out.push('\n'),
+                'r' => out.push('\r'),
+                't' => out.push('\t'),
+                '"' => out.push('"'),
+                '\\' => out.push('\\'),
+                '/' => out.push('/'),
+                'u' => {
+                    let hex: String = (0..4).filter_map(|_| chars.next().map(|(_, c)| c)).collect();
+                    let code = u32::from_str_radix(&hex, 16).ok()?;
+                    out.push(char::from_u32(code)?);
+                }
+                other => out.push(other),
+            },
+            c => out.push(c),
+        }
+    }
+
+    None
+}
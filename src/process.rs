@@ -1,7 +1,22 @@
 use std::ffi;
 use std::io;
+use std::os::unix::process::CommandExt as StdCommandExt;
+use std::path;
 use std::process;
 
+use crate::rlimits::Rlimits;
+
+/// Everything needed to spawn a pane's command, pulled out of `Process::spawn`'s argument list
+/// so callers building it up piecemeal (e.g. `:respawn-pane`, which starts from an existing
+/// pane's settings and overrides just the command) have somewhere to hold it.
+pub struct SpawnOptions<'a> {
+    pub command: &'a ffi::OsStr,
+    pub args: &'a [String],
+    pub cwd: Option<&'a path::Path>,
+    pub envs: &'a [(String, String)],
+    pub rlimits: Rlimits,
+}
+
 pub struct Process {
     pub input: Input,
     pub output: Output,
@@ -41,18 +56,24 @@ pub struct Exit {
 }
 
 impl Process {
-    pub fn spawn(
-        command: impl AsRef<ffi::OsStr>,
-        args: &[impl AsRef<ffi::OsStr>],
-    ) -> Result<Self, failure::Error> {
+    pub fn spawn(options: SpawnOptions) -> Result<Self, failure::Error> {
         use tokio::io::AsyncRead;
         use tokio_pty_process::CommandExt;
 
         let pty = tokio_pty_process::AsyncPtyMaster::open()?;
 
-        let child = process::Command::new(command)
-            .args(args)
-            .spawn_pty_async(&pty)?;
+        let mut command = process::Command::new(options.command);
+        command
+            .args(options.args)
+            .envs(options.envs.iter().map(|(key, value)| (key.as_str(), value.as_str())));
+        if let Some(cwd) = options.cwd {
+            command.current_dir(cwd);
+        }
+        let rlimits = options.rlimits;
+        unsafe {
+            command.before_exec(move || rlimits.apply());
+        }
+        let child = command.spawn_pty_async(&pty)?;
 
         let (output, input) = pty.split();
 
@@ -73,6 +94,10 @@ impl Process {
         })
     }
 
+    pub fn pid(&self) -> u32 {
+        self.exit.pid()
+    }
+
     pub fn split(self) -> (Write, Read) {
         let Self {
             input,
@@ -114,6 +139,10 @@ impl Exit {
     fn new(future: tokio_pty_process::Child) -> Self {
         Self { future }
     }
+
+    pub fn pid(&self) -> u32 {
+        self.future.id()
+    }
 }
 
 impl futures::sink::Sink for Input {
@@ -0,0 +1,64 @@
+//! `mux --replay-log PATH` feeds a `--log-dir` pane log back through a fresh terminal emulator
+//! and prints the resulting screen, for triaging a rendering bug report without needing to
+//! reproduce it live.
+//!
+//! The log is already a deterministic, sequence-numbered record: it's the pane's raw pty output,
+//! written in the order it arrived, so a byte offset into the file *is* the sequence number of
+//! whatever character or escape sequence lives there. To bisect which one corrupted the screen,
+//! truncate the file at different offsets and replay each truncation; the first one whose
+//! printed screen looks wrong brackets the responsible byte range.
+
+use std::io::Read;
+
+/// Size of the scratch terminal bytes are replayed into. This only needs to be big enough that
+/// typical captures don't wrap lines differently than the pane they were captured from; it isn't
+/// read back from the log itself, since `--log-dir` doesn't record the pane's dimensions.
+const REPLAY_SIZE: terminal_emulator::term::SizeInfo = terminal_emulator::term::SizeInfo {
+    width: 80.0,
+    height: 24.0,
+    cell_width: 1.0,
+    cell_height: 1.0,
+    padding_x: 0.0,
+    padding_y: 0.0,
+    dpr: 1.0,
+};
+
+/// Replay the pane log at `path` and print the resulting screen to stdout.
+pub fn run(path: &std::path::Path) -> Result<(), failure::Error> {
+    let mut bytes = Vec::new();
+    std::fs::File::open(path)?.read_to_end(&mut bytes)?;
+
+    let mut term = terminal_emulator::term::Term::new(REPLAY_SIZE)?;
+    let mut processor = terminal_emulator::Processor::new();
+    let mut sink = std::io::sink();
+
+    for byte in bytes {
+        processor.advance(&mut term, byte, &mut sink);
+    }
+
+    for line in screen_lines(&term) {
+        println!("{}", line);
+    }
+
+    Ok(())
+}
+
+/// Render the terminal's visible cells into one string per line, trimmed of trailing blanks.
+fn screen_lines(term: &terminal_emulator::term::Term) -> Vec<String> {
+    use std::collections::BTreeMap;
+
+    let mut cells: BTreeMap<(usize, usize), char> = BTreeMap::new();
+    for cell in term.renderable_cells() {
+        cells.insert((cell.line.0, cell.column.0), cell.chars[0]);
+    }
+
+    let mut lines: BTreeMap<usize, String> = BTreeMap::new();
+    for ((line, _column), ch) in cells {
+        lines.entry(line).or_insert_with(String::new).push(ch);
+    }
+
+    lines
+        .into_iter()
+        .map(|(_, line)| line.trim_end().to_owned())
+        .collect()
+}
@@ -0,0 +1,58 @@
+//! Resource limits applied to every spawned pane, to contain a runaway job (a fork bomb, a
+//! build that eats all available memory, a process that leaks file descriptors) without `mux`
+//! itself having to notice and kill it.
+//!
+//! This only covers `setrlimit(2)`, not a user namespace or cgroup: both need root (or a
+//! delegated cgroup subtree) to set up, which a pane-spawning tool running as an ordinary user
+//! can't assume it has. See the "Out of scope" section of the README for the rest of that
+//! reasoning.
+
+use std::io;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, serde::Deserialize)]
+#[serde(default)]
+pub struct Rlimits {
+    /// `RLIMIT_CPU`: total CPU seconds a pane's process (and its children, since limits are
+    /// inherited across `fork`/`exec`) may consume before the kernel sends it `SIGXCPU`.
+    pub cpu_seconds: Option<u64>,
+
+    /// `RLIMIT_AS`: maximum size in bytes of a pane's process's virtual address space.
+    pub memory_bytes: Option<u64>,
+
+    /// `RLIMIT_NOFILE`: maximum number of file descriptors a pane's process may have open at
+    /// once.
+    pub open_files: Option<u64>,
+}
+
+impl Rlimits {
+    /// Apply every limit that's set to the calling process, as `rlim_cur` and `rlim_max` both.
+    ///
+    /// Safe to call, but only actually safe to use this way from a `pre_exec`/`before_exec`
+    /// closure: it mutates process-wide kernel state, so calling it anywhere else would limit
+    /// `mux` itself rather than the pane about to be exec'd into.
+    pub fn apply(&self) -> io::Result<()> {
+        if let Some(cpu_seconds) = self.cpu_seconds {
+            set(libc::RLIMIT_CPU, cpu_seconds)?;
+        }
+        if let Some(memory_bytes) = self.memory_bytes {
+            set(libc::RLIMIT_AS, memory_bytes)?;
+        }
+        if let Some(open_files) = self.open_files {
+            set(libc::RLIMIT_NOFILE, open_files)?;
+        }
+        Ok(())
+    }
+}
+
+fn set(resource: libc::c_int, value: u64) -> io::Result<()> {
+    let limit = libc::rlimit {
+        rlim_cur: value as libc::rlim_t,
+        rlim_max: value as libc::rlim_t,
+    };
+    let result = unsafe { libc::setrlimit(resource, &limit) };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
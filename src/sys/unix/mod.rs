@@ -5,6 +5,7 @@ use std::io;
 pub use self::libc::termios as Termios;
 
 pub mod attr;
+pub mod proc_stats;
 pub mod tty;
 
 // Support functions for converting libc return values to io errors {
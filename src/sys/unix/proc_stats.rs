@@ -0,0 +1,174 @@
+//! CPU/IO usage attribution for a pane's process tree, via `/proc`.
+
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Usage {
+    /// Total CPU time (user + system), in clock ticks (see `sysconf(_SC_CLK_TCK)`).
+    pub cpu_ticks: u64,
+    /// Resident set size, in bytes.
+    pub rss_bytes: u64,
+    /// Bytes read from storage, per `/proc/[pid]/io`.
+    pub read_bytes: u64,
+    /// Bytes written to storage, per `/proc/[pid]/io`.
+    pub write_bytes: u64,
+}
+
+impl Usage {
+    fn add(&mut self, other: Usage) {
+        self.cpu_ticks += other.cpu_ticks;
+        self.rss_bytes += other.rss_bytes;
+        self.read_bytes += other.read_bytes;
+        self.write_bytes += other.write_bytes;
+    }
+}
+
+/// Sum of `usage_of` over `root_pid` and all of its descendants.
+///
+/// Processes that have already exited by the time they're visited are silently skipped, since
+/// that's a normal race rather than an error.
+pub fn tree_usage(root_pid: u32) -> io::Result<Usage> {
+    let mut total = Usage::default();
+    let mut seen = HashSet::new();
+    let mut stack = vec![root_pid];
+
+    while let Some(pid) = stack.pop() {
+        if !seen.insert(pid) {
+            continue;
+        }
+
+        if let Ok(usage) = usage_of(pid) {
+            total.add(usage);
+        }
+
+        stack.extend(children_of(pid)?);
+    }
+
+    Ok(total)
+}
+
+fn usage_of(pid: u32) -> io::Result<Usage> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid))?;
+    // Fields after the (possibly space-containing) comm are whitespace-separated; comm is
+    // wrapped in parentheses, so split after its closing paren.
+    let after_comm = stat.rsplit(')').next().unwrap_or("");
+    let fields: Vec<&str> = after_comm.split_whitespace().collect();
+
+    // utime and stime are fields 14 and 15 (1-indexed overall, i.e. indices 11 and 12 here,
+    // since `fields` starts at field 3 of the original record).
+    let utime: u64 = fields.get(11).and_then(|f| f.parse().ok()).unwrap_or(0);
+    let stime: u64 = fields.get(12).and_then(|f| f.parse().ok()).unwrap_or(0);
+
+    let status = fs::read_to_string(format!("/proc/{}/status", pid))?;
+    let rss_bytes = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))
+        .and_then(|rest| rest.trim().split_whitespace().next())
+        .and_then(|kb| kb.parse::<u64>().ok())
+        .map_or(0, |kb| kb * 1024);
+
+    let (read_bytes, write_bytes) = fs::read_to_string(format!("/proc/{}/io", pid))
+        .map(|io| {
+            let mut read_bytes = 0;
+            let mut write_bytes = 0;
+            for line in io.lines() {
+                if let Some(rest) = line.strip_prefix("read_bytes:") {
+                    read_bytes = rest.trim().parse().unwrap_or(0);
+                } else if let Some(rest) = line.strip_prefix("write_bytes:") {
+                    write_bytes = rest.trim().parse().unwrap_or(0);
+                }
+            }
+            (read_bytes, write_bytes)
+        })
+        // Unreadable without privileges on some systems; treat as zero rather than failing.
+        .unwrap_or((0, 0));
+
+    Ok(Usage {
+        cpu_ticks: utime + stime,
+        rss_bytes,
+        read_bytes,
+        write_bytes,
+    })
+}
+
+/// All pids in the process tree rooted at `root_pid`, including `root_pid` itself.
+pub fn tree_pids(root_pid: u32) -> io::Result<Vec<u32>> {
+    let mut pids = Vec::new();
+    let mut seen = HashSet::new();
+    let mut stack = vec![root_pid];
+
+    while let Some(pid) = stack.pop() {
+        if !seen.insert(pid) {
+            continue;
+        }
+
+        pids.push(pid);
+        stack.extend(children_of(pid)?);
+    }
+
+    Ok(pids)
+}
+
+/// Whether `root_pid` currently has any live descendant processes, at any depth.
+///
+/// Used to decide whether closing a pane needs confirmation: a bare shell with nothing left
+/// running under it can be killed outright, but a shell that spawned a long-running job
+/// shouldn't be torn down silently.
+pub fn has_descendants(root_pid: u32) -> io::Result<bool> {
+    Ok(tree_pids(root_pid)?.len() > 1)
+}
+
+/// Name of the process most likely to be the pane's active foreground command.
+///
+/// There's no cheap, privilege-free way to read the controlling terminal's foreground process
+/// group from here (that's `tcgetpgrp` on the pty fd, which this polling loop doesn't have), so
+/// this approximates it: the highest-pid leaf (childless) process in the tree is usually whatever
+/// the shell most recently exec'd, which is good enough for a status display even if it's
+/// occasionally wrong right after a fork.
+pub fn foreground_command(root_pid: u32) -> io::Result<Option<String>> {
+    let pids = tree_pids(root_pid)?;
+    let mut leaves = Vec::new();
+    for &pid in &pids {
+        if children_of(pid)?.is_empty() {
+            leaves.push(pid);
+        }
+    }
+
+    let leaf = match leaves.into_iter().max() {
+        Some(pid) => pid,
+        None => return Ok(None),
+    };
+
+    let comm = fs::read_to_string(format!("/proc/{}/comm", leaf))?;
+    Ok(Some(comm.trim().to_owned()))
+}
+
+fn children_of(pid: u32) -> io::Result<Vec<u32>> {
+    let mut children = Vec::new();
+
+    for entry in fs::read_dir("/proc")? {
+        let entry = entry?;
+        let candidate: u32 = match entry.file_name().to_str().and_then(|n| n.parse().ok()) {
+            Some(pid) => pid,
+            None => continue,
+        };
+
+        let stat = match fs::read_to_string(format!("/proc/{}/stat", candidate)) {
+            Ok(stat) => stat,
+            Err(_) => continue,
+        };
+        let ppid: Option<u32> = stat
+            .rsplit(')')
+            .next()
+            .and_then(|rest| rest.split_whitespace().nth(1))
+            .and_then(|f| f.parse().ok());
+
+        if ppid == Some(pid) {
+            children.push(candidate);
+        }
+    }
+
+    Ok(children)
+}
@@ -0,0 +1,70 @@
+//! Rolling keystroke-to-echo latency tracking for a single pane.
+//!
+//! `ProcessState` records an `Instant` every time user input is sent to its pane and matches it
+//! against the next byte of pty output that comes back, round-trip-time style. There's no way to
+//! tell *which* keystroke a given output byte is answering, so this only approximates end-to-end
+//! latency for roughly one-keystroke-per-echo interactive use (a shell prompt echoing what was
+//! typed), not bulk output from a running command.
+//!
+//! `mux` has no daemon, socket, or HTTP surface to query from outside the process (it's a single
+//! short-lived TUI), so there's no "stats endpoint" for these percentiles to be served from;
+//! they're surfaced the same way every other ad hoc report in this UI is, via the `:show-latency`
+//! command setting `status_message`.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Unanswered keystrokes older than this many are dropped rather than kept forever, so piping a
+/// large paste or input file into a pane that never echoes it back doesn't grow this unbounded.
+const MAX_PENDING: usize = 64;
+
+/// How many recent round trips percentiles are computed over.
+const WINDOW: usize = 256;
+
+#[derive(Default)]
+pub(crate) struct LatencyTracker {
+    pending: VecDeque<Instant>,
+    samples: VecDeque<Duration>,
+}
+
+impl LatencyTracker {
+    /// Record that user input was just sent to this pane.
+    pub(crate) fn record_keystroke(&mut self) {
+        if self.pending.len() >= MAX_PENDING {
+            self.pending.pop_front();
+        }
+        self.pending.push_back(Instant::now());
+    }
+
+    /// Record that this pane just produced output, matching it against the oldest unanswered
+    /// keystroke, if any.
+    pub(crate) fn record_echo(&mut self) {
+        let sent = match self.pending.pop_front() {
+            Some(sent) => sent,
+            None => return,
+        };
+
+        if self.samples.len() >= WINDOW {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sent.elapsed());
+    }
+
+    /// `p50`/`p90`/`p99` latency over the current window, or `None` if there aren't any samples
+    /// yet.
+    pub(crate) fn percentiles(&self) -> Option<(Duration, Duration, Duration)> {
+        if self.samples.is_empty() {
+            return None;
+        }
+
+        let mut sorted: Vec<Duration> = self.samples.iter().copied().collect();
+        sorted.sort_unstable();
+
+        let at = |p: f64| sorted[(((sorted.len() - 1) as f64) * p).round() as usize];
+        Some((at(0.50), at(0.90), at(0.99)))
+    }
+
+    pub(crate) fn sample_count(&self) -> usize {
+        self.samples.len()
+    }
+}
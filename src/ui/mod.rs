@@ -1,3 +1,4 @@
+mod latency;
 mod vertical_tabs;
 
 pub struct Ui<B>
@@ -7,6 +8,11 @@ where
     state: State,
     terminal: tui::Terminal<B>,
     last_size: tui::layout::Rect,
+    /// Minimum gap between redraws triggered by background (non-selected-pane) events, from
+    /// `Config::max_fps`. `Duration::default()` (zero) disables the cap.
+    min_frame_interval: std::time::Duration,
+    /// When the last frame was actually painted, for `min_frame_interval` to measure against.
+    last_frame: std::time::Instant,
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -15,7 +21,25 @@ pub enum Event {
     EndOfUserInput,
     ProcessOutput(usize, bytes::Bytes),
     ProcessExit(usize, std::process::ExitStatus),
+    ProcessUsage {
+        index: usize,
+        cpu_ticks: u64,
+        rss_bytes: u64,
+        read_bytes: u64,
+        write_bytes: u64,
+        has_descendants: bool,
+        /// Name of the process believed to be running in the foreground, per
+        /// `sys::proc_stats::foreground_command`.
+        foreground_command: Option<String>,
+    },
     Resized,
+    /// Fired once a second so panes can be checked for having gone silent.
+    Tick,
+    /// The config file's contents changed since the last time it was read (see `config::watch`).
+    /// Only `scrollback_lines` is applied to already-spawned panes; everything else in `Config`
+    /// is either baked into state that isn't live-reloadable (keybindings, hooks, ...) or a
+    /// per-process setting that was only ever read once, at spawn time.
+    ConfigReloaded { scrollback_lines: usize },
 }
 
 #[derive(Clone, Debug)]
@@ -24,25 +48,114 @@ pub enum Action {
         index: usize,
         data: bytes::Bytes,
     },
-    ProcessInputAll {
-        data: bytes::Bytes,
-    },
     #[allow(dead_code)]
     ProcessTermResize {
         index: usize,
         width: u16,
         height: u16,
     },
+    /// Send `signal` to every pid in the selected pane's process tree.
+    KillPane { index: usize, signal: libc::c_int },
+    /// Open a URL or path clicked in a pane's grid in the user's browser/opener.
+    OpenUrl { url: String },
 }
 
 pub struct ProcessSettings {
     pub initial_title: String,
+    pub scrollback_lines: usize,
+    pub color_mode: crate::palette::ColorMode,
+    pub color_depth: crate::palette::ColorDepth,
+    pub min_contrast: f64,
+    pub horizontal_overscan: usize,
+    pub scroll_lines: usize,
+    pub ambiguous_wide_chars: bool,
+    pub word_separators: String,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Usage {
+    cpu_ticks: u64,
+    rss_bytes: u64,
+    read_bytes: u64,
+    write_bytes: u64,
 }
 
 struct State {
     processes: Vec<ProcessState>,
     selected: usize,
     scroll: usize,
+    keybindings: crate::keybindings::Keybindings,
+    /// Contents of the `:` command-line prompt, when open.
+    command_line: Option<String>,
+    /// Feedback from the last `:`-command, shown in the prompt row once it's closed.
+    status_message: Option<String>,
+    /// Copy/paste buffer stack, from pane selections, OSC 52, or `:set-buffer`.
+    paste_buffers: crate::paste_buffers::PasteBuffers,
+    /// Index into `paste_buffers` highlighted by the `choose-buffer` overlay, when open.
+    choosing_buffer: Option<usize>,
+    /// Full-screen `choose-pane` overlay, when open.
+    pane_chooser: Option<PaneChooser>,
+    /// Full-screen `filter-pane` overlay, when open.
+    scrollback_filter: Option<ScrollbackFilter>,
+    /// `Action`s queued by the last `:`-command, collected by `Ui::on_event`.
+    pending_actions: Vec<Action>,
+    /// Whether typed input goes to every pane at once (the default, since `mux` is built around
+    /// driving identical commands in parallel) or just the selected one.
+    sync_input: bool,
+    hooks: crate::hooks::Hooks,
+    notifications: crate::notify::NotificationBackend,
+    event_bus: crate::event_bus::EventBus,
+    /// Silence threshold from `Config::monitor_silence_secs`; `None` disables the check.
+    silence: Option<std::time::Duration>,
+    /// Whether the selected pane's title should be mirrored onto the outer terminal's own
+    /// window/tab title, set via `:propagate-title`. Off by default: rewriting the title of the
+    /// terminal mux itself is running in is disruptive enough that it should be opt-in.
+    propagate_title: bool,
+    /// The last title `Ui::propagate_title` wrote to the outer terminal, so it only re-emits the
+    /// OSC 0 sequence when the selected pane's title actually changes.
+    last_propagated_title: Option<String>,
+    /// Set by `Command::Passthrough`: the next key event is forwarded straight to the selected
+    /// pane instead of being resolved against `keybindings`, even if it's bound to a command.
+    passthrough_pending: bool,
+    /// Full-screen `:timeline` overlay, showing the selected pane's `activity_history` as a
+    /// sparkline, when open.
+    showing_timeline: bool,
+    /// Whether to publish `LineChanged`/`CursorMoved`/`ScreenCleared` events to `event_bus`,
+    /// from `Config::accessibility_events`.
+    accessibility_events: bool,
+    /// Commands queued by `:schedule`/`:schedule-every`, checked once a second on `Event::Tick`.
+    /// Lives only as long as this process does, same as `ProcessState::marks` or anything else
+    /// kept in memory here - there's no session to persist it across, just this one.
+    scheduled: Vec<ScheduledCommand>,
+}
+
+/// A `:`-command queued to run later by `:schedule`/`:schedule-every`.
+struct ScheduledCommand {
+    run_at: std::time::Instant,
+    /// Set by `:schedule-every`: re-queued for another `interval` from now every time it runs,
+    /// instead of being dropped after running once.
+    interval: Option<std::time::Duration>,
+    command: String,
+}
+
+/// State of the `choose-pane` overlay: tmux `choose-tree` flattened to mux's single level of
+/// panes, since there are no sessions or windows here to nest it under.
+struct PaneChooser {
+    /// Typed filter text; only panes whose title or status line contain it (case-insensitively)
+    /// are shown.
+    query: String,
+    /// Index into the *filtered* list of panes, not into `processes` directly.
+    selected: usize,
+}
+
+/// State of the `filter-pane` overlay: an fzf-style incremental filter, shaped like
+/// `PaneChooser`'s query/selected-index pair, but over the selected pane's full scrollback
+/// (`Term::scrollback_lines`) instead of the flat list of panes.
+struct ScrollbackFilter {
+    /// Typed filter text; only lines containing it (case-insensitively) are shown.
+    query: String,
+    /// Index into the *filtered* list of lines, not into the full unfiltered scrollback.
+    selected: usize,
 }
 
 struct ProcessState {
@@ -51,27 +164,121 @@ struct ProcessState {
     title: String,
     exit_status: Option<std::process::ExitStatus>,
     input: Vec<u8>,
+    usage: Usage,
+    has_descendants: bool,
+    /// Name of the process believed to be running in the foreground, from `/proc` polling.
+    foreground_command: Option<String>,
+    /// First non-blank on-screen line, cached on each write so `tab_title` doesn't need to
+    /// rescan the grid every frame.
+    preview: String,
+    color_mode: crate::palette::ColorMode,
+    color_depth: crate::palette::ColorDepth,
+    min_contrast: f64,
+    /// Extra columns beyond the visible width that the grid was given, from
+    /// `Config::horizontal_overscan`; clamps how far `pan` can move `hscroll`.
+    horizontal_overscan: usize,
+    /// Columns scrolled past the left edge of the pane, for inspecting wide output that was
+    /// written off-screen while DECAWM (line wrap) is off.
+    hscroll: usize,
+    /// Lines scrolled per mouse wheel tick, from `Config::scroll_lines`.
+    scroll_lines: usize,
+    /// Set when the pane has rung the bell since it was last selected.
+    bell: bool,
+    /// Set when the pane has produced output since it was last selected, tmux
+    /// `monitor-activity` style.
+    activity: bool,
+    /// Set once `last_output` has been idle for longer than the configured silence threshold,
+    /// tmux `monitor-silence` style. Cleared as soon as more output arrives.
+    silent: bool,
+    last_output: std::time::Instant,
+    /// URL/path clicked in the grid, waiting to be opened by the main loop.
+    pending_url: Option<String>,
+    /// Rolling keystroke-to-echo latency samples, reported by `:show-latency`.
+    latency: latency::LatencyTracker,
+    /// When set, all keyboard/paste input destined for this pane (including `sync-input`
+    /// broadcasts) is dropped before it reaches the pty, for tail-follow log panes where an
+    /// accidental keystroke shouldn't reach the process. Toggled with `:read-only`.
+    read_only: bool,
+    /// Set on panes created by `:clone-pane`, which have no pty or pid behind them: `kill-pane`
+    /// drops them from `processes` directly instead of signaling a (nonexistent) process, and
+    /// `:clone-pane` itself can still be run again on one to snapshot it a second time.
+    is_clone: bool,
+    /// Output bytes/second for the last `ACTIVITY_HISTORY_LEN` seconds, oldest first, folded in
+    /// by `record_activity_tick` on every `Event::Tick`; feeds the `:timeline` sparkline so a
+    /// burst of output (or a long gap) is visible at a glance instead of having to scroll back
+    /// through the grid to find it.
+    activity_history: std::collections::VecDeque<u64>,
+    /// Output bytes received since the last `record_activity_tick` call.
+    bytes_since_tick: u64,
+    /// Each visible line's text as of the last `accessibility_changes` call, for diffing against
+    /// the current render. Empty until the first call, so that call always reports every
+    /// non-blank line rather than nothing.
+    accessibility_lines: Vec<String>,
+    /// Cursor `(line, col)` as of the last `accessibility_changes` call.
+    accessibility_cursor: Option<(usize, usize)>,
+    /// Command spawned by `:pipe-pane`, if any, that a copy of this pane's raw output is being
+    /// teed to. Cleared (closing its stdin) on an explicit `:pipe-pane` with no argument, or as
+    /// soon as a write to it fails, e.g. because the command already exited.
+    pipe: Option<std::process::ChildStdin>,
+    /// Buffer lines tagged by `:mark`, oldest first, for `:next-mark`/`:previous-mark` to step
+    /// between - a user-placed analogue of `Term`'s own `prompt_marks`, since "interesting
+    /// point in this log" isn't something a shell's OSC 133 integration can know about.
+    marks: Vec<(usize, Option<String>)>,
+    /// Set when the pane has asked for a desktop notification (OSC 9/777;notify) since it was
+    /// last selected, for the tab list to flag it the same way it flags a bell, separately from
+    /// `notifications`/`hooks.on_notify` actually delivering it. Cleared by `clear_activity`.
+    notified: bool,
 }
 
+/// Length of `ProcessState::activity_history`: two minutes of one-second buckets, enough to spot
+/// "it went quiet overnight" without keeping unbounded history per pane.
+const ACTIVITY_HISTORY_LEN: usize = 120;
+
+/// Upper bound on `ProcessState::marks`, the same cap `Term::prompt_marks` uses for the same
+/// reason: oldest-first history of something a long-running pane could otherwise add forever.
+const MAX_MARKS: usize = 64;
+
 impl<B> Ui<B>
 where
-    B: tui::backend::Backend + 'static,
+    B: tui::backend::Backend + std::io::Write + 'static,
 {
     pub fn new(
         terminal: tui::Terminal<B>,
         processes: impl IntoIterator<Item = ProcessSettings>,
+        keybindings: crate::keybindings::Keybindings,
+        hooks: crate::hooks::Hooks,
+        notifications: crate::notify::NotificationBackend,
+        event_bus: crate::event_bus::EventBus,
+        silence: Option<std::time::Duration>,
+        max_fps: u32,
+        accessibility_events: bool,
     ) -> Result<Self, failure::Error> {
         let processes = processes
             .into_iter()
             .map(ProcessState::from_settings)
-            .collect();
-        let state = State::new(processes);
+            .collect::<Result<Vec<_>, failure::Error>>()?;
+        let state = State::new(
+            processes,
+            keybindings,
+            hooks,
+            notifications,
+            event_bus,
+            silence,
+            accessibility_events,
+        );
         let last_size = terminal.size()?;
+        let min_frame_interval = if max_fps == 0 {
+            std::time::Duration::default()
+        } else {
+            std::time::Duration::from_millis(1000 / u64::from(max_fps))
+        };
 
         Ok(Self {
             state,
             terminal,
             last_size,
+            min_frame_interval,
+            last_frame: std::time::Instant::now(),
         })
     }
 
@@ -86,44 +293,95 @@ where
     }
 
     pub fn on_event(&mut self, event: &Event) -> Result<Vec<Action>, failure::Error> {
-        let mut process_input_all = None;
-        let process_input_all_ref = &mut process_input_all;
+        // Apply the event to `state` up front, whether or not this call ends up painting a
+        // frame: rate-limiting how often `mux` redraws the tty should never mean dropping pty
+        // bytes or input on the floor, only how eagerly they're reflected on screen.
+        let mut needs_prompt_frame = false;
+        match event {
+            Event::ProcessOutput(idx, data) => self.state.on_data(*idx, data.clone()),
+            Event::ProcessExit(idx, status) => {
+                self.state.on_exit(*idx, *status);
+                needs_prompt_frame = true;
+            }
+            Event::ProcessUsage {
+                index,
+                cpu_ticks,
+                rss_bytes,
+                read_bytes,
+                write_bytes,
+                has_descendants,
+                foreground_command,
+            } => self.state.on_usage(
+                *index,
+                *cpu_ticks,
+                *rss_bytes,
+                *read_bytes,
+                *write_bytes,
+                *has_descendants,
+                foreground_command.clone(),
+            ),
+            Event::Tick => self.state.on_tick(),
+            Event::UserInput(..) => needs_prompt_frame = true,
+            Event::Resized => needs_prompt_frame = true,
+            Event::EndOfUserInput => {}
+            Event::ConfigReloaded { scrollback_lines } => {
+                self.state.set_scrollback_lines(*scrollback_lines);
+            }
+        }
+
+        // Output on the pane that's actually visible (the selected one) always renders
+        // immediately - that's the whole point of a frame-rate cap being about CPU, not about
+        // making the foreground pane feel laggy. Everything else is coalesced to
+        // `min_frame_interval`, so a noisy background pane doesn't cost a full redraw per chunk.
+        let is_selected_output = match event {
+            Event::ProcessOutput(idx, _) => *idx == self.state.selected,
+            _ => false,
+        };
+        let frame_due = self.min_frame_interval == std::time::Duration::default()
+            || self.last_frame.elapsed() >= self.min_frame_interval;
+
+        if !needs_prompt_frame && !is_selected_output && !frame_due {
+            return Ok(self.drain_actions());
+        }
+        self.last_frame = std::time::Instant::now();
 
         let state_ref = &mut self.state;
         self.terminal.draw(move |mut frame| {
-            match event {
-                Event::ProcessOutput(idx, data) => {
-                    state_ref.on_data(*idx, data.clone());
-                }
-                Event::ProcessExit(idx, status) => {
-                    state_ref.on_exit(*idx, *status);
-                }
-                Event::UserInput(event, user_input) => {
-                    let handled_input = state_ref.on_user_input(frame.size(), event);
-                    if !handled_input {
-                        *process_input_all_ref = Some(user_input.clone());
+            if let Event::UserInput(event, user_input) = event {
+                let handled_input = state_ref.on_user_input(frame.size(), event);
+                if !handled_input {
+                    if state_ref.sync_input() {
+                        state_ref.queue_input_for_writable_panes(user_input.clone());
+                    } else {
+                        state_ref.queue_input_for_selected(user_input.clone());
                     }
                 }
-                _ => {}
-            };
+            }
 
             frame.render(state_ref, frame.size());
         })?;
 
-        let result = process_input_all
-            .into_iter()
-            .map(|data| Action::ProcessInputAll { data })
+        self.propagate_title()?;
+
+        Ok(self.drain_actions())
+    }
+
+    /// Collect the `Action`s/process-input bytes `state` has queued up since the last call,
+    /// regardless of whether this `on_event` call painted a frame.
+    fn drain_actions(&mut self) -> Vec<Action> {
+        self.state
+            .take_process_inputs()
+            .map(|(index, data)| Action::ProcessInput {
+                index,
+                data: data.freeze(),
+            })
+            .chain(self.state.take_pending_actions())
             .chain(
                 self.state
-                    .take_process_inputs()
-                    .map(|(index, data)| Action::ProcessInput {
-                        index,
-                        data: data.freeze(),
-                    }),
+                    .take_pending_urls()
+                    .map(|url| Action::OpenUrl { url }),
             )
-            .collect();
-
-        Ok(result)
+            .collect()
     }
 
     pub fn draw(&mut self) -> Result<(), failure::Error> {
@@ -131,6 +389,21 @@ where
         self.terminal.draw(|mut f| {
             f.render(state, f.size());
         })?;
+        self.propagate_title()?;
+        Ok(())
+    }
+
+    /// Mirror the selected pane's title onto the outer terminal's own window/tab title via
+    /// `ESC]0;...BEL`, the same escape a plain foreground program would send if it owned the
+    /// whole screen. Only writes when `:propagate-title` is on and the title's actually changed
+    /// since the last call, so normal drawing doesn't hit the tty on every frame.
+    fn propagate_title(&mut self) -> Result<(), failure::Error> {
+        use std::io::Write;
+
+        if let Some(title) = self.state.take_propagated_title() {
+            write!(self.terminal.backend_mut(), "\x1b]0;{}\x07", title)?;
+            self.terminal.backend_mut().flush()?;
+        }
         Ok(())
     }
 }
@@ -139,34 +412,1260 @@ impl Action {
     pub fn matches_index(&self, other_index: usize) -> bool {
         match *self {
             Action::ProcessInput { index, .. } => index == other_index,
-            Action::ProcessInputAll { .. } => true,
             Action::ProcessTermResize { index, .. } => index == other_index,
+            Action::KillPane { index, .. } => index == other_index,
+            Action::OpenUrl { .. } => false,
         }
     }
 }
 
 impl State {
-    fn new(processes: Vec<ProcessState>) -> Self {
+    fn new(
+        processes: Vec<ProcessState>,
+        keybindings: crate::keybindings::Keybindings,
+        hooks: crate::hooks::Hooks,
+        notifications: crate::notify::NotificationBackend,
+        event_bus: crate::event_bus::EventBus,
+        silence: Option<std::time::Duration>,
+        accessibility_events: bool,
+    ) -> Self {
         let selected = 0;
         let scroll = 0;
+        // `mux` always exports `MUX` into a spawned pane's environment (see `pane_envs` in
+        // `main.rs`), so inheriting it here means this process was itself started as a pane of
+        // another `mux`. The outer instance's keybindings would otherwise swallow the same
+        // chords before this one ever saw them; point at `passthrough` up front rather than
+        // leaving the user to rediscover nested-prefix confusion the way tmux users do.
+        let status_message = if std::env::var_os("MUX").is_some() {
+            Some(format!(
+                "running nested inside another mux pane; press {} to forward the next key to it",
+                keybindings.passthrough
+            ))
+        } else {
+            None
+        };
         Self {
             processes,
             selected,
             scroll,
+            keybindings,
+            command_line: None,
+            status_message,
+            paste_buffers: crate::paste_buffers::PasteBuffers::default(),
+            choosing_buffer: None,
+            pane_chooser: None,
+            scrollback_filter: None,
+            pending_actions: Vec::new(),
+            sync_input: true,
+            hooks,
+            notifications,
+            event_bus,
+            silence,
+            propagate_title: false,
+            last_propagated_title: None,
+            passthrough_pending: false,
+            showing_timeline: false,
+            accessibility_events,
+            scheduled: Vec::new(),
+        }
+    }
+
+    /// Parse and run a `:`-command.
+    ///
+    /// Only a tiny subset of commands make sense in mux's flat tab model (there are no windows
+    /// or splits to target), so this covers `select-pane`/`rename-pane`/`kill-pane`/`sync-input`
+    /// rather than trying to emulate tmux's full command language. tmux's own spellings of those
+    /// (`selectp`, `killp`, `renamew`, `set -g synchronize-panes`/`monitor-silence`) are accepted
+    /// as aliases so `.tmux.conf` snippets and tools like tmuxinator that only use this handful
+    /// of commands work unmodified; `new-window`/`split-window` have nowhere to go here and say
+    /// so rather than being silently ignored.
+    fn run_command(&mut self, command: &str) {
+        let command = command.trim();
+        let mut parts = command.splitn(2, char::is_whitespace);
+        let verb = parts.next().unwrap_or("");
+        let rest = parts.next().map(str::trim).unwrap_or("");
+
+        match verb {
+            "select-pane" | "selectp" => {
+                if let Ok(index) = rest.parse::<usize>() {
+                    if index < self.processes.len() {
+                        self.select_pane(index);
+                    }
+                }
+            }
+            "rename-pane" | "rename-window" | "renamew" => {
+                if let Some(process) = self.processes.get_mut(self.selected) {
+                    process.title = rest.to_owned();
+                }
+            }
+            "kill-pane" | "kill-pane!" | "killp" | "killp!" => {
+                self.kill_selected_pane(verb.ends_with('!'), rest)
+            }
+            "clone-pane" | "clonep" => self.clone_selected_pane(),
+            "sync-input" => self.set_sync_input(rest),
+            "read-only" => self.set_read_only(rest),
+            "pipe-pane" | "pipep" => self.set_pipe_pane(rest),
+            "scroll-lock" => self.set_scroll_lock(rest),
+            "follow" => self.jump_to_bottom_and_follow(),
+            "jump-to-time" => self.jump_to_time(rest),
+            "mark" => self.mark(rest),
+            "next-mark" => self.step_mark(true),
+            "previous-mark" => self.step_mark(false),
+            "schedule" => self.schedule_command(rest, false),
+            "schedule-every" => self.schedule_command(rest, true),
+            "auto-hyperlink" => self.set_auto_hyperlink(rest),
+            "propagate-title" => self.set_propagate_title(rest),
+            "set-option" | "set" => self.run_set_option(rest),
+            "set-buffer" | "setb" => self.set_buffer(rest),
+            "paste-buffer" | "pasteb" => self.paste_buffer(rest),
+            "choose-buffer" | "chooseb" => self.open_buffer_chooser(),
+            "choose-pane" | "choosep" | "choose-window" | "choosew" | "choose-tree" => {
+                self.open_pane_chooser()
+            }
+            "capture-pane" | "capturep" => self.capture_pane(rest),
+            "copy-last-output" | "last-output" => self.copy_last_output(rest),
+            "search-pane" | "searchp" | "find" => self.search_pane(rest),
+            "filter-pane" | "filterp" | "fzf" => self.open_scrollback_filter(),
+            "timeline" | "activity-timeline" => self.open_timeline(),
+            "show-latency" | "latency" => {
+                self.status_message = Some(
+                    self.latency_summary()
+                        .unwrap_or_else(|| "show-latency: no round trips recorded yet".to_owned()),
+                );
+            }
+            "new-window" | "neww" | "split-window" | "splitw" => {
+                self.status_message = Some(format!(
+                    "{}: mux has no windows or splits, just a flat list of panes",
+                    verb
+                ));
+            }
+            "respawn-pane" | "respawnp" | "respawn-window" | "respawnw" => {
+                self.status_message = Some(format!(
+                    "{}: not supported - each pane's pty is wired up once at startup, so a \
+                     dead pane's command can't be restarted in place; kill-pane removes it and \
+                     clone-pane can still snapshot what it last printed",
+                    verb
+                ));
+            }
+            "" => {}
+            _ => debug!("unknown command: {}", command),
+        }
+    }
+
+    fn set_sync_input(&mut self, value: &str) {
+        self.sync_input = match value {
+            "on" => true,
+            "off" => false,
+            _ => !self.sync_input,
+        };
+        self.status_message = Some(format!(
+            "sync-input: {}",
+            if self.sync_input { "on" } else { "off" }
+        ));
+    }
+
+    /// `:read-only [on|off]` - toggle the selected pane's `ProcessState::read_only` flag, the
+    /// same on/off/toggle spelling as `:sync-input`.
+    fn set_read_only(&mut self, value: &str) {
+        let process = match self.processes.get_mut(self.selected) {
+            Some(process) => process,
+            None => return,
+        };
+        process.read_only = match value {
+            "on" => true,
+            "off" => false,
+            _ => !process.read_only,
+        };
+        self.status_message = Some(format!(
+            "read-only: {}",
+            if process.read_only { "on" } else { "off" }
+        ));
+    }
+
+    /// `:pipe-pane [command]` - tee a copy of the selected pane's raw output to `command`,
+    /// spawned the same way as `hooks.*` entries (`sh -c command`, with `MUX_PANE` set in its
+    /// environment), tmux `pipe-pane` style. Run again with no argument to detach whatever was
+    /// previously piped, or with a new command to replace it - only one command can be attached
+    /// per pane at a time, the same restriction tmux itself has.
+    fn set_pipe_pane(&mut self, command: &str) {
+        let selected = self.selected;
+        if self.processes.get(selected).is_none() {
+            return;
+        }
+
+        if command.is_empty() {
+            if let Some(process) = self.processes.get_mut(selected) {
+                process.set_pipe(None);
+            }
+            self.status_message = Some("pipe-pane: detached".to_owned());
+            return;
+        }
+
+        let mut cmd = std::process::Command::new("sh");
+        cmd.arg("-c")
+            .arg(command)
+            .env("MUX_PANE", selected.to_string())
+            .stdin(std::process::Stdio::piped());
+
+        match cmd.spawn() {
+            Ok(mut child) => {
+                let stdin = child.stdin.take();
+                if let Some(process) = self.processes.get_mut(selected) {
+                    process.set_pipe(stdin);
+                }
+                self.status_message = Some(format!("pipe-pane: piping to `{}`", command));
+            }
+            Err(err) => {
+                self.status_message =
+                    Some(format!("pipe-pane: failed to run {:?}: {}", command, err));
+            }
+        }
+    }
+
+    /// `:scroll-lock [on|off]` - toggle whether the selected pane's viewport is allowed to
+    /// auto-scroll to the bottom when its process produces new output. Locking it keeps a
+    /// log-follow pane from yanking the view back down while it's scrolled up for review;
+    /// `:follow` undoes that and jumps straight back to the bottom.
+    fn set_scroll_lock(&mut self, value: &str) {
+        let process = match self.processes.get_mut(self.selected) {
+            Some(process) => process,
+            None => return,
+        };
+        let auto_scroll = match value {
+            "on" => false,
+            "off" => true,
+            _ => !process.terminal_emulator.auto_scroll(),
+        };
+        process.terminal_emulator.set_auto_scroll(auto_scroll);
+        self.status_message = Some(format!(
+            "scroll-lock: {}",
+            if auto_scroll { "off" } else { "on" }
+        ));
+    }
+
+    /// `:follow` - jump the selected pane's viewport to the bottom and resume auto-scrolling,
+    /// the same "jump to bottom and follow" action tmux's `copy-mode -e` binds to `q`.
+    fn jump_to_bottom_and_follow(&mut self) {
+        if let Some(process) = self.processes.get_mut(self.selected) {
+            process.terminal_emulator.jump_to_bottom_and_follow();
+        }
+    }
+
+    /// `:jump-to-time <duration>` - scroll the selected pane's viewport to the line whose
+    /// recorded timestamp is closest to `duration` ago (`30s`, `5m`, `2h`, `1d`, or a bare
+    /// number of seconds). Every `on_data` chunk tags the line it just
+    /// wrote with the time it arrived (see `ProcessState::stamp_current_line_time`), so this is
+    /// a linear scan over those tags rather than an index kept up to date as lines scroll - fine
+    /// for an occasional `:`-command over a few thousand scrollback lines at most.
+    fn jump_to_time(&mut self, rest: &str) {
+        let ago = match parse_duration_secs(rest) {
+            Some(ago) => ago,
+            None => {
+                self.status_message = Some(format!("jump-to-time: invalid duration {:?}", rest));
+                return;
+            }
+        };
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+        let target = now.saturating_sub(ago);
+
+        let process = match self.processes.get_mut(self.selected) {
+            Some(process) => process,
+            None => return,
+        };
+        let total =
+            process.terminal_emulator.grid().scroll_limit() + process.terminal_emulator.grid().num_lines().0;
+
+        let mut best: Option<(usize, u64)> = None;
+        for buffer_line in 0..total {
+            let time = process
+                .terminal_emulator
+                .line_metadata(buffer_line)
+                .and_then(|metadata| {
+                    metadata
+                        .iter()
+                        .find(|(key, _)| &**key == "time")
+                        .and_then(|(_, value)| value.parse::<u64>().ok())
+                });
+            let time = match time {
+                Some(time) => time,
+                None => continue,
+            };
+            let diff = if time > target {
+                time - target
+            } else {
+                target - time
+            };
+            if best.map_or(true, |(_, best_diff)| diff < best_diff) {
+                best = Some((buffer_line, diff));
+            }
+        }
+
+        match best {
+            Some((buffer_line, _)) => {
+                process.terminal_emulator.scroll_to_buffer_line(buffer_line);
+                self.status_message =
+                    Some(format!("jump-to-time: jumped to output from ~{} ago", rest.trim()));
+            }
+            None => {
+                self.status_message =
+                    Some("jump-to-time: no timestamped output to jump to yet".to_owned());
+            }
+        }
+    }
+
+    /// `:mark [NAME]` - tag the selected pane's current bottom-most line so `:next-mark`/
+    /// `:previous-mark` can jump back to it later, e.g. to bookmark where a deploy started in a
+    /// log pane you're about to scroll away from.
+    fn mark(&mut self, rest: &str) {
+        let process = match self.processes.get_mut(self.selected) {
+            Some(process) => process,
+            None => return,
+        };
+        let line = process
+            .terminal_emulator
+            .grid()
+            .visible_to_buffer(process.terminal_emulator.cursor().point)
+            .line;
+        let name = if rest.is_empty() {
+            None
+        } else {
+            Some(rest.to_owned())
+        };
+        process.marks.push((line, name));
+        if process.marks.len() > MAX_MARKS {
+            process.marks.remove(0);
+        }
+        self.status_message = Some("mark: added".to_owned());
+    }
+
+    /// `:next-mark`/`:previous-mark` - jump the selected pane's viewport to the next or previous
+    /// `:mark`, relative to where the viewport is currently scrolled to.
+    fn step_mark(&mut self, forward: bool) {
+        let process = match self.processes.get_mut(self.selected) {
+            Some(process) => process,
+            None => return,
+        };
+        let current = process.terminal_emulator.grid().display_offset();
+
+        let target = if forward {
+            process
+                .marks
+                .iter()
+                .map(|(line, _)| *line)
+                .filter(|line| *line < current)
+                .max()
+        } else {
+            process
+                .marks
+                .iter()
+                .map(|(line, _)| *line)
+                .filter(|line| *line > current)
+                .min()
+        };
+
+        match target {
+            Some(line) => {
+                process.terminal_emulator.scroll_to_buffer_line(line);
+                self.status_message = Some(if forward {
+                    "next-mark: jumped".to_owned()
+                } else {
+                    "previous-mark: jumped".to_owned()
+                });
+            }
+            None => {
+                self.status_message = Some(if forward {
+                    "next-mark: no later mark".to_owned()
+                } else {
+                    "previous-mark: no earlier mark".to_owned()
+                });
+            }
+        }
+    }
+
+    /// `:schedule <duration> <command>` / `:schedule-every <duration> <command>` - queue a
+    /// `:`-command (anything `run_command` accepts, e.g. `select-pane 1` or a keystroke sent via
+    /// whatever command ends up wrapping `queue_input_for_selected`) to run once `<duration>`
+    /// from now, or repeatedly every `<duration>`, for periodic refreshes or delayed kickoffs.
+    /// Checked once a second off `Event::Tick` rather than its own timer, the same granularity
+    /// `:monitor-silence` already runs at. Queued commands only live as long as this `mux`
+    /// process does - see `scheduled`'s own doc comment.
+    fn schedule_command(&mut self, rest: &str, recurring: bool) {
+        let mut parts = rest.splitn(2, char::is_whitespace);
+        let duration_spec = parts.next().unwrap_or("");
+        let command = parts.next().map(str::trim).unwrap_or("");
+
+        let verb = if recurring { "schedule-every" } else { "schedule" };
+
+        let seconds = match parse_duration_secs(duration_spec) {
+            Some(seconds) => seconds,
+            None => {
+                self.status_message =
+                    Some(format!("{}: invalid duration {:?}", verb, duration_spec));
+                return;
+            }
+        };
+        if command.is_empty() {
+            self.status_message = Some(format!("{}: no command given", verb));
+            return;
+        }
+
+        let interval = std::time::Duration::from_secs(seconds);
+        self.scheduled.push(ScheduledCommand {
+            run_at: std::time::Instant::now() + interval,
+            interval: if recurring { Some(interval) } else { None },
+            command: command.to_owned(),
+        });
+        self.status_message = Some(format!(
+            "{}: will run `{}` in {}",
+            verb,
+            command,
+            duration_spec.trim()
+        ));
+    }
+
+    /// Run every queued `:schedule`/`:schedule-every` command whose time has come, re-queuing the
+    /// recurring ones for another interval from now. Pulled out of `on_tick` so a scheduled
+    /// command can itself call `run_command` (e.g. another `:schedule`) without conflicting with
+    /// the borrow that's iterating `self.scheduled`.
+    fn run_scheduled_commands(&mut self) {
+        let now = std::time::Instant::now();
+        let mut due = Vec::new();
+        let mut index = 0;
+        while index < self.scheduled.len() {
+            if self.scheduled[index].run_at > now {
+                index += 1;
+                continue;
+            }
+
+            let mut scheduled = self.scheduled.remove(index);
+            due.push(scheduled.command.clone());
+            if let Some(interval) = scheduled.interval {
+                scheduled.run_at = now + interval;
+                self.scheduled.push(scheduled);
+            }
+        }
+
+        for command in due {
+            self.run_command(&command);
+        }
+    }
+
+    /// `:auto-hyperlink [on|off]` - toggle whether `capture-pane -h` tags URLs/paths it detects
+    /// with a synthetic hyperlink, for programs (most of them) that never emit a real OSC 8.
+    fn set_auto_hyperlink(&mut self, value: &str) {
+        let process = match self.processes.get_mut(self.selected) {
+            Some(process) => process,
+            None => return,
+        };
+        let auto_hyperlink = match value {
+            "on" => true,
+            "off" => false,
+            _ => !process.terminal_emulator.auto_hyperlink(),
+        };
+        process.terminal_emulator.set_auto_hyperlink(auto_hyperlink);
+        self.status_message = Some(format!(
+            "auto-hyperlink: {}",
+            if auto_hyperlink { "on" } else { "off" }
+        ));
+    }
+
+    /// `:propagate-title [on|off]` - toggle whether the selected pane's title is mirrored onto
+    /// the outer terminal's own window/tab title (`Ui::propagate_title`).
+    fn set_propagate_title(&mut self, value: &str) {
+        self.propagate_title = match value {
+            "on" => true,
+            "off" => false,
+            _ => !self.propagate_title,
+        };
+        if !self.propagate_title {
+            self.last_propagated_title = None;
+        }
+        self.status_message = Some(format!(
+            "propagate-title: {}",
+            if self.propagate_title { "on" } else { "off" }
+        ));
+    }
+
+    /// The selected pane's title, if `:propagate-title` is enabled and it's changed since the
+    /// last call - consumed by `Ui::propagate_title` to mirror it onto the outer terminal.
+    fn take_propagated_title(&mut self) -> Option<String> {
+        if !self.propagate_title {
+            return None;
+        }
+        let title = self.processes.get(self.selected)?.title.clone();
+        if self.last_propagated_title.as_deref() == Some(title.as_str()) {
+            return None;
+        }
+        self.last_propagated_title = Some(title.clone());
+        Some(title)
+    }
+
+    /// Handle the subset of tmux's `set-option`/`set` that maps onto something mux actually has:
+    /// `synchronize-panes` (-> `sync-input`) and `monitor-silence` (-> the configured silence
+    /// threshold). Everything else is acknowledged but not applied, same as an unknown option
+    /// would be in tmux itself.
+    fn run_set_option(&mut self, rest: &str) {
+        let rest = rest.trim_start_matches("-g").trim();
+        let mut parts = rest.splitn(2, char::is_whitespace);
+        let option = parts.next().unwrap_or("");
+        let value = parts.next().map(str::trim).unwrap_or("");
+
+        match option {
+            "synchronize-panes" => self.set_sync_input(value),
+            "monitor-silence" => {
+                self.silence = match value.parse::<u64>() {
+                    Ok(0) | Err(_) => None,
+                    Ok(secs) => Some(std::time::Duration::from_secs(secs)),
+                };
+                self.status_message = Some(match self.silence {
+                    Some(duration) => format!("monitor-silence: {}", duration.as_secs()),
+                    None => "monitor-silence: off".to_owned(),
+                });
+            }
+            "" => {}
+            _ => self.status_message = Some(format!("set-option: unsupported option {:?}", option)),
+        }
+    }
+
+    /// `:capture-pane [-e|-h|-j] [FILE]` - tmux's `capture-pane`, minus `-p` (there's no stdout to
+    /// print to from inside the TUI): with no `FILE`, the selected pane's visible text is pushed
+    /// as a paste buffer, the same "get pane contents somewhere I can use them" result `-b` gives
+    /// in tmux; with `FILE`, it's written there instead, for scripting CI snapshots and bug
+    /// report capture without leaving mux. `-e` captures with SGR escape sequences included
+    /// (`Term::render_ansi`) rather than plain text (`Term::render_text`); `-h` captures as an
+    /// HTML fragment (`Term::render_html`), tagging detected URLs/paths with a synthetic
+    /// hyperlink first if `:auto-hyperlink` is on for this pane; `-j` joins soft-wrapped lines
+    /// back into one line (`Term::render_text_unwrapped`) so a long command or URL that wrapped
+    /// at the pane's width pastes back as a single line instead of being split mid-word.
+    fn capture_pane(&mut self, rest: &str) {
+        enum Format {
+            Text,
+            TextUnwrapped,
+            Ansi,
+            Html,
+        }
+
+        let (format, file) = match rest.split_whitespace().collect::<Vec<_>>().as_slice() {
+            ["-e", file] => (Format::Ansi, Some(*file)),
+            ["-e"] => (Format::Ansi, None),
+            ["-h", file] => (Format::Html, Some(*file)),
+            ["-h"] => (Format::Html, None),
+            ["-j", file] => (Format::TextUnwrapped, Some(*file)),
+            ["-j"] => (Format::TextUnwrapped, None),
+            [file] => (Format::Text, Some(*file)),
+            _ => (Format::Text, None),
+        };
+
+        let process = match self.processes.get_mut(self.selected) {
+            Some(process) => process,
+            None => return,
+        };
+        let captured = match format {
+            Format::Ansi => process.terminal_emulator.render_ansi(),
+            Format::Html => {
+                process.terminal_emulator.apply_synthetic_hyperlinks();
+                process.terminal_emulator.render_html()
+            }
+            Format::Text => process.terminal_emulator.render_text(),
+            Format::TextUnwrapped => process.terminal_emulator.render_text_unwrapped(),
+        };
+
+        match file {
+            Some(path) => {
+                self.status_message = Some(match std::fs::write(path, &captured) {
+                    Ok(()) => format!("capture-pane: wrote {}", path),
+                    Err(err) => format!("capture-pane: {}: {}", path, err),
+                });
+            }
+            None => {
+                self.paste_buffers.push(None, captured);
+                self.status_message = Some("capture-pane: buffer updated".to_owned());
+            }
+        }
+    }
+
+    /// `:copy-last-output [-N] [FILE]` - extract the most recently finished command's output
+    /// from the selected pane, via `Term::last_command_output_selection` (built from the OSC
+    /// 133 shell integration marks the pane's shell sends around each prompt/command/output
+    /// (see `ansi::SemanticPromptMark`), into a paste buffer or `FILE` if given, without
+    /// switching into copy mode to select it by hand. `-N` keeps
+    /// only the last `N` logical (soft-wrap-joined) lines of that output, e.g. to grab just the
+    /// error at the tail of a noisy build. Needs a shell with OSC 133 integration enabled; with
+    /// no output-start/command-finished marks recorded yet, this is a no-op with a status
+    /// message saying so.
+    fn copy_last_output(&mut self, rest: &str) {
+        let (count, file) = match rest.split_whitespace().collect::<Vec<_>>().as_slice() {
+            [flag, file] if flag.starts_with('-') => (flag[1..].parse::<usize>().ok(), Some(*file)),
+            [flag] if flag.starts_with('-') => (flag[1..].parse::<usize>().ok(), None),
+            [file] => (None, Some(*file)),
+            _ => (None, None),
+        };
+
+        let process = match self.processes.get_mut(self.selected) {
+            Some(process) => process,
+            None => return,
+        };
+
+        let selection = match process.terminal_emulator.last_command_output_selection() {
+            Some(selection) => selection,
+            None => {
+                self.status_message = Some(
+                    "copy-last-output: no command output recorded yet - needs shell_integration"
+                        .to_owned(),
+                );
+                return;
+            }
+        };
+
+        let previous_selection = process.terminal_emulator.selection().clone();
+        *process.terminal_emulator.selection_mut() = Some(selection);
+        let captured = process
+            .terminal_emulator
+            .selection_to_string()
+            .unwrap_or_default();
+        *process.terminal_emulator.selection_mut() = previous_selection;
+
+        let captured = match count {
+            Some(n) => {
+                let lines: Vec<&str> = captured.split('\n').collect();
+                let start = lines.len().saturating_sub(n);
+                lines[start..].join("\n")
+            }
+            None => captured,
+        };
+
+        match file {
+            Some(path) => {
+                self.status_message = Some(match std::fs::write(path, &captured) {
+                    Ok(()) => format!("copy-last-output: wrote {}", path),
+                    Err(err) => format!("copy-last-output: {}: {}", path, err),
+                });
+            }
+            None => {
+                self.paste_buffers.push(None, captured);
+                self.status_message = Some("copy-last-output: buffer updated".to_owned());
+            }
+        }
+    }
+
+    /// `:search-pane [-b] PATTERN` - find `PATTERN` (a regex) in the selected pane's visible
+    /// grid, via `Term::find`, and select the first match so `Ctrl+C`/a mouse drag can pick up
+    /// from there without hunting for it by eye. `-b` restricts matches to bold text, e.g. to
+    /// jump straight to a compiler's bolded `error:` instead of every occurrence of the word in
+    /// plain output; there's no flag for fg/bg/hyperlink filters yet since nothing surfaces those
+    /// to a `:`-command user the way `-b` maps onto a key they actually press.
+    fn search_pane(&mut self, rest: &str) {
+        let (bold_only, pattern) = match rest.strip_prefix("-b ") {
+            Some(pattern) => (true, pattern.trim()),
+            None => (false, rest.trim()),
+        };
+
+        let regex = match regex::Regex::new(pattern) {
+            Ok(regex) => regex,
+            Err(err) => {
+                self.status_message = Some(format!("search-pane: {}", err));
+                return;
+            }
+        };
+        let filter = if bold_only {
+            terminal_emulator::term::search::StyleFilter {
+                flags: terminal_emulator::term::cell::Flags::BOLD,
+                ..terminal_emulator::term::search::StyleFilter::default()
+            }
+        } else {
+            terminal_emulator::term::search::StyleFilter::default()
+        };
+
+        let process = match self.processes.get_mut(self.selected) {
+            Some(process) => process,
+            None => return,
+        };
+        let matches = process.terminal_emulator.find(&regex, &filter);
+        self.status_message = Some(match matches.first() {
+            Some(found) => {
+                let start = process.terminal_emulator.grid().visible_to_buffer(found.start);
+                let end = process.terminal_emulator.grid().visible_to_buffer(found.end);
+                *process.terminal_emulator.selection_mut() = Some(
+                    terminal_emulator::selection::Selection::simple(
+                        start,
+                        terminal_emulator::index::Side::Left,
+                    ),
+                );
+                if let Some(selection) = process.terminal_emulator.selection_mut() {
+                    selection.update(end, terminal_emulator::index::Side::Right);
+                }
+                format!("search-pane: {} match(es), selected the first", matches.len())
+            }
+            None => "search-pane: no matches".to_owned(),
+        });
+    }
+
+    /// `:set-buffer [-b NAME] TEXT` - push a paste buffer, named or (if `-b` is omitted)
+    /// auto-named, the way an OSC 52 write or a copy-mode selection would.
+    fn set_buffer(&mut self, rest: &str) {
+        let (name, contents) = if rest.starts_with("-b ") {
+            let mut parts = rest[3..].splitn(2, char::is_whitespace);
+            let name = parts.next().unwrap_or("").to_owned();
+            let contents = parts.next().unwrap_or("").to_owned();
+            (Some(name), contents)
+        } else {
+            (None, rest.to_owned())
+        };
+        self.paste_buffers.push(name, contents);
+        self.status_message = Some("set-buffer: buffer updated".to_owned());
+    }
+
+    /// `:paste-buffer [NAME|INDEX]` - paste a buffer into the selected pane: the top of the
+    /// stack if no argument is given, otherwise the buffer with that name or, failing that,
+    /// at that index.
+    fn paste_buffer(&mut self, rest: &str) {
+        let buffer = if rest.is_empty() {
+            self.paste_buffers.top()
+        } else {
+            self.paste_buffers
+                .named(rest)
+                .or_else(|| rest.parse::<usize>().ok().and_then(|i| self.paste_buffers.get(i)))
+        };
+
+        match buffer.map(|buffer| buffer.contents.clone()) {
+            Some(contents) => self.queue_input_for_selected(bytes::Bytes::from(contents)),
+            None => self.status_message = Some("paste-buffer: no such buffer".to_owned()),
         }
     }
 
+    /// Open the `choose-buffer` overlay, highlighting the most recently added buffer first.
+    fn open_buffer_chooser(&mut self) {
+        if self.paste_buffers.is_empty() {
+            self.status_message = Some("choose-buffer: no buffers".to_owned());
+        } else {
+            self.choosing_buffer = Some(self.paste_buffers.len() - 1);
+        }
+    }
+
+    /// Open the full-screen `choose-pane` overlay, starting with an empty filter.
+    fn open_pane_chooser(&mut self) {
+        if self.processes.is_empty() {
+            self.status_message = Some("choose-pane: no panes".to_owned());
+        } else {
+            self.pane_chooser = Some(PaneChooser {
+                query: String::new(),
+                selected: 0,
+            });
+        }
+    }
+
+    /// Open the full-screen `:filter-pane` overlay, starting with an empty filter, unless the
+    /// selected pane has no scrollback at all to filter over.
+    fn open_scrollback_filter(&mut self) {
+        let has_lines = self
+            .processes
+            .get(self.selected)
+            .map_or(false, |process| process.terminal_emulator.grid().len() > 0);
+        if !has_lines {
+            self.status_message = Some("filter-pane: no scrollback".to_owned());
+        } else {
+            self.scrollback_filter = Some(ScrollbackFilter {
+                query: String::new(),
+                selected: 0,
+            });
+        }
+    }
+
+    /// Lines from the selected pane's scrollback (`Term::scrollback_lines`, oldest first)
+    /// containing `query` as a case-insensitive substring - the same plain-substring filtering
+    /// `filtered_pane_indices` uses, since there's no fuzzy-matching engine in this codebase.
+    fn filtered_scrollback_lines(
+        &self,
+        query: &str,
+    ) -> Vec<terminal_emulator::term::scrollback::ScrollbackLine> {
+        let query = query.to_lowercase();
+        let process = match self.processes.get(self.selected) {
+            Some(process) => process,
+            None => return Vec::new(),
+        };
+        process
+            .terminal_emulator
+            .scrollback_lines()
+            .into_iter()
+            .filter(|line| line.text.to_lowercase().contains(&query))
+            .collect()
+    }
+
+    /// Open the full-screen `:timeline` overlay over the selected pane's `activity_history`.
+    fn open_timeline(&mut self) {
+        if self.processes.is_empty() {
+            self.status_message = Some("timeline: no panes".to_owned());
+        } else {
+            self.showing_timeline = true;
+        }
+    }
+
+    /// Indices into `processes` of the panes matching `query` (a substring of the title or status
+    /// line, case-insensitively), in display order.
+    fn filtered_pane_indices(&self, query: &str) -> Vec<usize> {
+        let query = query.to_lowercase();
+        self.processes
+            .iter()
+            .enumerate()
+            .filter(|(_, process)| process.matches_filter(&query))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Signal the selected pane's whole process tree, not just its direct child.
+    ///
+    /// If the pane still has live descendant processes (e.g. a shell that spawned a long-running
+    /// job) and `force` isn't set, nothing is killed; instead `status_message` is set asking the
+    /// user to re-run as `kill-pane!` to confirm.
+    fn kill_selected_pane(&mut self, force: bool, signal: &str) {
+        let process = match self.processes.get(self.selected) {
+            Some(process) => process,
+            None => return,
+        };
+
+        // A clone has no pty or pid to signal; just drop it from the list. `processes` after it
+        // are only ever other clones (real panes keep their startup index stable), so removing
+        // it here can't desync the pty-index correlation `Event::ProcessOutput` and friends rely
+        // on.
+        if process.is_clone {
+            self.processes.remove(self.selected);
+            self.selected = self.selected.min(self.processes.len().saturating_sub(1));
+            self.status_message = Some("kill-pane: removed clone".to_owned());
+            return;
+        }
+
+        let signal = parse_signal(signal).unwrap_or(libc::SIGTERM);
+
+        if process.has_descendants && !force {
+            self.status_message = Some(
+                "pane has running child processes; re-run as kill-pane! to confirm".to_owned(),
+            );
+            return;
+        }
+
+        self.status_message = None;
+        self.pending_actions.push(Action::KillPane {
+            index: self.selected,
+            signal,
+        });
+    }
+
+    /// `:clone-pane` - snapshot the selected pane's scrollback and current screen into a new,
+    /// read-only pane appended after the last real pane, and select it. The original keeps
+    /// receiving live output; the clone is frozen, so it's safe to scroll and search through
+    /// without it jumping out from under you or taking keystrokes meant for the live pane.
+    fn clone_selected_pane(&mut self) {
+        let clone = match self.processes.get(self.selected) {
+            Some(process) => process.clone_snapshot(),
+            None => return,
+        };
+        self.processes.push(clone);
+        self.selected = self.processes.len() - 1;
+        self.status_message = Some("clone-pane: cloned into a new read-only pane".to_owned());
+    }
+
     fn on_data(&mut self, index: usize, data: bytes::Bytes) {
-        self.processes[index].on_data(data)
+        self.processes[index].pipe_data(&data);
+        if self.processes[index].on_data(data) {
+            self.hooks.run_on_bell(index);
+            self.notifications.notify(None, "pane rang the bell");
+            self.event_bus
+                .publish(crate::event_bus::Event::PaneBell { pane: index });
+        }
+        if let Some(contents) = self.processes[index].take_clipboard() {
+            self.paste_buffers.push(None, contents);
+        }
+        if let Some((title, body)) = self.processes[index].take_notification() {
+            self.notifications.notify(title.as_deref(), &body);
+            self.hooks.run_on_notify(index, title.as_deref());
+            self.processes[index].notified = true;
+        }
+    }
+
+    /// Select a pane, clearing any bell/activity/notification flags it was showing in the tab list
+    /// (tmux clears `monitor-activity` highlighting the same way when a window is viewed), and
+    /// sending DECSET-1004 focus-out/focus-in (`ESC[O`/`ESC[I`) to the panes that lose and gain
+    /// focus, the same sequences a real terminal sends its one foreground program when the whole
+    /// window's focus changes - so an editor in a background pane notices it's no longer in front
+    /// (and can dim itself or pause autosave-triggered reloads) and the one brought to front knows
+    /// to re-check the file it has open, without needing the outer terminal itself to lose and
+    /// regain focus.
+    ///
+    /// mux itself never observes real OS-level window focus changes (`termion`'s `Event` has no
+    /// focus variant to report them through), so this only covers switching which pane is
+    /// frontmost inside mux, not the outer terminal gaining or losing focus entirely.
+    fn select_pane(&mut self, index: usize) {
+        if index == self.selected {
+            return;
+        }
+
+        if let Some(process) = self.processes.get(self.selected) {
+            if process
+                .terminal_emulator
+                .mode()
+                .contains(terminal_emulator::term::TermMode::FOCUS_IN_OUT)
+            {
+                self.pending_actions.push(Action::ProcessInput {
+                    index: self.selected,
+                    data: bytes::Bytes::from_static(b"\x1b[O"),
+                });
+            }
+        }
+
+        self.selected = index;
+        if let Some(process) = self.processes.get_mut(index) {
+            process.clear_activity();
+            if process
+                .terminal_emulator
+                .mode()
+                .contains(terminal_emulator::term::TermMode::FOCUS_IN_OUT)
+            {
+                self.pending_actions.push(Action::ProcessInput {
+                    index,
+                    data: bytes::Bytes::from_static(b"\x1b[I"),
+                });
+            }
+        }
+    }
+
+    /// Flag panes that have gone quiet for longer than the configured silence threshold, and
+    /// (if `accessibility_events` is on) publish this tick's debounced line/cursor changes.
+    fn on_tick(&mut self) {
+        self.run_scheduled_commands();
+
+        for process in self.processes.iter_mut() {
+            process.record_activity_tick();
+        }
+
+        if self.accessibility_events {
+            for (index, process) in self.processes.iter_mut().enumerate() {
+                for event in process.accessibility_changes(index) {
+                    self.event_bus.publish(event);
+                }
+            }
+        }
+
+        let threshold = match self.silence {
+            Some(threshold) => threshold,
+            None => return,
+        };
+
+        for (index, process) in self.processes.iter_mut().enumerate() {
+            if process.exit_status.is_some() || process.silent {
+                continue;
+            }
+
+            if process.last_output.elapsed() >= threshold {
+                process.silent = true;
+                self.hooks.run_on_silence(index);
+                self.notifications.notify(None, "pane has gone quiet");
+            }
+        }
+    }
+
+    /// Apply a reloaded `Config::scrollback_lines` to every already-spawned pane's terminal
+    /// emulator (see `config::watch`), instead of only taking effect on the next `mux` restart.
+    fn set_scrollback_lines(&mut self, scrollback_lines: usize) {
+        for process in self.processes.iter_mut() {
+            process
+                .terminal_emulator
+                .set_scrollback_capacity(scrollback_lines);
+        }
     }
 
     fn on_exit(&mut self, index: usize, status: std::process::ExitStatus) {
-        self.processes[index].on_exit(status)
+        use std::os::unix::process::ExitStatusExt;
+
+        self.processes[index].on_exit(status);
+        self.hooks.run_on_pane_exit(index, status);
+        self.notifications
+            .notify(None, &format!("pane exited ({})", status));
+        self.event_bus.publish(crate::event_bus::Event::PaneExit {
+            pane: index,
+            exit_code: status.code(),
+            signal: status.signal(),
+        });
+    }
+
+    fn on_usage(
+        &mut self,
+        index: usize,
+        cpu_ticks: u64,
+        rss_bytes: u64,
+        read_bytes: u64,
+        write_bytes: u64,
+        has_descendants: bool,
+        foreground_command: Option<String>,
+    ) {
+        self.processes[index].on_usage(
+            cpu_ticks,
+            rss_bytes,
+            read_bytes,
+            write_bytes,
+            has_descendants,
+            foreground_command,
+        )
     }
 
     fn on_user_input(&mut self, area: tui::layout::Rect, event: &termion::event::Event) -> bool {
         match *event {
-            termion::event::Event::Key(_) => false,
+            termion::event::Event::Key(termion::event::Key::Char(':'))
+                if self.command_line.is_none() =>
+            {
+                self.command_line = Some(String::new());
+                self.status_message = None;
+                true
+            }
+            termion::event::Event::Key(key) if self.command_line.is_some() => {
+                match key {
+                    termion::event::Key::Char('\n') => {
+                        let command = self.command_line.take().unwrap_or_default();
+                        self.run_command(&command);
+                    }
+                    termion::event::Key::Esc => {
+                        self.command_line = None;
+                    }
+                    termion::event::Key::Backspace => {
+                        if let Some(ref mut line) = self.command_line {
+                            line.pop();
+                        }
+                    }
+                    termion::event::Key::Char(c) => {
+                        if let Some(ref mut line) = self.command_line {
+                            line.push(c);
+                        }
+                    }
+                    _ => {}
+                }
+                true
+            }
+            termion::event::Event::Key(key) if self.choosing_buffer.is_some() => {
+                match key {
+                    termion::event::Key::Up => {
+                        if let Some(ref mut index) = self.choosing_buffer {
+                            *index = index.saturating_sub(1);
+                        }
+                    }
+                    termion::event::Key::Down => {
+                        if let Some(ref mut index) = self.choosing_buffer {
+                            *index = (*index + 1).min(self.paste_buffers.len() - 1);
+                        }
+                    }
+                    termion::event::Key::Char('\n') => {
+                        if let Some(index) = self.choosing_buffer.take() {
+                            let contents = self
+                                .paste_buffers
+                                .get(index)
+                                .map(|buffer| buffer.contents.clone());
+                            if let Some(contents) = contents {
+                                self.queue_input_for_selected(bytes::Bytes::from(contents));
+                            }
+                        }
+                    }
+                    termion::event::Key::Esc => {
+                        self.choosing_buffer = None;
+                    }
+                    _ => {}
+                }
+                true
+            }
+            termion::event::Event::Key(key) if self.pane_chooser.is_some() => {
+                match key {
+                    termion::event::Key::Esc => {
+                        self.pane_chooser = None;
+                    }
+                    termion::event::Key::Up => {
+                        if let Some(ref mut chooser) = self.pane_chooser {
+                            chooser.selected = chooser.selected.saturating_sub(1);
+                        }
+                    }
+                    termion::event::Key::Down => {
+                        if let Some(query) = self.pane_chooser.as_ref().map(|c| c.query.clone()) {
+                            let last = self.filtered_pane_indices(&query).len().saturating_sub(1);
+                            if let Some(ref mut chooser) = self.pane_chooser {
+                                chooser.selected = (chooser.selected + 1).min(last);
+                            }
+                        }
+                    }
+                    termion::event::Key::Backspace => {
+                        if let Some(ref mut chooser) = self.pane_chooser {
+                            chooser.query.pop();
+                            chooser.selected = 0;
+                        }
+                    }
+                    termion::event::Key::Char('\n') => {
+                        if let Some(chooser) = self.pane_chooser.take() {
+                            let indices = self.filtered_pane_indices(&chooser.query);
+                            if let Some(&index) = indices.get(chooser.selected) {
+                                self.select_pane(index);
+                            }
+                        }
+                    }
+                    // Readline-style "kill" rather than tmux's bare `x`, since ordinary
+                    // characters are already claimed for typing the filter.
+                    termion::event::Key::Ctrl('k') => {
+                        if let Some(chooser) = self.pane_chooser.take() {
+                            let indices = self.filtered_pane_indices(&chooser.query);
+                            if let Some(&index) = indices.get(chooser.selected) {
+                                self.select_pane(index);
+                                self.kill_selected_pane(false, "");
+                            }
+                        }
+                    }
+                    termion::event::Key::Char(c) => {
+                        if let Some(ref mut chooser) = self.pane_chooser {
+                            chooser.query.push(c);
+                            chooser.selected = 0;
+                        }
+                    }
+                    _ => {}
+                }
+                true
+            }
+            termion::event::Event::Key(key) if self.scrollback_filter.is_some() => {
+                match key {
+                    termion::event::Key::Esc => {
+                        self.scrollback_filter = None;
+                    }
+                    termion::event::Key::Up => {
+                        if let Some(ref mut filter) = self.scrollback_filter {
+                            filter.selected = filter.selected.saturating_sub(1);
+                        }
+                    }
+                    termion::event::Key::Down => {
+                        if let Some(query) = self.scrollback_filter.as_ref().map(|f| f.query.clone())
+                        {
+                            let last = self.filtered_scrollback_lines(&query).len().saturating_sub(1);
+                            if let Some(ref mut filter) = self.scrollback_filter {
+                                filter.selected = (filter.selected + 1).min(last);
+                            }
+                        }
+                    }
+                    termion::event::Key::Backspace => {
+                        if let Some(ref mut filter) = self.scrollback_filter {
+                            filter.query.pop();
+                            filter.selected = 0;
+                        }
+                    }
+                    termion::event::Key::Char('\n') => {
+                        if let Some(filter) = self.scrollback_filter.take() {
+                            let lines = self.filtered_scrollback_lines(&filter.query);
+                            if let Some(line) = lines.get(filter.selected) {
+                                if let Some(process) = self.processes.get_mut(self.selected) {
+                                    process
+                                        .terminal_emulator
+                                        .scroll_to_buffer_line(line.buffer_line);
+                                }
+                            }
+                        }
+                    }
+                    // vim's yank mnemonic, since Enter above is already claimed for jumping the
+                    // viewport to the selected line instead of copying it.
+                    termion::event::Key::Ctrl('y') => {
+                        if let Some(filter) = self.scrollback_filter.take() {
+                            let lines = self.filtered_scrollback_lines(&filter.query);
+                            if let Some(line) = lines.get(filter.selected) {
+                                self.paste_buffers.push(None, line.text.clone());
+                            }
+                        }
+                    }
+                    termion::event::Key::Char(c) => {
+                        if let Some(ref mut filter) = self.scrollback_filter {
+                            filter.query.push(c);
+                            filter.selected = 0;
+                        }
+                    }
+                    _ => {}
+                }
+                true
+            }
+            termion::event::Event::Key(_) if self.showing_timeline => {
+                self.showing_timeline = false;
+                true
+            }
+            termion::event::Event::Key(key) if self.passthrough_pending => {
+                self.passthrough_pending = false;
+                let flags = self.processes[self.selected].kitty_keyboard_flags();
+                let encoded = kitty_csi_u(key, flags).or_else(|| {
+                    let process = &self.processes[self.selected];
+                    crate::input_encoding::encode_key(
+                        key,
+                        process.terminal_emulator.mode(),
+                        process.modify_other_keys(),
+                    )
+                });
+                match encoded {
+                    Some(bytes) => {
+                        self.queue_input_for_selected(bytes::Bytes::from(bytes));
+                        true
+                    }
+                    None => false,
+                }
+            }
+            termion::event::Event::Key(key) => match self.keybindings.resolve(key) {
+                Some(crate::keybindings::Command::NextPane) => {
+                    self.select_pane((self.selected + 1) % self.processes.len().max(1));
+                    true
+                }
+                Some(crate::keybindings::Command::PrevPane) => {
+                    self.select_pane(
+                        (self.selected + self.processes.len().max(1) - 1)
+                            % self.processes.len().max(1),
+                    );
+                    true
+                }
+                Some(crate::keybindings::Command::PanLeft) => {
+                    self.processes[self.selected].pan(-1);
+                    true
+                }
+                Some(crate::keybindings::Command::PanRight) => {
+                    self.processes[self.selected].pan(1);
+                    true
+                }
+                Some(crate::keybindings::Command::PasteBuffer) => {
+                    self.paste_buffer("");
+                    true
+                }
+                Some(crate::keybindings::Command::ChooseBuffer) => {
+                    self.open_buffer_chooser();
+                    true
+                }
+                Some(crate::keybindings::Command::ChoosePane) => {
+                    self.open_pane_chooser();
+                    true
+                }
+                Some(crate::keybindings::Command::Passthrough) => {
+                    self.passthrough_pending = true;
+                    self.status_message =
+                        Some("passthrough: forwarding the next key straight to the pane".to_owned());
+                    true
+                }
+                Some(crate::keybindings::Command::Quit) => false,
+                None => {
+                    let flags = self.processes[self.selected].kitty_keyboard_flags();
+                    let encoded = kitty_csi_u(key, flags).or_else(|| {
+                        let process = &self.processes[self.selected];
+                        crate::input_encoding::encode_key(
+                            key,
+                            process.terminal_emulator.mode(),
+                            process.modify_other_keys(),
+                        )
+                    });
+                    match encoded {
+                        // Goes to the selected pane only, even when `sync_input` is on: the
+                        // encoding depends on what that pane's own foreground program negotiated,
+                        // so there's no single re-encoded byte string that's correct to broadcast.
+                        Some(bytes) => {
+                            self.queue_input_for_selected(bytes::Bytes::from(bytes));
+                            true
+                        }
+                        None => false,
+                    }
+                }
+            },
             termion::event::Event::Mouse(m) => {
                 let (tabs_area, process_area) = self.layout(area);
                 let (x, y) = mouse_event_coords(&m);
@@ -174,7 +1673,7 @@ impl State {
                 if contains_point(tabs_area, x, y) {
                     match self.tabs().on_mouse_event(tabs_area, &m) {
                         Some(vertical_tabs::MouseAction::Select(selected)) => {
-                            self.selected = selected;
+                            self.select_pane(selected);
                         }
                         Some(vertical_tabs::MouseAction::ScrollUp) => {
                             self.scroll = 0.max(self.scroll as isize - 1) as usize;
@@ -231,6 +1730,154 @@ impl State {
             .scroll(self.scroll)
     }
 
+    /// `VerticalTabs` listing the paste buffers, newest last, for the `choose-buffer` overlay.
+    fn buffer_chooser(&self) -> vertical_tabs::VerticalTabs {
+        vertical_tabs::VerticalTabs::default()
+            .titles(
+                self.paste_buffers
+                    .iter()
+                    .map(|buffer| {
+                        vertical_tabs::Title::default()
+                            .text(&buffer.name)
+                            .preview(Some(buffer.contents.as_str()))
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .block(
+                tui::widgets::Block::default()
+                    .title("choose-buffer")
+                    .borders(tui::widgets::Borders::ALL),
+            )
+            .style(tui::style::Style::default())
+            .highlight_style(
+                tui::style::Style::default()
+                    .modifier(tui::style::Modifier::BOLD | tui::style::Modifier::UNDERLINED),
+            )
+            .select(self.choosing_buffer.unwrap_or(0))
+    }
+
+    /// Draw the full-screen `choose-pane` overlay: a filter prompt row over a `VerticalTabs` of
+    /// the panes it currently matches.
+    fn draw_pane_chooser(&mut self, area: tui::layout::Rect, buf: &mut tui::buffer::Buffer) {
+        let (query, selected) = match self.pane_chooser {
+            Some(ref chooser) => (chooser.query.clone(), chooser.selected),
+            None => return,
+        };
+
+        let chunks = tui::layout::Layout::default()
+            .direction(tui::layout::Direction::Vertical)
+            .constraints(
+                [
+                    tui::layout::Constraint::Length(1),
+                    tui::layout::Constraint::Min(0),
+                ]
+                .as_ref(),
+            )
+            .split(area);
+
+        tui::widgets::Paragraph::new(
+            [tui::widgets::Text::raw(format!("choose-pane: {}", query))].iter(),
+        )
+        .style(tui::style::Style::default().bg(tui::style::Color::Black))
+        .draw(chunks[0], buf);
+
+        let indices = self.filtered_pane_indices(&query);
+        let titles = indices
+            .iter()
+            .map(|&index| self.processes[index].tab_title())
+            .collect::<Vec<_>>();
+
+        vertical_tabs::VerticalTabs::default()
+            .titles(titles)
+            .block(
+                tui::widgets::Block::default()
+                    .title("choose-pane")
+                    .borders(tui::widgets::Borders::ALL),
+            )
+            .style(tui::style::Style::default())
+            .highlight_style(
+                tui::style::Style::default()
+                    .modifier(tui::style::Modifier::BOLD | tui::style::Modifier::UNDERLINED),
+            )
+            .select(selected)
+            .draw(chunks[1], buf);
+    }
+
+    /// Draw the full-screen `filter-pane` overlay: a filter prompt row over a `SelectableList` of
+    /// the scrollback lines it currently matches, mirroring `draw_pane_chooser`'s layout but over
+    /// plain text lines instead of pane tab titles.
+    fn draw_scrollback_filter(&mut self, area: tui::layout::Rect, buf: &mut tui::buffer::Buffer) {
+        let (query, selected) = match self.scrollback_filter {
+            Some(ref filter) => (filter.query.clone(), filter.selected),
+            None => return,
+        };
+
+        let chunks = tui::layout::Layout::default()
+            .direction(tui::layout::Direction::Vertical)
+            .constraints(
+                [
+                    tui::layout::Constraint::Length(1),
+                    tui::layout::Constraint::Min(0),
+                ]
+                .as_ref(),
+            )
+            .split(area);
+
+        tui::widgets::Paragraph::new(
+            [tui::widgets::Text::raw(format!("filter-pane: {}", query))].iter(),
+        )
+        .style(tui::style::Style::default().bg(tui::style::Color::Black))
+        .draw(chunks[0], buf);
+
+        let items = self
+            .filtered_scrollback_lines(&query)
+            .into_iter()
+            .map(|line| line.text)
+            .collect::<Vec<_>>();
+
+        tui::widgets::SelectableList::default()
+            .items(&items)
+            .block(
+                tui::widgets::Block::default()
+                    .title("filter-pane")
+                    .borders(tui::widgets::Borders::ALL),
+            )
+            .style(tui::style::Style::default())
+            .highlight_style(
+                tui::style::Style::default()
+                    .modifier(tui::style::Modifier::BOLD | tui::style::Modifier::UNDERLINED),
+            )
+            .select(Some(selected))
+            .draw(chunks[1], buf);
+    }
+
+    /// Draw the full-screen `:timeline` overlay: a sparkline of the selected pane's
+    /// `activity_history` (output bytes/second, oldest first), so "when did this job go quiet
+    /// overnight" is visible at a glance instead of scrolling back through the grid to find it.
+    /// Dismissed by any key press.
+    fn draw_timeline(&mut self, area: tui::layout::Rect, buf: &mut tui::buffer::Buffer) {
+        let process = match self.processes.get(self.selected) {
+            Some(process) => process,
+            None => return,
+        };
+
+        let data = process
+            .activity_history
+            .iter()
+            .copied()
+            .collect::<Vec<_>>();
+
+        tui::widgets::Sparkline::default()
+            .block(
+                tui::widgets::Block::default()
+                    .title(&format!("timeline: {} (bytes/sec)", process.title))
+                    .borders(tui::widgets::Borders::ALL),
+            )
+            .style(tui::style::Style::default().fg(tui::style::Color::Cyan))
+            .data(&data)
+            .draw(area, buf);
+    }
+
     fn take_process_inputs<'a>(
         &'a mut self,
     ) -> impl Iterator<Item = (usize, bytes::BytesMut)> + 'a {
@@ -239,49 +1886,314 @@ impl State {
             .enumerate()
             .flat_map(|(idx, process)| process.take_process_input().map(|d| (idx, d)))
     }
+
+    fn take_pending_actions(&mut self) -> Vec<Action> {
+        std::mem::replace(&mut self.pending_actions, Vec::new())
+    }
+
+    fn take_pending_urls<'a>(&'a mut self) -> impl Iterator<Item = String> + 'a {
+        self.processes
+            .iter_mut()
+            .filter_map(ProcessState::take_pending_url)
+    }
+
+    fn sync_input(&self) -> bool {
+        self.sync_input
+    }
+
+    fn queue_input_for_selected(&mut self, data: bytes::Bytes) {
+        let process = match self.processes.get_mut(self.selected) {
+            Some(process) => process,
+            None => return,
+        };
+        if process.read_only {
+            return;
+        }
+        process.latency.record_keystroke();
+        self.pending_actions.push(Action::ProcessInput {
+            index: self.selected,
+            data,
+        });
+    }
+
+    /// Queue `data` for every pane except those marked `read_only`, for `sync-input on`
+    /// broadcasts - a per-pane flag that "discards all input" has to apply here too, not just to
+    /// direct typing, or a read-only log-tail pane would still take keystrokes whenever another
+    /// pane is being typed into at the same time.
+    fn queue_input_for_writable_panes(&mut self, data: bytes::Bytes) {
+        for (index, process) in self.processes.iter_mut().enumerate() {
+            if process.read_only {
+                continue;
+            }
+            process.latency.record_keystroke();
+            self.pending_actions.push(Action::ProcessInput {
+                index,
+                data: data.clone(),
+            });
+        }
+    }
+
+    /// `p50`/`p90`/`p99` keystroke-to-echo latency for the selected pane, formatted for the
+    /// status line, or `None` if it hasn't produced a round trip yet.
+    fn latency_summary(&self) -> Option<String> {
+        let process = self.processes.get(self.selected)?;
+        let (p50, p90, p99) = process.latency.percentiles()?;
+        Some(format!(
+            "latency: p50={:?} p90={:?} p99={:?} (n={})",
+            p50,
+            p90,
+            p99,
+            process.latency.sample_count(),
+        ))
+    }
+}
+
+/// Parse a `<N><unit>` duration like `30s`/`5m`/`2h`/`1d` (or a bare `<N>`, `:monitor-silence`
+/// style, meaning seconds) into a number of seconds, for `:jump-to-time`.
+fn parse_duration_secs(spec: &str) -> Option<u64> {
+    let spec = spec.trim();
+    let split = spec.find(|c: char| !c.is_ascii_digit()).unwrap_or(spec.len());
+    let (digits, unit) = spec.split_at(split);
+    let amount: u64 = digits.parse().ok()?;
+    let multiplier = match unit {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return None,
+    };
+    Some(amount * multiplier)
+}
+
+/// Parse a signal name (`"TERM"`, `"SIGKILL"`, ...) or raw number into its libc value.
+fn parse_signal(spec: &str) -> Option<libc::c_int> {
+    if spec.is_empty() {
+        return None;
+    }
+
+    match spec.trim().to_ascii_uppercase().trim_start_matches("SIG") {
+        "TERM" => Some(libc::SIGTERM),
+        "KILL" => Some(libc::SIGKILL),
+        "INT" => Some(libc::SIGINT),
+        "HUP" => Some(libc::SIGHUP),
+        "QUIT" => Some(libc::SIGQUIT),
+        other => other.parse().ok(),
+    }
 }
 
 impl tui::widgets::Widget for State {
     fn draw(&mut self, area: tui::layout::Rect, buf: &mut tui::buffer::Buffer) {
+        if self.pane_chooser.is_some() {
+            self.draw_pane_chooser(area, buf);
+            return;
+        }
+
+        if self.scrollback_filter.is_some() {
+            self.draw_scrollback_filter(area, buf);
+            return;
+        }
+
+        if self.showing_timeline {
+            self.draw_timeline(area, buf);
+            return;
+        }
+
         let (tabs_area, process_area) = self.layout(area);
 
         self.tabs().draw(tabs_area, buf);
 
         self.processes[self.selected].draw(process_area, buf);
+
+        if self.choosing_buffer.is_some() {
+            self.buffer_chooser().draw(process_area, buf);
+        }
+
+        if let Some(ref command_line) = self.command_line {
+            let prompt_area = tui::layout::Rect {
+                y: area.bottom().saturating_sub(1),
+                height: 1,
+                ..area
+            };
+            tui::widgets::Paragraph::new([tui::widgets::Text::raw(format!(":{}", command_line))].iter())
+                .style(tui::style::Style::default().bg(tui::style::Color::Black))
+                .draw(prompt_area, buf);
+        } else if let Some(ref status_message) = self.status_message {
+            let prompt_area = tui::layout::Rect {
+                y: area.bottom().saturating_sub(1),
+                height: 1,
+                ..area
+            };
+            tui::widgets::Paragraph::new([tui::widgets::Text::raw(status_message.clone())].iter())
+                .style(
+                    tui::style::Style::default()
+                        .fg(tui::style::Color::Yellow)
+                        .bg(tui::style::Color::Black),
+                )
+                .draw(prompt_area, buf);
+        }
     }
 }
 
 impl ProcessState {
-    fn from_settings(settings: ProcessSettings) -> Self {
+    fn from_settings(settings: ProcessSettings) -> Result<Self, failure::Error> {
         use terminal_emulator::Handler;
 
-        let mut terminal_emulator =
-            terminal_emulator::term::Term::new(terminal_emulator::term::SizeInfo {
-                width: 80.0,
+        let mut terminal_emulator = terminal_emulator::term::Term::with_scrollback(
+            terminal_emulator::term::SizeInfo {
+                width: 80.0 + settings.horizontal_overscan as f32,
                 height: 24.0,
                 cell_width: 1.0,
                 cell_height: 1.0,
                 padding_x: 0.0,
                 padding_y: 0.0,
                 dpr: 1.0,
-            });
+            },
+            settings.scrollback_lines,
+        )?;
+        terminal_emulator.set_ambiguous_wide(settings.ambiguous_wide_chars);
+        terminal_emulator.set_semantic_escape_chars(&settings.word_separators);
         let processor = terminal_emulator::Processor::new();
         let exit_status = None;
         let input = Vec::new();
+        let usage = Usage::default();
+        let has_descendants = false;
+        let preview = String::new();
+        let color_mode = settings.color_mode;
+        let color_depth = settings.color_depth;
+        let min_contrast = settings.min_contrast;
+        let horizontal_overscan = settings.horizontal_overscan;
+        let scroll_lines = settings.scroll_lines;
 
         terminal_emulator.set_title(&settings.initial_title);
         let title = settings.initial_title;
 
-        Self {
+        Ok(Self {
             terminal_emulator,
             processor,
             title,
             exit_status,
             input,
+            usage,
+            has_descendants,
+            foreground_command: None,
+            preview,
+            color_mode,
+            color_depth,
+            min_contrast,
+            horizontal_overscan,
+            hscroll: 0,
+            scroll_lines,
+            bell: false,
+            activity: false,
+            silent: false,
+            last_output: std::time::Instant::now(),
+            pending_url: None,
+            latency: latency::LatencyTracker::default(),
+            read_only: false,
+            is_clone: false,
+            activity_history: std::collections::VecDeque::with_capacity(ACTIVITY_HISTORY_LEN),
+            bytes_since_tick: 0,
+            accessibility_lines: Vec::new(),
+            accessibility_cursor: None,
+            pipe: None,
+            marks: Vec::new(),
+            notified: false,
+        })
+    }
+
+    /// Snapshot this pane into a new, read-only pane with no pty behind it, for `:clone-pane`:
+    /// the clone's `Term` (grid, scrollback, cursor, modes, everything `Term::clone` carries)
+    /// is a point-in-time copy, so it keeps showing exactly what this pane looked like when
+    /// cloned even as this pane keeps receiving live output.
+    fn clone_snapshot(&self) -> Self {
+        Self {
+            terminal_emulator: self.terminal_emulator.clone(),
+            processor: terminal_emulator::Processor::new(),
+            title: format!("{} (clone)", self.title),
+            exit_status: None,
+            input: Vec::new(),
+            usage: Usage::default(),
+            has_descendants: false,
+            foreground_command: None,
+            preview: self.preview.clone(),
+            color_mode: self.color_mode,
+            color_depth: self.color_depth,
+            min_contrast: self.min_contrast,
+            horizontal_overscan: self.horizontal_overscan,
+            hscroll: self.hscroll,
+            scroll_lines: self.scroll_lines,
+            bell: false,
+            activity: false,
+            silent: false,
+            last_output: std::time::Instant::now(),
+            pending_url: None,
+            latency: latency::LatencyTracker::default(),
+            read_only: true,
+            is_clone: true,
+            activity_history: self.activity_history.clone(),
+            bytes_since_tick: 0,
+            accessibility_lines: Vec::new(),
+            accessibility_cursor: None,
+            pipe: None,
+            marks: Vec::new(),
+            notified: false,
+        }
+    }
+
+    /// Pan the viewport by `delta` columns, clamped to `0..=horizontal_overscan`.
+    fn pan(&mut self, delta: isize) {
+        let max = self.horizontal_overscan as isize;
+        self.hscroll = (self.hscroll as isize + delta).max(0).min(max) as usize;
+    }
+
+    /// Kitty keyboard protocol enhancement flags currently negotiated by this pane (`0` if the
+    /// foreground program never asked for any), from `CSI u` mode pushes it sent us.
+    fn kitty_keyboard_flags(&self) -> u8 {
+        self.terminal_emulator.keyboard_mode_flags()
+    }
+
+    /// xterm `modifyOtherKeys` level currently negotiated by this pane (`0` if the foreground
+    /// program never asked for any), from `CSI > 4 ; level m`.
+    fn modify_other_keys(&self) -> u8 {
+        self.terminal_emulator.modify_other_keys()
+    }
+
+    /// Text written to the system clipboard via OSC 52 since the last call, if any.
+    fn take_clipboard(&mut self) -> Option<String> {
+        self.terminal_emulator.take_clipboard()
+    }
+
+    /// Desktop notification requested via OSC 9/777 since the last call, if any.
+    fn take_notification(&mut self) -> Option<(Option<String>, String)> {
+        self.terminal_emulator.take_notification()
+    }
+
+    /// Attach (or, with `None`, detach) the command `:pipe-pane` is teeing this pane's raw
+    /// output to, replacing whatever was attached before.
+    fn set_pipe(&mut self, stdin: Option<std::process::ChildStdin>) {
+        self.pipe = stdin;
+    }
+
+    /// Write a copy of `data` to the piped command's stdin, detaching it if the write fails,
+    /// e.g. because the command already exited.
+    fn pipe_data(&mut self, data: &[u8]) {
+        use std::io::Write;
+
+        let pipe = match &mut self.pipe {
+            Some(pipe) => pipe,
+            None => return,
+        };
+        if pipe.write_all(data).is_err() {
+            self.pipe = None;
         }
     }
 
-    fn on_data(&mut self, data: bytes::Bytes) {
+    /// Feed output to the terminal emulator, returning `true` if this call rang the bell.
+    fn on_data(&mut self, data: bytes::Bytes) -> bool {
+        if !data.is_empty() {
+            self.latency.record_echo();
+        }
+
         for byte in data {
             // TODO: maybe do something smarter than passing sink() here
             self.processor
@@ -291,16 +2203,241 @@ impl ProcessState {
         if let Some(title) = self.terminal_emulator.get_next_title() {
             self.title = title;
         }
+
+        self.activity = true;
+        self.silent = false;
+        self.last_output = std::time::Instant::now();
+        self.bytes_since_tick += data.len() as u64;
+        self.preview = self.compute_preview_line();
+        self.stamp_current_line_time();
+
+        let rang = self.terminal_emulator.take_bell();
+        self.bell = self.bell || rang;
+        rang
+    }
+
+    /// Tag the buffer line the cursor just finished writing to with the current wall-clock
+    /// time, for `:jump-to-time` to search over later. Stamped once per `on_data` chunk rather
+    /// than once per line, so a pane producing output a byte at a time doesn't end up doing a
+    /// `set_line_metadata` call per byte.
+    fn stamp_current_line_time(&mut self) {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+        let line = self
+            .terminal_emulator
+            .grid()
+            .visible_to_buffer(self.terminal_emulator.cursor().point)
+            .line;
+        let timestamp = now.to_string();
+        self.terminal_emulator
+            .set_line_metadata(line, &[("time", timestamp.as_str())]);
+    }
+
+    /// Clear the unread bell/activity flags, e.g. once the pane has been selected and viewed.
+    fn clear_activity(&mut self) {
+        self.bell = false;
+        self.activity = false;
+        self.notified = false;
+    }
+
+    /// Fold `bytes_since_tick` into `activity_history` as this second's bucket and reset it,
+    /// dropping the oldest bucket once the history is full.
+    fn record_activity_tick(&mut self) {
+        if self.activity_history.len() >= ACTIVITY_HISTORY_LEN {
+            self.activity_history.pop_front();
+        }
+        self.activity_history.push_back(self.bytes_since_tick);
+        self.bytes_since_tick = 0;
+    }
+
+    /// Debounced line/cursor diff against the pane's visible screen, for
+    /// `Config::accessibility_events`. Called once a tick (once a second) rather than per byte
+    /// of output, so a burst of redraws settles on one coherent set of changes instead of
+    /// reporting every intermediate frame a screen reader would have to keep up with.
+    fn accessibility_changes(&mut self, index: usize) -> Vec<crate::event_bus::Event> {
+        use crate::event_bus::Event;
+
+        let lines: Vec<String> = self
+            .terminal_emulator
+            .render_text()
+            .split('\n')
+            .map(str::to_owned)
+            .collect();
+
+        let mut events = Vec::new();
+
+        let went_blank = self.accessibility_lines.iter().any(|line| !line.is_empty())
+            && lines.iter().all(|line| line.is_empty());
+
+        if went_blank {
+            events.push(Event::ScreenCleared { pane: index });
+        } else {
+            for (line, new_text) in lines.iter().enumerate() {
+                let old_text = self.accessibility_lines.get(line).map_or("", String::as_str);
+                if old_text != new_text.as_str() {
+                    events.push(Event::LineChanged {
+                        pane: index,
+                        line,
+                        text: new_text.clone(),
+                    });
+                }
+            }
+        }
+        self.accessibility_lines = lines;
+
+        let point = self.terminal_emulator.cursor().point;
+        let cursor = (point.line.0, point.col.0);
+        if self.accessibility_cursor != Some(cursor) {
+            self.accessibility_cursor = Some(cursor);
+            events.push(Event::CursorMoved {
+                pane: index,
+                line: cursor.0,
+                col: cursor.1,
+            });
+        }
+
+        events
     }
 
     fn on_exit(&mut self, status: std::process::ExitStatus) {
         self.exit_status = Some(status);
     }
 
-    fn on_user_input(&mut self, _area: tui::layout::Rect, _event: &termion::event::Event) -> bool {
+    fn on_usage(
+        &mut self,
+        cpu_ticks: u64,
+        rss_bytes: u64,
+        read_bytes: u64,
+        write_bytes: u64,
+        has_descendants: bool,
+        foreground_command: Option<String>,
+    ) {
+        self.usage = Usage {
+            cpu_ticks,
+            rss_bytes,
+            read_bytes,
+            write_bytes,
+        };
+        self.has_descendants = has_descendants;
+        self.foreground_command = foreground_command;
+    }
+
+    fn on_user_input(&mut self, area: tui::layout::Rect, event: &termion::event::Event) -> bool {
+        let m = match *event {
+            termion::event::Event::Mouse(ref m) => m,
+            _ => return true,
+        };
+
+        // When the foreground app has asked for mouse reporting, it wants to handle clicks,
+        // drags, and the wheel itself (e.g. `vim`, `htop`); forward the raw bytes instead of
+        // doing any of the local hit-testing below. Unlike xterm, there's no way to hold a
+        // modifier to force local handling here, since `termion`'s `MouseEvent` doesn't carry
+        // modifier state.
+        if self.terminal_emulator.mode().intersects(
+            terminal_emulator::term::TermMode::MOUSE_REPORT_CLICK
+                | terminal_emulator::term::TermMode::MOUSE_DRAG
+                | terminal_emulator::term::TermMode::MOUSE_MOTION,
+        ) {
+            return false;
+        }
+
+        match *m {
+            termion::event::MouseEvent::Press(termion::event::MouseButton::Left, ..) => {
+                let (x, y) = mouse_event_coords(m);
+                if let Some(point) = self.grid_point(area, x, y) {
+                    self.pending_url = self
+                        .terminal_emulator
+                        .visible_urls()
+                        .into_iter()
+                        .find(|url_match| url_match.start <= point && point <= url_match.end)
+                        .map(|url_match| url_match.text);
+
+                    let buffer_point = self.terminal_emulator.grid().visible_to_buffer(point);
+                    *self.terminal_emulator.selection_mut() = Some(
+                        terminal_emulator::selection::Selection::simple(
+                            buffer_point,
+                            terminal_emulator::index::Side::Left,
+                        ),
+                    );
+                }
+            }
+            termion::event::MouseEvent::Hold(x, y) => {
+                if let Some(point) = self.grid_point(area, x, y) {
+                    let buffer_point = self.terminal_emulator.grid().visible_to_buffer(point);
+                    if let Some(selection) = self.terminal_emulator.selection_mut() {
+                        selection.update(buffer_point, terminal_emulator::index::Side::Right);
+                    }
+                }
+            }
+            termion::event::MouseEvent::Press(termion::event::MouseButton::WheelUp, ..) => {
+                self.scroll_or_send_key(true);
+            }
+            termion::event::MouseEvent::Press(termion::event::MouseButton::WheelDown, ..) => {
+                self.scroll_or_send_key(false);
+            }
+            _ => {}
+        }
         true
     }
 
+    /// Translate pane-relative mouse coordinates into a grid point, or `None` if they land
+    /// outside `area`.
+    fn grid_point(
+        &self,
+        area: tui::layout::Rect,
+        x: u16,
+        y: u16,
+    ) -> Option<terminal_emulator::index::Point> {
+        if x >= area.x && y >= area.y {
+            Some(terminal_emulator::index::Point::new(
+                terminal_emulator::index::Line((y - area.y) as usize),
+                terminal_emulator::index::Column((x - area.x) as usize + self.hscroll),
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// Scroll the viewport by one wheel tick, or, on the alt screen (which has no scrollback of
+    /// its own), send the arrow key press an app without mouse reporting would expect instead —
+    /// the same fallback xterm-family terminals use for wheel input to alt-screen apps like
+    /// `less`/`vim` that haven't turned on mouse reporting.
+    fn scroll_or_send_key(&mut self, up: bool) {
+        if self
+            .terminal_emulator
+            .mode()
+            .contains(terminal_emulator::term::TermMode::ALT_SCREEN)
+        {
+            let key = if up {
+                termion::event::Key::Up
+            } else {
+                termion::event::Key::Down
+            };
+            // `modify_other_keys` doesn't apply here: it only concerns printable/control
+            // characters, and the arrow keys always go through the DECCKM CSI/SS3 branch.
+            if let Some(sequence) =
+                crate::input_encoding::encode_key(key, self.terminal_emulator.mode(), 0)
+            {
+                self.input.extend_from_slice(&sequence);
+            }
+        } else {
+            let lines = if up {
+                self.scroll_lines as isize
+            } else {
+                -(self.scroll_lines as isize)
+            };
+            self.terminal_emulator
+                .scroll_display(terminal_emulator::grid::Scroll::Lines(lines));
+        }
+    }
+
+    /// URL or path clicked in this pane's grid since the last call, if any.
+    fn take_pending_url(&mut self) -> Option<String> {
+        self.pending_url.take()
+    }
+
     fn take_process_input(&mut self) -> Option<bytes::BytesMut> {
         use std::mem;
 
@@ -312,10 +2449,121 @@ impl ProcessState {
         }
     }
 
+    /// First non-blank line currently on screen, trimmed, as a cheap stand-in for a visual
+    /// thumbnail: the tab list is one text row per pane, so there's no room for an actual
+    /// rendered preview, but a line of live content is still more useful than the title alone.
+    fn compute_preview_line(&self) -> String {
+        use std::collections::BTreeMap;
+
+        let mut cells: BTreeMap<(usize, usize), char> = BTreeMap::new();
+        for cell in self.terminal_emulator.renderable_cells() {
+            cells.insert((cell.line.0, cell.column.0), cell.chars[0]);
+        }
+
+        let mut lines: BTreeMap<usize, String> = BTreeMap::new();
+        for ((line, _column), ch) in cells {
+            lines.entry(line).or_insert_with(String::new).push(ch);
+        }
+
+        lines
+            .into_iter()
+            .map(|(_, line)| line.trim().to_owned())
+            .find(|line| !line.is_empty())
+            .unwrap_or_default()
+    }
+
+    /// Status line shown after the title: the foreground command and working directory when
+    /// known (tmux `pane_current_command`/`pane_current_path` style), or `None` when neither is
+    /// available yet (`tab_title` then falls back to a snippet of on-screen content).
+    fn status_line(&self) -> Option<String> {
+        match (&self.foreground_command, self.terminal_emulator.cwd()) {
+            (Some(command), Some(cwd)) => Some(format!("{} {}", command, cwd)),
+            (Some(command), None) => Some(command.clone()),
+            (None, Some(cwd)) => Some(cwd.to_owned()),
+            (None, None) => None,
+        }
+    }
+
+    /// Whether this pane's title or status line contains `query` (already lowercased), for the
+    /// `choose-pane` overlay's live filter.
+    fn matches_filter(&self, lowercase_query: &str) -> bool {
+        if lowercase_query.is_empty() {
+            return true;
+        }
+
+        self.title.to_lowercase().contains(lowercase_query)
+            || self
+                .status_line()
+                .map_or(false, |line| line.to_lowercase().contains(lowercase_query))
+    }
+
     fn tab_title(&self) -> vertical_tabs::Title {
-        let mut title = vertical_tabs::Title::default()
-            .text(&self.title)
-            .style(tui::style::Style::default());
+        let status_line = self.status_line();
+        let preview = if self.exit_status.is_some() {
+            None
+        } else if let Some(ref status_line) = status_line {
+            Some(status_line.as_str())
+        } else if !self.preview.is_empty() {
+            Some(self.preview.as_str())
+        } else {
+            None
+        };
+
+        let mut symbols = Vec::new();
+
+        if self.read_only {
+            symbols.push(tui::widgets::Text::Styled(
+                "🔒".into(),
+                tui::style::Style::default().fg(tui::style::Color::DarkGray),
+            ));
+        }
+
+        if !self.terminal_emulator.auto_scroll() {
+            symbols.push(tui::widgets::Text::Styled(
+                "📜".into(),
+                tui::style::Style::default().fg(tui::style::Color::DarkGray),
+            ));
+        }
+
+        if self.notified {
+            symbols.push(tui::widgets::Text::Styled(
+                "📣".into(),
+                tui::style::Style::default()
+                    .fg(tui::style::Color::Magenta)
+                    .modifier(tui::style::Modifier::BOLD),
+            ));
+        }
+
+        if self.bell {
+            symbols.push(tui::widgets::Text::Styled(
+                "🔔".into(),
+                tui::style::Style::default()
+                    .fg(tui::style::Color::Yellow)
+                    .modifier(tui::style::Modifier::BOLD),
+            ));
+        } else if self.activity {
+            symbols.push(tui::widgets::Text::Styled(
+                "●".into(),
+                tui::style::Style::default().fg(tui::style::Color::Cyan),
+            ));
+        }
+
+        if self.silent {
+            symbols.push(tui::widgets::Text::Styled(
+                "💤".into(),
+                tui::style::Style::default().fg(tui::style::Color::Blue),
+            ));
+        }
+
+        if self.usage.rss_bytes > 0 {
+            let mb = self.usage.rss_bytes / (1024 * 1024);
+            symbols.push(tui::widgets::Text::Styled(
+                format!("{}MB", mb).into(),
+                tui::style::Style::default()
+                    .fg(tui::style::Color::DarkGray)
+                    .modifier(tui::style::Modifier::DIM),
+            ));
+        }
 
         if let Some(ref exit_status) = self.exit_status {
             let style = if exit_status.success() {
@@ -333,10 +2581,14 @@ impl ProcessState {
                 "☇".into()
             };
 
-            title = title.symbols(vec![tui::widgets::Text::Styled(symbol, style)])
+            symbols.push(tui::widgets::Text::Styled(symbol, style));
         }
 
-        title
+        vertical_tabs::Title::default()
+            .text(&self.title)
+            .preview(preview)
+            .style(tui::style::Style::default())
+            .symbols(symbols)
     }
 }
 
@@ -353,8 +2605,11 @@ impl tui::widgets::Widget for ProcessState {
         let status_chunk = chunks[1];
 
         for cell in self.terminal_emulator.renderable_cells() {
+            if cell.column.0 < self.hscroll {
+                continue;
+            }
             #[allow(clippy::cast_possible_truncation)]
-            let x = cell.column.0 as u16;
+            let x = (cell.column.0 - self.hscroll) as u16;
             #[allow(clippy::cast_possible_truncation)]
             let y = cell.line.0 as u16;
             if x < main_chunk.width && y < main_chunk.height {
@@ -362,8 +2617,11 @@ impl tui::widgets::Widget for ProcessState {
                 let y = main_chunk.y + y;
                 let buf_cell = buf.get_mut(x, y);
                 buf_cell.set_char(cell.chars[0]);
-                buf_cell.set_bg(convert_color(cell.bg));
-                buf_cell.set_fg(convert_color(cell.fg));
+                let bg = self.color_mode.remap(convert_color(cell.bg));
+                let fg = self.color_mode.remap(convert_color(cell.fg));
+                let fg = crate::palette::enforce_min_contrast(self.min_contrast, fg, bg);
+                buf_cell.set_bg(self.color_depth.quantize(bg));
+                buf_cell.set_fg(self.color_depth.quantize(fg));
                 buf_cell.set_modifier(convert_flags(cell.flags));
             }
         }
@@ -406,6 +2664,48 @@ fn mouse_event_coords(event: &termion::event::MouseEvent) -> (u16, u16) {
     }
 }
 
+/// Kitty keyboard protocol "disambiguate escape codes" flag (bit 0 of the enhancement flags set
+/// via `CSI u`); see <https://sw.kovidgoyal.net/kitty/keyboard-protocol/#progressive-enhancement>.
+const KITTY_DISAMBIGUATE: u8 = 0b1;
+
+/// Encode `key` as a kitty keyboard protocol `CSI u` sequence, if the pane has asked for
+/// disambiguated escape codes (`flags & KITTY_DISAMBIGUATE != 0`) and `key` is one the protocol
+/// would otherwise render ambiguously.
+///
+/// This only covers `Ctrl`/`Alt`-modified characters and a bare `Esc`, which is what actually
+/// collides with legacy control-character encoding (e.g. `Ctrl+I` and `Tab` are both `0x09`).
+/// Arrow keys, function keys, and plain unmodified characters keep going through the existing
+/// raw-byte path: `termion`'s `Key` has no functional-key codepoint table to look up (kitty's
+/// reserved Unicode private-use range), and plain characters were never ambiguous in the first
+/// place. `termion` also has no key-release events and folds shift into the character case
+/// itself, so neither shows up here either - the same gap as the mouse-modifier one noted on
+/// `ProcessState::on_user_input`.
+fn kitty_csi_u(key: termion::event::Key, flags: u8) -> Option<Vec<u8>> {
+    use termion::event::Key;
+
+    if flags & KITTY_DISAMBIGUATE == 0 {
+        return None;
+    }
+
+    // Bit 2 of the kitty modifier field; bit 1 (shift) is never set, for the reasons above.
+    const MOD_ALT: u32 = 0b10;
+    const MOD_CTRL: u32 = 0b100;
+
+    let (codepoint, modifiers) = match key {
+        Key::Esc => (27, 0),
+        Key::Ctrl(c) => (c as u32, MOD_CTRL),
+        Key::Alt(c) => (c as u32, MOD_ALT),
+        _ => return None,
+    };
+
+    let mut sequence = format!("\x1b[{}", codepoint);
+    if modifiers != 0 {
+        sequence.push_str(&format!(";{}", modifiers + 1));
+    }
+    sequence.push('u');
+    Some(sequence.into_bytes())
+}
+
 fn convert_color(color: terminal_emulator::ansi::Color) -> tui::style::Color {
     match color {
         terminal_emulator::ansi::Color::Named(named) => match named {
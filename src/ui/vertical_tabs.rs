@@ -11,6 +11,8 @@ pub struct VerticalTabs<'a> {
 #[derive(Default)]
 pub struct Title<'a> {
     text: &'a str,
+    /// Short dim preview text drawn right after `text`, space permitting.
+    preview: Option<&'a str>,
     symbols: Vec<tui::widgets::Text<'a>>,
     style: tui::style::Style,
 }
@@ -202,6 +204,11 @@ impl<'a> Title<'a> {
         self
     }
 
+    pub fn preview(mut self, preview: Option<&'a str>) -> Self {
+        self.preview = preview;
+        self
+    }
+
     pub fn style(mut self, style: tui::style::Style) -> Self {
         self.style = style;
         self
@@ -231,10 +238,27 @@ impl<'a> tui::widgets::Widget for Title<'a> {
             area.width -= char_count as u16 + 1;
         }
 
-        if unicode_segmentation::UnicodeSegmentation::graphemes(self.text, true).count()
-            <= area.width as usize
-        {
+        let text_len =
+            unicode_segmentation::UnicodeSegmentation::graphemes(self.text, true).count();
+
+        if text_len <= area.width as usize {
             buf.set_stringn(area.x, area.y, self.text, area.width as usize, self.style);
+
+            let remaining = area.width as usize - text_len;
+            if let Some(preview) = self.preview {
+                // Leave a one-column gap between the title and its preview.
+                if remaining > 1 {
+                    buf.set_stringn(
+                        area.x + text_len as u16 + 1,
+                        area.y,
+                        preview,
+                        remaining - 1,
+                        tui::style::Style::default()
+                            .fg(tui::style::Color::DarkGray)
+                            .modifier(tui::style::Modifier::DIM),
+                    );
+                }
+            }
         } else {
             buf.set_stringn(
                 area.x,
@@ -0,0 +1,117 @@
+//! Benchmarks for the operations on `Term`'s grid and scrollback that don't go through the ANSI
+//! parser at all: scrolling a bounded region, resizing a terminal with a large scrollback, and
+//! extracting a selection spanning many wrapped lines.
+//!
+//! Run with `cargo bench`; compare against a saved baseline with
+//! `cargo bench -- --save-baseline before` and `cargo bench -- --baseline before` after a change.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use terminal_emulator::ansi::Processor;
+use terminal_emulator::index;
+use terminal_emulator::selection::Selection;
+use terminal_emulator::term::{SizeInfo, Term};
+
+fn size() -> SizeInfo {
+    SizeInfo {
+        width: 720.0,
+        height: 432.0,
+        cell_width: 9.0,
+        cell_height: 18.0,
+        padding_x: 0.0,
+        padding_y: 0.0,
+        dpr: 1.0,
+    }
+}
+
+fn feed(term: &mut Term, bytes: &[u8]) {
+    let mut processor = Processor::new();
+    for &byte in bytes {
+        processor.advance(term, byte, &mut std::io::sink());
+    }
+}
+
+/// Set a scroll region covering the middle two thirds of the screen (DECSTBM), then scroll it by
+/// writing newlines past its bottom margin until it's wrapped around several times over.
+fn scroll_in_region(c: &mut Criterion) {
+    let lines = size().lines().0;
+    let top = lines / 6;
+    let bottom = lines - lines / 6;
+    let mut region = format!("\x1b[{};{}r\x1b[{};1H", top + 1, bottom, bottom);
+    for _ in 0..lines * 4 {
+        region.push_str("scrolling in a bounded region\n");
+    }
+    let region = region.into_bytes();
+
+    c.bench_function("scroll_in_region", |b| {
+        b.iter(|| {
+            let mut term = Term::new(size()).unwrap();
+            feed(&mut term, &region);
+        });
+    });
+}
+
+/// Resize a terminal that's accumulated a full scrollback of real (not blank) lines back and
+/// forth between two sizes with `Term::resize`.
+fn resize_with_large_scrollback(c: &mut Criterion) {
+    let scrollback_lines = 10_000;
+    let mut term = Term::with_scrollback(size(), scrollback_lines).unwrap();
+    let mut fill = String::new();
+    for i in 0..scrollback_lines * 2 {
+        fill.push_str(&format!("line {} of scrollback filler text\n", i));
+    }
+    feed(&mut term, fill.as_bytes());
+
+    let mut wide = size();
+    wide.width *= 2.0;
+
+    c.bench_function("resize_with_large_scrollback", |b| {
+        b.iter(|| {
+            term.resize(&wide);
+            term.resize(&size());
+        });
+    });
+}
+
+/// Select every line of a screen that's entirely soft-wrapped (one logical line spanning every
+/// row via `WRAPLINE`) and extract it as a string, the same path `:copy-last-output` goes
+/// through (`Term::selection_to_string`).
+fn selection_to_string_on_wrapped_lines(c: &mut Criterion) {
+    let size = size();
+    let mut term = Term::new(size).unwrap();
+    let cols = size.cols().0;
+    let lines = size.lines().0;
+
+    let mut wrapped = String::new();
+    for _ in 0..lines {
+        wrapped.push_str(&"x".repeat(cols));
+    }
+    feed(&mut term, wrapped.as_bytes());
+
+    let mut selection = Selection::simple(
+        index::Point {
+            line: lines - 1,
+            col: index::Column(0),
+        },
+        index::Side::Left,
+    );
+    selection.update(
+        index::Point {
+            line: 0,
+            col: index::Column(cols - 1),
+        },
+        index::Side::Right,
+    );
+    *term.selection_mut() = Some(selection);
+
+    c.bench_function("selection_to_string_on_wrapped_lines", |b| {
+        b.iter(|| term.selection_to_string());
+    });
+}
+
+criterion_group!(
+    benches,
+    scroll_in_region,
+    resize_with_large_scrollback,
+    selection_to_string_on_wrapped_lines
+);
+criterion_main!(benches);
@@ -0,0 +1,76 @@
+//! Throughput benchmarks for feeding bytes through `ansi::Processor` into a `Term`, the same
+//! per-byte loop `mux` itself runs in `ProcessState::on_data` (see `src/ui/mod.rs`).
+//!
+//! Run with `cargo bench`; compare against a saved baseline with
+//! `cargo bench -- --save-baseline before` and `cargo bench -- --baseline before` after a change.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use terminal_emulator::ansi::Processor;
+use terminal_emulator::term::{SizeInfo, Term};
+
+fn size() -> SizeInfo {
+    SizeInfo {
+        width: 720.0,
+        height: 432.0,
+        cell_width: 9.0,
+        cell_height: 18.0,
+        padding_x: 0.0,
+        padding_y: 0.0,
+        dpr: 1.0,
+    }
+}
+
+/// Plain ASCII text with a line feed after every 54 characters (short of the 80-column screen,
+/// so nothing soft-wraps), no escape sequences at all - the cheapest
+/// possible thing the parser can be asked to do, and a floor for every other benchmark here.
+fn ascii_payload(bytes: usize) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(bytes);
+    while payload.len() < bytes {
+        payload.extend_from_slice(b"the quick brown fox jumps over the lazy dog 0123456789\n");
+    }
+    payload.truncate(bytes);
+    payload
+}
+
+/// Every character individually wrapped in an SGR reset/color pair, the worst case for a
+/// real-world build log or `ls --color` stream: one full escape-sequence parse per cell written
+/// instead of one parse per whole line.
+fn heavy_sgr_payload(bytes: usize) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(bytes);
+    let mut color = 31;
+    while payload.len() < bytes {
+        payload.extend_from_slice(format!("\x1b[{}mx\x1b[0m", color).as_bytes());
+        color = if color == 36 { 31 } else { color + 1 };
+        if payload.len() % 80 == 0 {
+            payload.push(b'\n');
+        }
+    }
+    payload.truncate(bytes);
+    payload
+}
+
+fn bench_payload(c: &mut Criterion, name: &str, payload: Vec<u8>) {
+    let mut group = c.benchmark_group(name);
+    group.throughput(Throughput::Bytes(payload.len() as u64));
+    group.bench_with_input(BenchmarkId::from_parameter(payload.len()), &payload, |b, payload| {
+        b.iter(|| {
+            let mut term = Term::new(size()).unwrap();
+            let mut processor = Processor::new();
+            for &byte in payload {
+                processor.advance(&mut term, byte, &mut std::io::sink());
+            }
+        });
+    });
+    group.finish();
+}
+
+fn bulk_ascii_throughput(c: &mut Criterion) {
+    bench_payload(c, "bulk_ascii_throughput", ascii_payload(1 << 20));
+}
+
+fn heavy_sgr_throughput(c: &mut Criterion) {
+    bench_payload(c, "heavy_sgr_throughput", heavy_sgr_payload(1 << 20));
+}
+
+criterion_group!(benches, bulk_ascii_throughput, heavy_sgr_throughput);
+criterion_main!(benches);
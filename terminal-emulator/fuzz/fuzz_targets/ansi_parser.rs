@@ -0,0 +1,30 @@
+//! `cargo fuzz run ansi_parser` - feeds arbitrary bytes straight through `Processor::advance`
+//! into a fixed-size `Term`, the same entry point `mux`'s `ProcessState::on_data` drives with
+//! live pty output. There's no escaping/validation step to get past: any byte sequence, valid
+//! escape code or not, has to be handled without panicking or growing the grid without bound.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use terminal_emulator::term::{SizeInfo, Term};
+use terminal_emulator::Processor;
+
+fuzz_target!(|data: &[u8]| {
+    let mut term = match Term::new(SizeInfo {
+        width: 80.0,
+        height: 24.0,
+        cell_width: 1.0,
+        cell_height: 1.0,
+        padding_x: 0.0,
+        padding_y: 0.0,
+        dpr: 1.0,
+    }) {
+        Ok(term) => term,
+        Err(_) => return,
+    };
+
+    let mut processor = Processor::new();
+    let mut sink = Vec::new();
+    for &byte in data {
+        processor.advance(&mut term, byte, &mut sink);
+    }
+});
@@ -0,0 +1,63 @@
+//! `cargo fuzz run grid_resize` - drives `Grid::resize`/`scroll_up`/`scroll_down` through random
+//! sequences of dimensions and regions, the operations `Term` calls on every terminal resize and
+//! every line feed/scroll respectively.
+//!
+//! Every dimension, scroll-region bound, and scroll distance pulled out of the fuzz input is
+//! reduced into a small, valid range before being used (see `MAX_DIM`/`MAX_SCROLLBACK` and the
+//! region/positions clamping below) rather than passed through raw. A caller legitimately asking
+//! `resize` to grow a grid to gigabytes, or `scroll_up`/`scroll_down` to move more lines than a
+//! region holds, would either really need that much memory or hit an invariant `Grid` itself
+//! already documents and asserts on elsewhere (e.g. `assert_eq_size!` in `grid::storage`) - that
+//! isn't a fuzz-sized bug in the library, it's a fuzz-harness design problem, so it's solved here
+//! at the harness level instead of by weakening what `Grid`'s API accepts.
+#![no_main]
+
+use std::ops::Range;
+
+use libfuzzer_sys::fuzz_target;
+use terminal_emulator::grid::Grid;
+use terminal_emulator::index::{Column, Line};
+use terminal_emulator::term::cell::Cell;
+
+const MAX_DIM: usize = 64;
+const MAX_SCROLLBACK: usize = 256;
+const MAX_OPS: usize = 32;
+
+fuzz_target!(|data: &[u8]| {
+    if data.len() < 3 {
+        return;
+    }
+
+    let template = Cell::default();
+    let lines = Line(1 + data[0] as usize % MAX_DIM);
+    let cols = Column(1 + data[1] as usize % MAX_DIM);
+    let scrollback = data[2] as usize % MAX_SCROLLBACK;
+
+    let mut grid = Grid::new(lines, cols, scrollback, template);
+
+    for op in data[3..].chunks(4).take(MAX_OPS) {
+        if op.len() < 4 {
+            break;
+        }
+
+        let num_lines = grid.num_lines().0;
+        let top = op[1] as usize % num_lines;
+        let span = 1 + op[2] as usize % (num_lines - top);
+        let region = Range {
+            start: Line(top),
+            end: Line(top + span),
+        };
+        // A scroll can never move more lines than the region it's confined to holds.
+        let positions = Line(1 + op[3] as usize % span);
+
+        match op[0] % 3 {
+            0 => {
+                let new_lines = Line(1 + op[1] as usize % MAX_DIM);
+                let new_cols = Column(1 + op[2] as usize % MAX_DIM);
+                grid.resize(new_lines, new_cols, &template);
+            }
+            1 => grid.scroll_up(&region, positions, &template),
+            _ => grid.scroll_down(&region, positions, &template),
+        }
+    }
+});
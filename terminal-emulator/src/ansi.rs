@@ -14,6 +14,7 @@
 //
 //! ANSI Terminal Stream Parsing
 use std::io;
+use std::mem;
 use std::ops::Range;
 use std::str;
 
@@ -119,6 +120,13 @@ pub struct Processor {
 /// Internal state for VTE processor
 pub struct ProcessorState {
     preceding_char: Option<char>,
+    /// Intermediate bytes captured by the most recent unterminated DCS `hook`, used by `unhook`
+    /// to tell XTGETTCAP (`+`) and DECRQSS (`$`) apart - this vte version doesn't pass the DCS
+    /// sequence's final byte through to either call, only what came before it.
+    dcs_intermediates: Vec<u8>,
+    /// Payload bytes collected via `put` between `hook` and `unhook`, for the two DCS requests
+    /// above.
+    dcs_payload: Vec<u8>,
 }
 
 /// Helper type that implements `vte::Perform`.
@@ -152,6 +160,8 @@ impl Default for Processor {
         Processor {
             state: ProcessorState {
                 preceding_char: None,
+                dcs_intermediates: Vec::new(),
+                dcs_payload: Vec::new(),
             },
             parser: vte::Parser::new(),
         }
@@ -185,6 +195,23 @@ pub trait TermInfo {
 pub enum MouseCursor {
     Arrow,
     Text,
+    /// Requested via OSC 22 with an X cursor name of `crosshair` or `cross`, e.g. by an
+    /// application offering a picker over the terminal grid.
+    Crosshair,
+}
+
+/// A FinalTerm / OSC 133 shell-integration mark, delineating prompts, commands, and their
+/// output so that a frontend can jump between prompts or grab a command's output.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum SemanticPromptMark {
+    /// `A`: a new prompt is about to be drawn
+    PromptStart,
+    /// `B`: the prompt finished drawing; the command line the user types follows
+    CommandStart,
+    /// `C`: the command was submitted; its output follows
+    OutputStart,
+    /// `D`: the command finished, with its exit code if the shell reported one
+    CommandFinished { exit_code: Option<u8> },
 }
 
 /// Type that handles actions from the parser
@@ -198,9 +225,13 @@ pub trait Handler {
     /// Set the window's mouse cursor
     fn set_mouse_cursor(&mut self, _: MouseCursor) {}
 
-    /// Set the cursor style
+    /// Set the cursor shape
     fn set_cursor_style(&mut self, _: Option<CursorStyle>) {}
 
+    /// Set whether the cursor blinks, from the same DECSCUSR sequence that sets its shape.
+    /// `None`, like `set_cursor_style`'s `None`, means "use the terminal's default".
+    fn set_cursor_blinking(&mut self, _: Option<bool>) {}
+
     /// A character to be displayed
     fn input(&mut self, _c: char) {}
 
@@ -230,6 +261,14 @@ pub trait Handler {
     // Report device status
     fn device_status<W: io::Write>(&mut self, _: &mut W, _: usize) {}
 
+    /// XTGETTCAP: answer a single hex-encoded termcap/terminfo capability name with its
+    /// hex-encoded value, DCS-style, or report it as unsupported.
+    fn report_termcap<W: io::Write>(&mut self, _: &mut W, _name: &str) {}
+
+    /// DECRQSS: report the current value of the setting named by `request` (e.g. `"m"` for SGR,
+    /// `"r"` for DECSTBM, `" q"` for DECSCUSR), or that it's unsupported.
+    fn report_setting<W: io::Write>(&mut self, _: &mut W, _request: &str) {}
+
     /// Move cursor forward `cols`
     fn move_forward(&mut self, _: Column) {}
 
@@ -298,6 +337,9 @@ pub trait Handler {
     /// Move forward `count` tabs
     fn move_forward_tabs(&mut self, _count: i64) {}
 
+    /// DECST8C: clear all tabstops and reset them to every 8 columns.
+    fn set_tabs_every_eight_columns(&mut self) {}
+
     /// Save current cursor position
     fn save_cursor_position(&mut self) {}
 
@@ -310,6 +352,12 @@ pub trait Handler {
     /// Clear screen
     fn clear_screen(&mut self, _mode: ClearMode) {}
 
+    /// DECSEL - like `clear_line`, but leaves DECSCA-protected cells untouched.
+    fn selective_clear_line(&mut self, _mode: LineClearMode) {}
+
+    /// DECSED - like `clear_screen`, but leaves DECSCA-protected cells untouched.
+    fn selective_clear_screen(&mut self, _mode: ClearMode) {}
+
     /// Clear tab stops
     fn clear_tabs(&mut self, _mode: TabulationClearMode) {}
 
@@ -332,6 +380,33 @@ pub trait Handler {
     /// Unset mode
     fn unset_mode(&mut self, _: Mode) {}
 
+    /// Kitty keyboard protocol: push `flags` onto the progressive-enhancement stack
+    /// (`CSI > flags u`).
+    fn push_keyboard_mode(&mut self, _flags: u8) {}
+
+    /// Kitty keyboard protocol: pop `count` entries off the progressive-enhancement stack
+    /// (`CSI < count u`).
+    fn pop_keyboard_mode(&mut self, _count: usize) {}
+
+    /// Kitty keyboard protocol: set/add/remove bits in the flags on top of the
+    /// progressive-enhancement stack (`CSI = flags ; mode u`).
+    fn set_keyboard_mode(&mut self, _flags: u8, _mode: KeyboardModeOp) {}
+
+    /// Kitty keyboard protocol: report the flags on top of the progressive-enhancement stack
+    /// (`CSI ? u`).
+    fn report_keyboard_mode<W: io::Write>(&mut self, _: &mut W) {}
+
+    /// xterm's `modifyOtherKeys` level (`CSI > 4 ; level m`): `0` is legacy encoding, `1` reports
+    /// modified keys that would otherwise be ambiguous or lost, `2` reports every modified key.
+    fn set_modify_other_keys(&mut self, _level: u8) {}
+
+    /// XTWINOPS: push the current window title onto a stack (`CSI 22 ; 0 t`).
+    fn push_title(&mut self) {}
+
+    /// XTWINOPS: pop the window title stack, restoring whatever title was on top, if any
+    /// (`CSI 23 ; 0 t`).
+    fn pop_title(&mut self) {}
+
     /// DECSTBM - Set the terminal scrolling region
     fn set_scrolling_region(&mut self, _: Range<Line>) {}
 
@@ -362,8 +437,48 @@ pub trait Handler {
     /// Set the clipboard
     fn set_clipboard(&mut self, _: &str) {}
 
+    /// OSC 7: report the shell's current working directory, as a `file://host/path` URI
+    fn set_cwd(&mut self, _: &str) {}
+
+    /// OSC 133: a shell integration mark delineating a prompt, command, or its output
+    fn semantic_prompt_mark(&mut self, _: SemanticPromptMark) {}
+
+    /// OSC 9 or OSC 777;notify: a desktop notification requested by the program running in the
+    /// terminal, with an optional title (OSC 777 carries one; OSC 9 never does).
+    fn notify(&mut self, _title: Option<&str>, _body: &str) {}
+
     /// Run the dectest routine
     fn dectest(&mut self) {}
+
+    /// DECSCA - mark characters written from now on as protected (or, with `false`, unprotected)
+    /// from DECSERA/SELECTIVE erase, until the next DECSCA changes it again.
+    fn set_character_protection(&mut self, _protected: bool) {}
+
+    /// DECCRA - copy the rectangle bounded by `top`/`left`/`bottom`/`right` so its top-left
+    /// corner lands at `dest_top`/`dest_left`. mux has no concept of DECCRA's source/destination
+    /// "page" parameters, so there's nothing for a caller to pass for those.
+    fn copy_rectangle(
+        &mut self,
+        _top: Line,
+        _left: Column,
+        _bottom: Line,
+        _right: Column,
+        _dest_top: Line,
+        _dest_left: Column,
+    ) {
+    }
+
+    /// DECFRA - fill the rectangle bounded by `top`/`left`/`bottom`/`right` with `c`, using the
+    /// cursor's current attributes.
+    fn fill_rectangle(&mut self, _c: char, _top: Line, _left: Column, _bottom: Line, _right: Column) {}
+
+    /// DECERA - erase the rectangle bounded by `top`/`left`/`bottom`/`right`, ignoring character
+    /// protection.
+    fn erase_rectangle(&mut self, _top: Line, _left: Column, _bottom: Line, _right: Column) {}
+
+    /// DECSERA - erase the rectangle bounded by `top`/`left`/`bottom`/`right`, leaving cells
+    /// marked protected by DECSCA untouched.
+    fn selective_erase_rectangle(&mut self, _top: Line, _left: Column, _bottom: Line, _right: Column) {}
 }
 
 /// Describes shape of cursor
@@ -435,10 +550,21 @@ pub enum Mode {
     ReportFocusInOut = 1004,
     /// ?1006
     SgrMouse = 1006,
+    /// ?66 - DECNKM, application keypad mode
+    ///
+    /// Equivalent to `ESC =` / `ESC >`, but toggleable (and queryable via DECRQM) as a private
+    /// mode instead of an escape sequence.
+    DecApplicationKeypad = 66,
     /// ?1049
     SwapScreenAndSetRestoreCursor = 1049,
     /// ?2004
     BracketedPaste = 2004,
+    /// ?2027
+    ///
+    /// Tells the terminal that the application understands that multi-codepoint grapheme
+    /// clusters (emoji ZWJ sequences, flags, ...) are rendered and cursor-advanced as a single
+    /// wide cell, per <https://github.com/contour-terminal/terminal-unicode-core>.
+    GraphemeClustering = 2027,
 }
 
 impl Mode {
@@ -454,6 +580,7 @@ impl Mode {
                 7 => Mode::LineWrap,
                 12 => Mode::BlinkingCursor,
                 25 => Mode::ShowCursor,
+                66 => Mode::DecApplicationKeypad,
                 1000 => Mode::ReportMouseClicks,
                 1002 => Mode::ReportCellMouseMotion,
                 1003 => Mode::ReportAllMouseMotion,
@@ -461,6 +588,7 @@ impl Mode {
                 1006 => Mode::SgrMouse,
                 1049 => Mode::SwapScreenAndSetRestoreCursor,
                 2004 => Mode::BracketedPaste,
+                2027 => Mode::GraphemeClustering,
                 _ => {
                     trace!("[unimplemented] primitive mode: {}", num);
                     return None;
@@ -476,6 +604,18 @@ impl Mode {
     }
 }
 
+/// How `CSI = flags ; mode u` should combine `flags` into the current kitty keyboard protocol
+/// enhancement flags, per <https://sw.kovidgoyal.net/kitty/keyboard-protocol/#progressive-enhancement>.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum KeyboardModeOp {
+    /// Replace the current flags outright.
+    Set,
+    /// Set the given bits, leaving the rest alone.
+    Add,
+    /// Clear the given bits, leaving the rest alone.
+    Remove,
+}
+
 /// Mode for clearing line
 ///
 /// Relative to cursor
@@ -742,21 +882,39 @@ where
     }
 
     #[inline]
-    fn hook(&mut self, params: &[i64], intermediates: &[u8], ignore: bool) {
-        debug!(
-            "[unhandled hook] params={:?}, ints: {:?}, ignore: {:?}",
-            params, intermediates, ignore
-        );
+    fn hook(&mut self, _params: &[i64], intermediates: &[u8], _ignore: bool) {
+        self.state.dcs_intermediates = intermediates.to_vec();
+        self.state.dcs_payload.clear();
     }
 
     #[inline]
     fn put(&mut self, byte: u8) {
-        debug!("[unhandled put] byte={:?}", byte);
+        self.state.dcs_payload.push(byte);
     }
 
     #[inline]
     fn unhook(&mut self) {
-        debug!("[unhandled unhook]");
+        let intermediates = mem::take(&mut self.state.dcs_intermediates);
+        let payload = mem::take(&mut self.state.dcs_payload);
+        let payload = match str::from_utf8(&payload) {
+            Ok(payload) => payload,
+            Err(_) => return,
+        };
+
+        match intermediates.as_slice() {
+            // XTGETTCAP - one or more hex-encoded capability names, separated by `;`.
+            [b'+'] => {
+                for name in payload.split(';') {
+                    self.handler.report_termcap(self.writer, name);
+                }
+            }
+            // DECRQSS - the setting being asked about, e.g. "m", "r", or " q".
+            [b'$'] => self.handler.report_setting(self.writer, payload),
+            _ => debug!(
+                "[unhandled DCS] intermediates={:?}, payload={:?}",
+                intermediates, payload
+            ),
+        }
     }
 
     // TODO replace OSC parsing with parser combinators
@@ -794,6 +952,39 @@ where
             // This is ignored, since alacritty has no concept of tabs
             b"1" => return,
 
+            // Report current working directory
+            b"7" => {
+                if params.len() >= 2 {
+                    if let Ok(uri) = str::from_utf8(params[1]) {
+                        self.handler.set_cwd(uri);
+                        return;
+                    }
+                }
+                unhandled(params);
+            }
+
+            // OSC 9: desktop notification (iTerm2/Growl style), body only
+            b"9" => {
+                if params.len() >= 2 {
+                    if let Ok(body) = str::from_utf8(params[1]) {
+                        self.handler.notify(None, body);
+                        return;
+                    }
+                }
+                unhandled(params);
+            }
+
+            // OSC 777;notify;title;body: desktop notification (rxvt-unicode style)
+            b"777" => {
+                if params.len() >= 4 && params[1] == b"notify" {
+                    if let (Ok(title), Ok(body)) = (str::from_utf8(params[2]), str::from_utf8(params[3])) {
+                        self.handler.notify(Some(title), body);
+                        return;
+                    }
+                }
+                unhandled(params);
+            }
+
             // Set color index
             b"4" => {
                 if params.len() > 1 && params.len() % 2 != 0 {
@@ -844,6 +1035,25 @@ where
                 unhandled(params);
             }
 
+            // Set mouse pointer shape, by X cursor font name
+            b"22" => {
+                if params.len() >= 2 {
+                    if let Ok(name) = str::from_utf8(params[1]) {
+                        let cursor = match name {
+                            "default" | "left_ptr" | "pointer" => Some(MouseCursor::Arrow),
+                            "text" | "xterm" | "ibeam" => Some(MouseCursor::Text),
+                            "crosshair" | "cross" => Some(MouseCursor::Crosshair),
+                            _ => None,
+                        };
+                        if let Some(cursor) = cursor {
+                            self.handler.set_mouse_cursor(cursor);
+                            return;
+                        }
+                    }
+                }
+                unhandled(params);
+            }
+
             // Set cursor style
             b"50" => {
                 if params.len() >= 2
@@ -908,6 +1118,26 @@ where
             // Reset text cursor color
             b"112" => self.handler.reset_color(NamedColor::Cursor as usize),
 
+            // Shell integration: semantic prompt marks
+            b"133" => {
+                if params.len() >= 2 && !params[1].is_empty() {
+                    let mark = match params[1][0] {
+                        b'A' => Some(SemanticPromptMark::PromptStart),
+                        b'B' => Some(SemanticPromptMark::CommandStart),
+                        b'C' => Some(SemanticPromptMark::OutputStart),
+                        b'D' => Some(SemanticPromptMark::CommandFinished {
+                            exit_code: params.get(2).and_then(|code| parse_number(code)),
+                        }),
+                        _ => None,
+                    };
+                    if let Some(mark) = mark {
+                        self.handler.semantic_prompt_mark(mark);
+                        return;
+                    }
+                }
+                unhandled(params);
+            }
+
             _ => unhandled(params),
         }
     }
@@ -972,6 +1202,8 @@ where
                 handler.goto(Line(y - 1), Column(x - 1));
             }
             'I' => handler.move_forward_tabs(arg_or_default!(idx: 0, default: 1)),
+            // DECSED, when prefixed with `?`, is the same modes as ED but leaves DECSCA-protected
+            // cells untouched.
             'J' => {
                 let mode = match arg_or_default!(idx: 0, default: 0) {
                     0 => ClearMode::Below,
@@ -981,8 +1213,14 @@ where
                     _ => unhandled!(),
                 };
 
-                handler.clear_screen(mode);
+                if private {
+                    handler.selective_clear_screen(mode);
+                } else {
+                    handler.clear_screen(mode);
+                }
             }
+            // DECSEL, when prefixed with `?`, is the same modes as EL but leaves DECSCA-protected
+            // cells untouched.
             'K' => {
                 let mode = match arg_or_default!(idx: 0, default: 0) {
                     0 => LineClearMode::Right,
@@ -991,7 +1229,11 @@ where
                     _ => unhandled!(),
                 };
 
-                handler.clear_line(mode);
+                if private {
+                    handler.selective_clear_line(mode);
+                } else {
+                    handler.clear_line(mode);
+                }
             }
             'S' => handler.scroll_up(Line(arg_or_default!(idx: 0, default: 1) as usize)),
             'T' => handler.scroll_down(Line(arg_or_default!(idx: 0, default: 1) as usize)),
@@ -1009,6 +1251,9 @@ where
             'X' => handler.erase_chars(Column(arg_or_default!(idx: 0, default: 1) as usize)),
             'P' => handler.delete_chars(Column(arg_or_default!(idx: 0, default: 1) as usize)),
             'Z' => handler.move_backward_tabs(arg_or_default!(idx: 0, default: 1)),
+            'W' if private && arg_or_default!(idx: 0, default: 0) == 5 => {
+                handler.set_tabs_every_eight_columns();
+            }
             'd' => handler.goto_line(Line(arg_or_default!(idx: 0, default: 1) as usize - 1)),
             'h' => {
                 for arg in args {
@@ -1019,6 +1264,11 @@ where
                     }
                 }
             }
+            'm' if intermediates.get(0) == Some(&b'>') => {
+                // xterm's modifyOtherKeys: `CSI > 4 ; level m`. The `Ps` selector (always `4`
+                // for modifyOtherKeys) isn't otherwise interesting, so only the level is used.
+                handler.set_modify_other_keys(arg_or_default!(idx: 1, default: 0) as u8);
+            }
             'm' => {
                 // Sometimes a C-style for loop is just what you need
                 let mut i = 0; // C-for initializer
@@ -1128,17 +1378,117 @@ where
                 handler.set_scrolling_region(top..bottom);
             }
             's' => handler.save_cursor_position(),
-            'u' => handler.restore_cursor_position(),
-            'q' => {
-                let style = match arg_or_default!(idx: 0, default: 0) {
-                    0 => None,
-                    1 | 2 => Some(CursorStyle::Block),
-                    3 | 4 => Some(CursorStyle::Underline),
-                    5 | 6 => Some(CursorStyle::Beam),
+            // XTWINOPS. Only the title stack operations are implemented; everything else
+            // (resize/reposition/report queries meant for a real window manager) is a no-op, the
+            // same way they'd be ignored by a terminal with no window of its own to move.
+            't' => match arg_or_default!(idx: 0, default: 0) {
+                22 => handler.push_title(),
+                23 => handler.pop_title(),
+                _ => unhandled!(),
+            },
+            'u' => match intermediates.get(0) {
+                None => handler.restore_cursor_position(),
+                Some(b'>') => {
+                    handler.push_keyboard_mode(arg_or_default!(idx: 0, default: 0) as u8)
+                }
+                Some(b'<') => {
+                    handler.pop_keyboard_mode(arg_or_default!(idx: 0, default: 1) as usize)
+                }
+                Some(b'=') => {
+                    let flags = arg_or_default!(idx: 0, default: 0) as u8;
+                    let mode = match arg_or_default!(idx: 1, default: 1) {
+                        1 => KeyboardModeOp::Set,
+                        2 => KeyboardModeOp::Add,
+                        3 => KeyboardModeOp::Remove,
+                        _ => unhandled!(),
+                    };
+                    handler.set_keyboard_mode(flags, mode);
+                }
+                Some(b'?') => handler.report_keyboard_mode(writer),
+                Some(_) => unhandled!(),
+            },
+            'p' => {
+                // DECSTR - soft terminal reset
+                if intermediates == [b'!'] {
+                    handler.reset_state();
+                } else {
+                    unhandled!();
+                }
+            }
+            // DECSCUSR - cursor style. Only the `CSI Ps SP q` form (with the space
+            // intermediate) sets it; a bare `CSI Ps q` is a different, unimplemented control.
+            'q' if intermediates == [b' '] => {
+                let (style, blinking) = match arg_or_default!(idx: 0, default: 0) {
+                    0 => (None, None),
+                    1 => (Some(CursorStyle::Block), Some(true)),
+                    2 => (Some(CursorStyle::Block), Some(false)),
+                    3 => (Some(CursorStyle::Underline), Some(true)),
+                    4 => (Some(CursorStyle::Underline), Some(false)),
+                    5 => (Some(CursorStyle::Beam), Some(true)),
+                    6 => (Some(CursorStyle::Beam), Some(false)),
                     _ => unhandled!(),
                 };
 
                 handler.set_cursor_style(style);
+                handler.set_cursor_blinking(blinking);
+            }
+            // DECSCA - mark subsequently-written characters protected (or, with a reset code,
+            // unprotected) from DECSERA. Shares the `q` final byte with DECSCUSR above; the `"`
+            // intermediate (instead of a space) is what picks this one out.
+            'q' if intermediates == [b'"'] => {
+                let protected = arg_or_default!(idx: 0, default: 0) == 1;
+                handler.set_character_protection(protected);
+            }
+            // DECCRA - copy a rectangular area. mux has no concept of DECCRA's source/destination
+            // "page" parameters (args 4 and 7), so they're parsed and ignored.
+            'v' if intermediates == [b'$'] => {
+                let top = Line(arg_or_default!(idx: 0, default: 1) as usize - 1);
+                let left = Column(arg_or_default!(idx: 1, default: 1) as usize - 1);
+                let bottom =
+                    Line(arg_or_default!(idx: 2, default: handler.lines().0 as _) as usize - 1);
+                let right =
+                    Column(arg_or_default!(idx: 3, default: handler.cols().0 as _) as usize - 1);
+                let dest_top = Line(arg_or_default!(idx: 5, default: 1) as usize - 1);
+                let dest_left = Column(arg_or_default!(idx: 6, default: 1) as usize - 1);
+                handler.copy_rectangle(top, left, bottom, right, dest_top, dest_left);
+            }
+            // DECFRA - fill a rectangular area with one character.
+            'x' if intermediates == [b'$'] => {
+                let c = match std::char::from_u32(arg_or_default!(idx: 0, default: 0) as u32) {
+                    Some(c) => c,
+                    None => unhandled!(),
+                };
+                let top = Line(arg_or_default!(idx: 1, default: 1) as usize - 1);
+                let left = Column(arg_or_default!(idx: 2, default: 1) as usize - 1);
+                let bottom =
+                    Line(arg_or_default!(idx: 3, default: handler.lines().0 as _) as usize - 1);
+                let right =
+                    Column(arg_or_default!(idx: 4, default: handler.cols().0 as _) as usize - 1);
+                handler.fill_rectangle(c, top, left, bottom, right);
+            }
+            // DECSACE - select attribute-change extent. Only meaningful to DECCARA/DECRARA
+            // (attribute-only rectangle changes), which mux doesn't implement - see the README's
+            // "Out of scope" section.
+            'x' if intermediates == [b'*'] => unhandled!(),
+            // DECERA - erase a rectangular area, ignoring character protection.
+            'z' if intermediates == [b'$'] => {
+                let top = Line(arg_or_default!(idx: 0, default: 1) as usize - 1);
+                let left = Column(arg_or_default!(idx: 1, default: 1) as usize - 1);
+                let bottom =
+                    Line(arg_or_default!(idx: 2, default: handler.lines().0 as _) as usize - 1);
+                let right =
+                    Column(arg_or_default!(idx: 3, default: handler.cols().0 as _) as usize - 1);
+                handler.erase_rectangle(top, left, bottom, right);
+            }
+            // DECSERA - erase a rectangular area, leaving DECSCA-protected cells untouched.
+            '{' if intermediates == [b'$'] => {
+                let top = Line(arg_or_default!(idx: 0, default: 1) as usize - 1);
+                let left = Column(arg_or_default!(idx: 1, default: 1) as usize - 1);
+                let bottom =
+                    Line(arg_or_default!(idx: 2, default: handler.lines().0 as _) as usize - 1);
+                let right =
+                    Column(arg_or_default!(idx: 3, default: handler.cols().0 as _) as usize - 1);
+                handler.selective_erase_rectangle(top, left, bottom, right);
             }
             _ => unhandled!(),
         }
@@ -1402,8 +1752,9 @@ pub mod C1 {
 #[cfg(test)]
 mod tests {
     use super::{
-        parse_number, parse_rgb_color, Attr, CharsetIndex, Color, Handler, Processor, Rgb,
-        StandardCharset, TermInfo,
+        parse_number, parse_rgb_color, Attr, CharsetIndex, Color, CursorStyle, Handler,
+        KeyboardModeOp, Mode, MouseCursor, Processor, Rgb, SemanticPromptMark, StandardCharset,
+        TermInfo,
     };
     use crate::index::{Column, Line};
     use std::io;
@@ -1442,6 +1793,14 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_grapheme_clustering_mode() {
+        assert_eq!(
+            Mode::from_primitive(true, 2027),
+            Some(Mode::GraphemeClustering)
+        );
+    }
+
     #[test]
     fn parse_control_attribute() {
         static BYTES: &'static [u8] = &[0x1b, 0x5b, 0x31, 0x6d];
@@ -1617,4 +1976,514 @@ mod tests {
     fn parse_number_too_large() {
         assert_eq!(parse_number(b"321"), None);
     }
+
+    #[derive(Default)]
+    struct ResetHandler {
+        reset: bool,
+    }
+
+    impl Handler for ResetHandler {
+        fn reset_state(&mut self) {
+            self.reset = true;
+        }
+    }
+
+    impl TermInfo for ResetHandler {
+        fn lines(&self) -> Line {
+            Line(24)
+        }
+
+        fn cols(&self) -> Column {
+            Column(80)
+        }
+    }
+
+    #[test]
+    fn decstr_soft_reset() {
+        static BYTES: &'static [u8] = &[0x1b, 0x5b, 0x21, 0x70]; // CSI ! p
+
+        let mut parser = Processor::new();
+        let mut handler = ResetHandler::default();
+
+        for byte in &BYTES[..] {
+            parser.advance(&mut handler, *byte, &mut Void);
+        }
+
+        assert!(handler.reset);
+    }
+
+    #[derive(Default)]
+    struct CwdHandler {
+        cwd: Option<String>,
+    }
+
+    impl Handler for CwdHandler {
+        fn set_cwd(&mut self, uri: &str) {
+            self.cwd = Some(uri.to_owned());
+        }
+    }
+
+    impl TermInfo for CwdHandler {
+        fn lines(&self) -> Line {
+            Line(24)
+        }
+
+        fn cols(&self) -> Column {
+            Column(80)
+        }
+    }
+
+    #[test]
+    fn osc_7_reports_cwd() {
+        static BYTES: &'static [u8] =
+            b"\x1b]7;file://myhost/home/user\x07";
+
+        let mut parser = Processor::new();
+        let mut handler = CwdHandler::default();
+
+        for byte in &BYTES[..] {
+            parser.advance(&mut handler, *byte, &mut Void);
+        }
+
+        assert_eq!(handler.cwd.as_deref(), Some("file://myhost/home/user"));
+    }
+
+    #[derive(Default)]
+    struct PromptMarkHandler {
+        marks: Vec<SemanticPromptMark>,
+    }
+
+    impl Handler for PromptMarkHandler {
+        fn semantic_prompt_mark(&mut self, mark: SemanticPromptMark) {
+            self.marks.push(mark);
+        }
+    }
+
+    impl TermInfo for PromptMarkHandler {
+        fn lines(&self) -> Line {
+            Line(24)
+        }
+
+        fn cols(&self) -> Column {
+            Column(80)
+        }
+    }
+
+    #[test]
+    fn osc_133_reports_prompt_marks() {
+        static BYTES: &'static [u8] = b"\x1b]133;A\x07\x1b]133;B\x07\x1b]133;C\x07\x1b]133;D;1\x07";
+
+        let mut parser = Processor::new();
+        let mut handler = PromptMarkHandler::default();
+
+        for byte in &BYTES[..] {
+            parser.advance(&mut handler, *byte, &mut Void);
+        }
+
+        assert_eq!(
+            handler.marks,
+            vec![
+                SemanticPromptMark::PromptStart,
+                SemanticPromptMark::CommandStart,
+                SemanticPromptMark::OutputStart,
+                SemanticPromptMark::CommandFinished { exit_code: Some(1) },
+            ]
+        );
+    }
+
+    #[derive(Default)]
+    struct NotifyHandler {
+        notifications: Vec<(Option<String>, String)>,
+    }
+
+    impl Handler for NotifyHandler {
+        fn notify(&mut self, title: Option<&str>, body: &str) {
+            self.notifications
+                .push((title.map(str::to_owned), body.to_owned()));
+        }
+    }
+
+    impl TermInfo for NotifyHandler {
+        fn lines(&self) -> Line {
+            Line(24)
+        }
+
+        fn cols(&self) -> Column {
+            Column(80)
+        }
+    }
+
+    #[test]
+    fn osc_9_reports_a_titleless_notification() {
+        static BYTES: &'static [u8] = b"\x1b]9;build finished\x07";
+
+        let mut parser = Processor::new();
+        let mut handler = NotifyHandler::default();
+
+        for byte in &BYTES[..] {
+            parser.advance(&mut handler, *byte, &mut Void);
+        }
+
+        assert_eq!(
+            handler.notifications,
+            vec![(None, "build finished".to_owned())]
+        );
+    }
+
+    #[test]
+    fn osc_777_notify_reports_a_titled_notification() {
+        static BYTES: &'static [u8] = b"\x1b]777;notify;mux;build finished\x07";
+
+        let mut parser = Processor::new();
+        let mut handler = NotifyHandler::default();
+
+        for byte in &BYTES[..] {
+            parser.advance(&mut handler, *byte, &mut Void);
+        }
+
+        assert_eq!(
+            handler.notifications,
+            vec![(Some("mux".to_owned()), "build finished".to_owned())]
+        );
+    }
+
+    #[derive(Default)]
+    struct KeyboardModeHandler {
+        stack: Vec<u8>,
+        modify_other_keys: u8,
+    }
+
+    impl Handler for KeyboardModeHandler {
+        fn push_keyboard_mode(&mut self, flags: u8) {
+            self.stack.push(flags);
+        }
+
+        fn set_modify_other_keys(&mut self, level: u8) {
+            self.modify_other_keys = level;
+        }
+
+        fn pop_keyboard_mode(&mut self, count: usize) {
+            let new_len = self.stack.len().saturating_sub(count);
+            self.stack.truncate(new_len);
+        }
+
+        fn set_keyboard_mode(&mut self, flags: u8, mode: KeyboardModeOp) {
+            let current = self.stack.last().copied().unwrap_or(0);
+            let new_flags = match mode {
+                KeyboardModeOp::Set => flags,
+                KeyboardModeOp::Add => current | flags,
+                KeyboardModeOp::Remove => current & !flags,
+            };
+            match self.stack.last_mut() {
+                Some(top) => *top = new_flags,
+                None => self.stack.push(new_flags),
+            }
+        }
+
+        fn report_keyboard_mode<W: io::Write>(&mut self, writer: &mut W) {
+            let flags = self.stack.last().copied().unwrap_or(0);
+            let _ = write!(writer, "\x1b[?{}u", flags);
+        }
+    }
+
+    impl TermInfo for KeyboardModeHandler {
+        fn lines(&self) -> Line {
+            Line(24)
+        }
+
+        fn cols(&self) -> Column {
+            Column(80)
+        }
+    }
+
+    #[test]
+    fn kitty_keyboard_protocol_push_set_pop() {
+        static BYTES: &'static [u8] = b"\x1b[>1u\x1b[=3;2u\x1b[<1u";
+
+        let mut parser = Processor::new();
+        let mut handler = KeyboardModeHandler::default();
+
+        for byte in &BYTES[..] {
+            parser.advance(&mut handler, *byte, &mut Void);
+        }
+
+        // Pushed 1, added bit 2 (now 3), then popped back to empty.
+        assert_eq!(handler.stack, Vec::<u8>::new());
+    }
+
+    #[test]
+    fn kitty_keyboard_protocol_reports_current_flags() {
+        static BYTES: &'static [u8] = b"\x1b[>5u\x1b[?u";
+
+        let mut parser = Processor::new();
+        let mut handler = KeyboardModeHandler::default();
+        let mut writer = Vec::new();
+
+        for byte in &BYTES[..] {
+            parser.advance(&mut handler, *byte, &mut writer);
+        }
+
+        assert_eq!(writer, b"\x1b[?5u");
+    }
+
+    #[test]
+    fn modify_other_keys_sets_level() {
+        static BYTES: &'static [u8] = b"\x1b[>4;2m";
+
+        let mut parser = Processor::new();
+        let mut handler = KeyboardModeHandler::default();
+
+        for byte in &BYTES[..] {
+            parser.advance(&mut handler, *byte, &mut Void);
+        }
+
+        assert_eq!(handler.modify_other_keys, 2);
+    }
+
+    #[derive(Default)]
+    struct CursorStyleHandler {
+        style: Option<CursorStyle>,
+        blinking: Option<bool>,
+    }
+
+    impl Handler for CursorStyleHandler {
+        fn set_cursor_style(&mut self, style: Option<CursorStyle>) {
+            self.style = style;
+        }
+
+        fn set_cursor_blinking(&mut self, blinking: Option<bool>) {
+            self.blinking = blinking;
+        }
+    }
+
+    impl TermInfo for CursorStyleHandler {
+        fn lines(&self) -> Line {
+            Line(24)
+        }
+
+        fn cols(&self) -> Column {
+            Column(80)
+        }
+    }
+
+    #[test]
+    fn decscusr_sets_style_and_blink() {
+        static BYTES: &'static [u8] = b"\x1b[3 q";
+
+        let mut parser = Processor::new();
+        let mut handler = CursorStyleHandler::default();
+
+        for byte in &BYTES[..] {
+            parser.advance(&mut handler, *byte, &mut Void);
+        }
+
+        assert_eq!(handler.style, Some(CursorStyle::Underline));
+        assert_eq!(handler.blinking, Some(true));
+    }
+
+    #[test]
+    fn bare_csi_q_does_not_set_cursor_style() {
+        static BYTES: &'static [u8] = b"\x1b[3q";
+
+        let mut parser = Processor::new();
+        let mut handler = CursorStyleHandler::default();
+
+        for byte in &BYTES[..] {
+            parser.advance(&mut handler, *byte, &mut Void);
+        }
+
+        assert_eq!(handler.style, None);
+        assert_eq!(handler.blinking, None);
+    }
+
+    #[derive(Default)]
+    struct TitleStackHandler {
+        pushed: usize,
+        popped: usize,
+    }
+
+    impl Handler for TitleStackHandler {
+        fn push_title(&mut self) {
+            self.pushed += 1;
+        }
+
+        fn pop_title(&mut self) {
+            self.popped += 1;
+        }
+    }
+
+    impl TermInfo for TitleStackHandler {
+        fn lines(&self) -> Line {
+            Line(24)
+        }
+
+        fn cols(&self) -> Column {
+            Column(80)
+        }
+    }
+
+    #[test]
+    fn xtwinops_22_and_23_push_and_pop_the_title_stack() {
+        static BYTES: &'static [u8] = b"\x1b[22;0t\x1b[23;0t";
+
+        let mut parser = Processor::new();
+        let mut handler = TitleStackHandler::default();
+
+        for byte in &BYTES[..] {
+            parser.advance(&mut handler, *byte, &mut Void);
+        }
+
+        assert_eq!(handler.pushed, 1);
+        assert_eq!(handler.popped, 1);
+    }
+
+    #[test]
+    fn other_xtwinops_are_ignored() {
+        static BYTES: &'static [u8] = b"\x1b[8;24;80t";
+
+        let mut parser = Processor::new();
+        let mut handler = TitleStackHandler::default();
+
+        for byte in &BYTES[..] {
+            parser.advance(&mut handler, *byte, &mut Void);
+        }
+
+        assert_eq!(handler.pushed, 0);
+        assert_eq!(handler.popped, 0);
+    }
+
+    #[derive(Default)]
+    struct DcsHandler {
+        termcap_queries: Vec<String>,
+        setting_queries: Vec<String>,
+    }
+
+    impl Handler for DcsHandler {
+        fn report_termcap<W: io::Write>(&mut self, _: &mut W, name: &str) {
+            self.termcap_queries.push(name.to_owned());
+        }
+
+        fn report_setting<W: io::Write>(&mut self, _: &mut W, request: &str) {
+            self.setting_queries.push(request.to_owned());
+        }
+    }
+
+    impl TermInfo for DcsHandler {
+        fn lines(&self) -> Line {
+            Line(24)
+        }
+
+        fn cols(&self) -> Column {
+            Column(80)
+        }
+    }
+
+    #[test]
+    fn xtgettcap_dispatches_each_semicolon_separated_name() {
+        // DCS + q 436f ; 524742 ST - XTGETTCAP for "Co" and "RGB".
+        static BYTES: &'static [u8] = b"\x1bP+q436f;524742\x1b\\";
+
+        let mut parser = Processor::new();
+        let mut handler = DcsHandler::default();
+
+        for byte in &BYTES[..] {
+            parser.advance(&mut handler, *byte, &mut Void);
+        }
+
+        assert_eq!(handler.termcap_queries, vec!["436f", "524742"]);
+        assert_eq!(handler.setting_queries, Vec::<String>::new());
+    }
+
+    #[test]
+    fn decrqss_dispatches_the_whole_payload_as_one_request() {
+        // DCS $ q m ST - DECRQSS asking for the current SGR state.
+        static BYTES: &'static [u8] = b"\x1bP$qm\x1b\\";
+
+        let mut parser = Processor::new();
+        let mut handler = DcsHandler::default();
+
+        for byte in &BYTES[..] {
+            parser.advance(&mut handler, *byte, &mut Void);
+        }
+
+        assert_eq!(handler.setting_queries, vec!["m"]);
+        assert_eq!(handler.termcap_queries, Vec::<String>::new());
+    }
+
+    #[derive(Default)]
+    struct MouseCursorHandler {
+        cursor: Option<MouseCursor>,
+    }
+
+    impl Handler for MouseCursorHandler {
+        fn set_mouse_cursor(&mut self, cursor: MouseCursor) {
+            self.cursor = Some(cursor);
+        }
+    }
+
+    impl TermInfo for MouseCursorHandler {
+        fn lines(&self) -> Line {
+            Line(24)
+        }
+
+        fn cols(&self) -> Column {
+            Column(80)
+        }
+    }
+
+    #[test]
+    fn osc_22_sets_pointer_shape() {
+        static BYTES: &'static [u8] = b"\x1b]22;pointer\x07";
+
+        let mut parser = Processor::new();
+        let mut handler = MouseCursorHandler::default();
+
+        for byte in &BYTES[..] {
+            parser.advance(&mut handler, *byte, &mut Void);
+        }
+
+        assert_eq!(handler.cursor, Some(MouseCursor::Arrow));
+    }
+
+    #[test]
+    fn osc_22_sets_text_shape() {
+        static BYTES: &'static [u8] = b"\x1b]22;text\x07";
+
+        let mut parser = Processor::new();
+        let mut handler = MouseCursorHandler::default();
+
+        for byte in &BYTES[..] {
+            parser.advance(&mut handler, *byte, &mut Void);
+        }
+
+        assert_eq!(handler.cursor, Some(MouseCursor::Text));
+    }
+
+    #[test]
+    fn osc_22_sets_crosshair_shape() {
+        static BYTES: &'static [u8] = b"\x1b]22;crosshair\x07";
+
+        let mut parser = Processor::new();
+        let mut handler = MouseCursorHandler::default();
+
+        for byte in &BYTES[..] {
+            parser.advance(&mut handler, *byte, &mut Void);
+        }
+
+        assert_eq!(handler.cursor, Some(MouseCursor::Crosshair));
+    }
+
+    #[test]
+    fn osc_22_ignores_an_unknown_shape_name() {
+        static BYTES: &'static [u8] = b"\x1b]22;watch\x07";
+
+        let mut parser = Processor::new();
+        let mut handler = MouseCursorHandler::default();
+
+        for byte in &BYTES[..] {
+            parser.advance(&mut handler, *byte, &mut Void);
+        }
+
+        assert_eq!(handler.cursor, None);
+    }
 }
@@ -144,10 +144,14 @@ impl<T: Copy + Clone> Grid<T> {
         self.line_to_offset(line) + self.display_offset
     }
 
-    /// Update the size of the scrollback history
+    /// Update the size of the scrollback history. `max_scroll_limit` is updated too, not just
+    /// the currently-used `scroll_limit` - otherwise a later `increase_scroll_limit` (triggered
+    /// by ordinary scrolling) would clamp back up to whatever capacity the grid was originally
+    /// created with instead of respecting the new one.
     pub fn update_history(&mut self, history_size: usize, template: &T) {
         self.raw
             .update_history(history_size, Row::new(self.cols, &template));
+        self.max_scroll_limit = history_size;
         self.scroll_limit = min(self.scroll_limit, history_size);
     }
 
@@ -170,6 +174,14 @@ impl<T: Copy + Clone> Grid<T> {
         }
     }
 
+    /// Move the viewport so buffer-absolute line `line` (as returned by `Index<usize>`, where `0`
+    /// is the most recent row and larger indices are further back in history) becomes the
+    /// bottom-most visible row, clamped to `scroll_limit` like every other `display_offset` write
+    /// above.
+    pub fn scroll_to_line(&mut self, line: usize) {
+        self.display_offset = min(line, self.scroll_limit);
+    }
+
     pub fn resize(&mut self, lines: index::Line, cols: index::Column, template: &T) {
         // Check that there's actually work to do and return early if not
         if lines == self.lines && cols == self.cols {
@@ -22,6 +22,14 @@ use crate::index::Line;
 /// Maximum number of invisible lines before buffer is resized
 const TRUNCATE_STEP: usize = 100;
 
+/// This is still one `Row<T>` (and therefore one heap allocation) per line, not the chunked,
+/// copy-on-write layout that would be needed to make 100k+ lines of scrollback cheap: `swap`
+/// below hard-codes `Row<T>`'s current in-memory layout (`assert_eq_size!(Row<T>, [usize; 4])`)
+/// to swap two rows as four raw qwords instead of going through `slice::swap`, so replacing what
+/// a `Row` owns (e.g. a shared, copy-on-write buffer instead of its own `Vec`) means reworking
+/// that unsafe fast path too, and re-verifying the zero/rotation invariants the tests below pin
+/// down still hold against the new representation. See `benches::bytes_per_blank_line` for the
+/// current per-line cost this would need to improve on.
 #[derive(Clone, Debug)]
 pub struct Storage<T> {
     inner: Vec<Row<T>>,
@@ -753,3 +761,31 @@ fn initialize() {
     assert_eq!(storage.zero, shrinking_expected.zero);
     assert_eq!(storage.len, shrinking_expected.len);
 }
+
+#[cfg(all(test, feature = "bench"))]
+mod benches {
+    extern crate test;
+
+    use std::mem;
+
+    use super::{Row, Storage};
+    use crate::index::{Column, Line};
+    use crate::term::cell::Cell;
+
+    /// Bytes per blank line in a `Storage<Cell>` sized for 100k lines of 80-column scrollback:
+    /// `Row<Cell>`'s own `Vec<Cell>` allocation plus the `Row` struct itself. This is the
+    /// baseline a chunked/copy-on-write storage redesign (see the doc comment on `Storage`
+    /// above) would need to improve on.
+    #[bench]
+    fn bytes_per_blank_line(b: &mut test::Bencher) {
+        let lines = Line(100_000);
+        let cols = Column(80);
+
+        b.iter(|| {
+            let storage: Storage<Cell> =
+                Storage::with_capacity(lines, Row::new(cols, &Cell::default()));
+            let per_line = mem::size_of::<Row<Cell>>() + cols.0 * mem::size_of::<Cell>();
+            test::black_box((storage.len(), per_line))
+        });
+    }
+}
@@ -27,7 +27,7 @@ pub enum Side {
 }
 
 /// Index in the grid using row, column notation
-#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, PartialOrd, Hash)]
 pub struct Point<L = Line> {
     pub line: L,
     pub col: Column,
@@ -72,7 +72,7 @@ impl From<Point> for Point<usize> {
 /// A line
 ///
 /// Newtype to avoid passing values incorrectly
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Default, Ord, PartialOrd)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default, Ord, PartialOrd, Hash)]
 pub struct Line(pub usize);
 
 impl fmt::Display for Line {
@@ -84,7 +84,7 @@ impl fmt::Display for Line {
 /// A column
 ///
 /// Newtype to avoid passing values incorrectly
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Default, Ord, PartialOrd)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default, Ord, PartialOrd, Hash)]
 pub struct Column(pub usize);
 
 impl fmt::Display for Column {
@@ -8,6 +8,10 @@ pub mod ansi;
 pub mod mode;
 pub mod selection;
 pub mod term;
+pub mod url;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 pub use ansi::Handler;
 pub use ansi::Processor;
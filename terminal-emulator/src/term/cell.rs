@@ -11,6 +11,8 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+use std::num::{NonZeroU16, NonZeroU32};
+
 use bitflags::bitflags;
 
 use crate::ansi::{Color, NamedColor};
@@ -22,6 +24,71 @@ use arrayvec::ArrayString;
 pub const MAX_ZEROWIDTH_CHARS: usize = 5;
 pub const MAX_CELL_LEN: usize = 4 * (1 + MAX_ZEROWIDTH_CHARS);
 
+/// Process-wide, append-only interner for the zero-width extras (combining marks, ZWJ emoji
+/// sequences, skin-tone modifiers, ...) a cell needs beyond its first character.
+///
+/// Almost every cell only ever holds one character, so keeping the extras out of `Cell` itself
+/// (a `u32` handle instead of a `MAX_CELL_LEN`-byte `ArrayString`) shrinks the struct that the
+/// grid allocates one of per column and `reset`s on every write. A real session only ever
+/// produces a handful of distinct extra-character combinations even over a long scrollback, so
+/// entries are cheap to accumulate and, like most string interners, are never evicted.
+mod extra_chars {
+    use std::sync::{Mutex, OnceLock};
+
+    fn table() -> &'static Mutex<Vec<Box<[char]>>> {
+        static TABLE: OnceLock<Mutex<Vec<Box<[char]>>>> = OnceLock::new();
+        TABLE.get_or_init(|| Mutex::new(Vec::new()))
+    }
+
+    /// Intern `chars`, returning a handle `lookup` can later exchange for an equal slice.
+    pub fn intern(chars: &[char]) -> u32 {
+        let mut table = table().lock().unwrap();
+        if let Some(index) = table.iter().position(|existing| &**existing == chars) {
+            return index as u32;
+        }
+        table.push(chars.into());
+        (table.len() - 1) as u32
+    }
+
+    pub fn lookup(handle: u32) -> Box<[char]> {
+        table().lock().unwrap()[handle as usize].clone()
+    }
+}
+
+/// Process-wide, append-only interner for hyperlink target URIs, mirroring `extra_chars`: a
+/// `Cell` only needs a handle, not the URI's bytes inline. Handles are `u16` rather than
+/// `extra_chars`'s `u32` - a session realistically never points at more than a few thousand
+/// distinct links, and the narrower handle keeps `Cell` from growing past `extra`'s `u32` and
+/// tipping the struct over `MAX_CELL_LEN`.
+mod hyperlinks {
+    use std::sync::{Mutex, OnceLock};
+
+    fn table() -> &'static Mutex<Vec<Box<str>>> {
+        static TABLE: OnceLock<Mutex<Vec<Box<str>>>> = OnceLock::new();
+        TABLE.get_or_init(|| Mutex::new(Vec::new()))
+    }
+
+    /// Intern `uri`, returning a handle `lookup` can later exchange for an equal string.
+    ///
+    /// Saturates at `u16::MAX` distinct links rather than panicking or wrapping: past that point
+    /// new links alias the last interned one, which is a far less surprising failure mode for a
+    /// display-only feature than a crash.
+    pub fn intern(uri: &str) -> u16 {
+        let mut table = table().lock().unwrap();
+        if let Some(index) = table.iter().position(|existing| &**existing == uri) {
+            return index as u16;
+        }
+        if table.len() < u16::MAX as usize {
+            table.push(uri.into());
+        }
+        (table.len() - 1) as u16
+    }
+
+    pub fn lookup(handle: u16) -> Box<str> {
+        table().lock().unwrap()[handle as usize].clone()
+    }
+}
+
 bitflags! {
     pub struct Flags: u16 {
         const INVERSE           = 0b00_0000_0001;
@@ -35,6 +102,8 @@ bitflags! {
         const DIM_BOLD          = 0b00_1000_0010;
         const HIDDEN            = 0b01_0000_0000;
         const STRIKEOUT         = 0b10_0000_0000;
+        /// Set by DECSCA; read by DECSERA to decide which cells a selective erase may touch.
+        const PROTECTED         = 0b100_0000_0000;
     }
 }
 
@@ -43,7 +112,13 @@ pub struct Cell {
     pub fg: Color,
     pub bg: Color,
     pub flags: Flags,
-    pub contents: ArrayString<[u8; MAX_CELL_LEN]>,
+    c: char,
+    /// Handle into `extra_chars`, offset by one so `None` (the common case) needs no lookup;
+    /// `Some(handle)` means `extra_chars::lookup(handle.get() - 1)`.
+    extra: Option<NonZeroU32>,
+    /// Handle into `hyperlinks`, offset by one the same way `extra` is. Set either by a real
+    /// OSC 8 sequence or synthetically, e.g. by `Term::apply_synthetic_hyperlinks`.
+    link: Option<NonZeroU16>,
 }
 
 impl Default for Cell {
@@ -71,7 +146,7 @@ impl LineLength for grid::Row<Cell> {
         }
 
         for (index, cell) in self[..].iter().rev().enumerate() {
-            if cell.contents.as_str() != " " {
+            if cell.as_str().as_str() != " " {
                 length = Column(self.len() - index);
                 break;
             }
@@ -98,19 +173,35 @@ impl Cell {
     }
 
     pub fn new(c: char, fg: Color, bg: Color) -> Cell {
-        let mut contents = ArrayString::new();
-        contents.push(c);
         Cell {
-            contents,
+            c,
+            extra: None,
+            link: None,
             bg,
             fg,
             flags: Flags::empty(),
         }
     }
 
+    /// Extra characters beyond the first, if this cell has any, in the order they were pushed.
+    fn extra_chars(&self) -> Box<[char]> {
+        match self.extra {
+            Some(handle) => extra_chars::lookup(handle.get() - 1),
+            None => Box::new([]),
+        }
+    }
+
+    /// Whether this cell needed more than one character (a combining mark, a ZWJ emoji sequence,
+    /// ...).
+    #[inline]
+    pub fn has_extra(&self) -> bool {
+        self.extra.is_some()
+    }
+
     #[inline]
     pub fn is_empty(&self) -> bool {
-        (self.contents.as_str() == " " || self.contents.as_str() == "\t")
+        (self.c == ' ' || self.c == '\t')
+            && !self.has_extra()
             && self.bg == Color::Named(NamedColor::Background)
             && !self
                 .flags
@@ -123,46 +214,62 @@ impl Cell {
         *self = *template;
     }
 
-    #[inline]
     pub fn as_str(&self) -> arrayvec::ArrayString<[u8; MAX_CELL_LEN]> {
-        self.contents
+        let mut contents = ArrayString::new();
+        contents.push(self.c);
+        for c in self.extra_chars().iter() {
+            contents.push(*c);
+        }
+        contents
     }
 
-    #[inline]
     pub fn chars(&self) -> [char; 1 + MAX_ZEROWIDTH_CHARS] {
         let mut out = [' '; 1 + MAX_ZEROWIDTH_CHARS];
-        for (i, chr) in self
-            .contents
-            .as_str()
-            .chars()
-            .enumerate()
-            .take(1 + MAX_ZEROWIDTH_CHARS)
-        {
-            out[i] = chr;
+        out[0] = self.c;
+        for (slot, c) in out[1..].iter_mut().zip(self.extra_chars().iter()) {
+            *slot = *c;
         }
         out
     }
 
+    /// The last character in this cell: an extra one, if it has any, otherwise its first (and
+    /// only) one. Used to look back at what a grapheme cluster in progress last appended, without
+    /// the padding `chars()` pads its unused slots with getting mistaken for real content.
+    #[inline]
+    pub fn last_char(&self) -> char {
+        self.extra_chars().last().copied().unwrap_or(self.c)
+    }
+
     #[inline]
     pub fn push_extra(&mut self, c: char) {
-        self.contents.push(c);
+        let mut extras = self.extra_chars().into_vec();
+        if extras.len() < MAX_ZEROWIDTH_CHARS {
+            extras.push(c);
+        }
+        let handle = extra_chars::intern(&extras);
+        self.extra = NonZeroU32::new(handle + 1);
     }
 
     #[inline]
     pub fn first_char(&self) -> char {
-        self.contents
-            .as_str()
-            .chars()
-            .next()
-            .expect("cell should always have at least one char")
+        self.c
     }
 
     #[inline]
-    pub fn set_char(&mut self, chr: char) -> ArrayString<[u8; MAX_CELL_LEN]> {
-        let mut contents = ArrayString::new();
-        contents.push(chr);
-        std::mem::swap(&mut self.contents, &mut contents);
-        contents
+    pub fn set_char(&mut self, chr: char) {
+        self.c = chr;
+        self.extra = None;
+    }
+
+    /// This cell's hyperlink target, if it has one.
+    #[inline]
+    pub fn hyperlink(&self) -> Option<Box<str>> {
+        self.link.map(|handle| hyperlinks::lookup(handle.get() - 1))
+    }
+
+    /// Set or clear this cell's hyperlink target.
+    pub fn set_hyperlink(&mut self, uri: Option<&str>) {
+        self.link = uri.map(|uri| NonZeroU16::new(hyperlinks::intern(uri) + 1).unwrap());
     }
 }
 
@@ -190,6 +297,42 @@ mod tests {
 
         assert_eq!(row.line_length(), Column(10));
     }
+
+    #[test]
+    fn hyperlink_round_trips_through_the_interner() {
+        let mut cell = Cell::default();
+        assert_eq!(cell.hyperlink(), None);
+
+        cell.set_hyperlink(Some("https://example.com"));
+        assert_eq!(
+            cell.hyperlink().as_deref(),
+            Some("https://example.com")
+        );
+
+        cell.set_hyperlink(None);
+        assert_eq!(cell.hyperlink(), None);
+    }
+
+    #[test]
+    fn extra_chars_round_trip_through_the_interner() {
+        let mut cell = Cell::default();
+        cell.set_char('e');
+        cell.push_extra('\u{301}');
+
+        assert!(cell.has_extra());
+        assert_eq!(cell.first_char(), 'e');
+        assert_eq!(cell.last_char(), '\u{301}');
+        assert_eq!(cell.as_str().as_str(), "e\u{301}");
+    }
+
+    #[test]
+    fn cell_shrunk_by_interning_its_extra_characters() {
+        // `Cell` used to carry a `MAX_CELL_LEN`-byte `ArrayString` inline (`super::MAX_CELL_LEN`
+        // is 24 on its own); interning the rare extra characters out into a handle instead keeps
+        // the struct well under that, which matters since the grid allocates and `reset`s one per
+        // column.
+        assert!(std::mem::size_of::<Cell>() < super::MAX_CELL_LEN);
+    }
 }
 
 #[cfg(all(test, feature = "bench"))]
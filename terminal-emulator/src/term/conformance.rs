@@ -0,0 +1,201 @@
+//! A small escape-sequence conformance suite, named and grouped after the categories in
+//! [esctest](https://github.com/kovidgoyal/esctest) (cursor movement, erase, SGR, scrolling
+//! regions), so coverage gaps in `Term`'s `ansi::Handler` implementation show up as named failing
+//! tests instead of only surfacing as bug reports against real programs.
+//!
+//! This drives `Term` directly through `ansi::Processor`, the same entry point
+//! `ProcessState::on_data` feeds live pty bytes through in the `mux` binary, rather than spawning
+//! a PTY pair and an external Python harness: esctest itself expects to launch and control a real
+//! terminal emulator process over a pty, which `terminal-emulator` (a library with no binary or
+//! process of its own) has nothing to plug into. Driving the handler in-process keeps these in
+//! the same style as the rest of the crate's tests and runnable without any extra dependencies or
+//! setup, at the cost of only covering what's been ported here as a test rather than esctest's
+//! full suite.
+//!
+//! Every test is `#[ignore]`d so a plain `cargo test` stays fast; run them explicitly with
+//! `cargo test -- --ignored conformance`.
+//!
+//! The `_snapshot` tests below are the golden-file half of this suite: instead of asserting one
+//! cell or flag at a time, [`grid_snapshot`] renders the rows that matter to plain text and
+//! compares the whole block against an inline "golden" string literal, which reads closer to
+//! what the terminal would actually look like and catches regressions a narrower assertion might
+//! miss. There's no fixture-file convention elsewhere in this crate (every other test embeds its
+//! expectations inline), so the golden text lives in the test function as a string constant
+//! rather than a file on disk.
+//!
+//! What this suite deliberately doesn't include is a tool to *record* new cases from a live pty:
+//! that needs to spawn and drive a real process, which means a pty/process-handling dependency
+//! (`tokio-pty-process` or similar) that this crate doesn't otherwise have any use for - `mux`'s
+//! `src/` binary is the only place in this repository that talks to ptys, specifically so this
+//! library can stay a dependency-light, embeddable `Term`/`Processor` implementation. Recording
+//! would belong there, built on `process::Process`, rather than here.
+
+use super::{SizeInfo, Term};
+use crate::ansi::Processor;
+use crate::index::{Column, Line};
+
+fn conformance_term() -> Term {
+    Term::new(SizeInfo {
+        width: 80.0,
+        height: 24.0,
+        cell_width: 1.0,
+        cell_height: 1.0,
+        padding_x: 0.0,
+        padding_y: 0.0,
+        dpr: 1.0,
+    })
+    .unwrap()
+}
+
+fn feed(term: &mut Term, input: &str) {
+    let mut processor = Processor::new();
+    let mut sink = Vec::new();
+    for byte in input.bytes() {
+        processor.advance(term, byte, &mut sink);
+    }
+}
+
+fn cell_char(term: &Term, line: usize, col: usize) -> char {
+    term.grid()[Line(line)][Column(col)].first_char()
+}
+
+/// Render the first `lines` rows of `term`'s grid as plain text, one row per line with trailing
+/// blanks trimmed, for comparing against a golden string literal.
+fn grid_snapshot(term: &Term, lines: usize) -> String {
+    let cols = term.grid().num_cols().0;
+    (0..lines)
+        .map(|line| {
+            let row = &term.grid()[Line(line)];
+            let text: String = (0..cols).map(|col| row[Column(col)].first_char()).collect();
+            text.trim_end().to_owned()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+#[ignore]
+fn cursor_cup_moves_to_absolute_position() {
+    let mut term = conformance_term();
+    feed(&mut term, "\x1b[5;10H");
+    assert_eq!(term.cursor().point, crate::index::Point::new(Line(4), Column(9)));
+}
+
+#[test]
+#[ignore]
+fn cursor_cuu_cud_cuf_cub_move_relative() {
+    let mut term = conformance_term();
+    feed(&mut term, "\x1b[5;5H");
+    feed(&mut term, "\x1b[2A");
+    assert_eq!(term.cursor().point.line, Line(2));
+    feed(&mut term, "\x1b[3B");
+    assert_eq!(term.cursor().point.line, Line(5));
+    feed(&mut term, "\x1b[4C");
+    assert_eq!(term.cursor().point.col, Column(8));
+    feed(&mut term, "\x1b[1D");
+    assert_eq!(term.cursor().point.col, Column(7));
+}
+
+#[test]
+#[ignore]
+fn erase_ed_clears_from_cursor_to_end_of_screen() {
+    let mut term = conformance_term();
+    feed(&mut term, "AAAA\r\nBBBB\r\nCCCC");
+    feed(&mut term, "\x1b[2;1H");
+    feed(&mut term, "\x1b[0J");
+    assert_eq!(cell_char(&term, 0, 0), 'A');
+    assert_eq!(cell_char(&term, 1, 0), ' ');
+    assert_eq!(cell_char(&term, 2, 0), ' ');
+}
+
+#[test]
+#[ignore]
+fn erase_el_clears_from_cursor_to_end_of_line() {
+    let mut term = conformance_term();
+    feed(&mut term, "ABCDE");
+    feed(&mut term, "\x1b[1;3H");
+    feed(&mut term, "\x1b[0K");
+    assert_eq!(cell_char(&term, 0, 0), 'A');
+    assert_eq!(cell_char(&term, 0, 1), 'B');
+    assert_eq!(cell_char(&term, 0, 2), ' ');
+    assert_eq!(cell_char(&term, 0, 3), ' ');
+}
+
+#[test]
+#[ignore]
+fn sgr_bold_sets_the_bold_flag_on_written_cells() {
+    let mut term = conformance_term();
+    feed(&mut term, "\x1b[1mX");
+    assert!(term.grid()[Line(0)][Column(0)]
+        .flags
+        .contains(super::cell::Flags::BOLD));
+}
+
+#[test]
+#[ignore]
+fn sgr_reset_clears_previously_set_attributes() {
+    let mut term = conformance_term();
+    feed(&mut term, "\x1b[1mX\x1b[0mY");
+    assert!(term.grid()[Line(0)][Column(0)]
+        .flags
+        .contains(super::cell::Flags::BOLD));
+    assert!(!term.grid()[Line(0)][Column(1)]
+        .flags
+        .contains(super::cell::Flags::BOLD));
+}
+
+#[test]
+#[ignore]
+fn decstbm_confines_scrolling_to_the_configured_region() {
+    let mut term = conformance_term();
+    feed(&mut term, "\x1b[2;4r");
+    feed(&mut term, "\x1b[1;1H");
+    feed(&mut term, "TOP");
+    feed(&mut term, "\x1b[4;1H\n");
+    // The top line sits outside the scrolling region, so it isn't pushed off by the scroll
+    // triggered inside the region.
+    assert_eq!(cell_char(&term, 0, 0), 'T');
+}
+
+#[test]
+#[ignore]
+fn wrapping_snapshot() {
+    let mut term = conformance_term();
+    let first_line: String = (0..80).map(|i| (b'0' + (i % 10) as u8) as char).collect();
+    feed(&mut term, &first_line);
+    feed(&mut term, "XY");
+
+    assert_eq!(grid_snapshot(&term, 2), vec![first_line, "XY".to_owned()].join("\n"));
+}
+
+#[test]
+#[ignore]
+fn erase_ed_within_a_scrolling_region_snapshot() {
+    let mut term = conformance_term();
+    feed(&mut term, "\x1b[2;4r");
+    // Reaching the bottom margin (row 4) scrolls the region up, so "DDDD" and "EEEE" both end up
+    // written to that same bottom row in turn, leaving "CCCC" (scrolled up into row 2) as the
+    // last surviving middle line.
+    feed(&mut term, "AAAA\r\nBBBB\r\nCCCC\r\nDDDD\r\nEEEE");
+    feed(&mut term, "\x1b[3;1H\x1b[0J");
+
+    // ED erases to the end of the whole screen, not just to the bottom margin of the scrolling
+    // region the cursor happens to be confined to for scrolling.
+    let expected = vec!["AAAA", "CCCC", "", "", ""].join("\n");
+    assert_eq!(grid_snapshot(&term, 5), expected);
+}
+
+#[test]
+#[ignore]
+fn sgr_and_cursor_movement_combo_snapshot() {
+    let mut term = conformance_term();
+    feed(&mut term, "\x1b[1;31mRED\x1b[0m\x1b[2;1HBLUE");
+
+    assert_eq!(grid_snapshot(&term, 2), "RED\nBLUE");
+    assert!(term.grid()[Line(0)][Column(0)]
+        .flags
+        .contains(super::cell::Flags::BOLD));
+    assert!(!term.grid()[Line(1)][Column(0)]
+        .flags
+        .contains(super::cell::Flags::BOLD));
+}
@@ -0,0 +1,497 @@
+//! Headless export of a `Term`'s currently visible grid to plain text, an ANSI-escaped byte
+//! stream, or a standalone HTML fragment - for CI snapshots and bug reports where what's wanted
+//! is a copy of what the screen shows, not a live, interactive pane.
+//!
+//! All three exporters only cover the visible viewport (the same rows `Index<Line>` already
+//! exposes, i.e. whatever `scroll_display` currently has on screen), not the rest of scrollback:
+//! `Grid`'s `Index<Line>` impl is viewport-relative by design, and scrolling the display to
+//! capture earlier history is a pre-existing, stateful operation a caller can already do via
+//! `Term::scroll_display` before calling one of these rather than something an export method
+//! should silently trigger as a side effect.
+
+use crate::ansi::{Color, NamedColor, Rgb};
+use crate::index::{Column, Line};
+use crate::term::cell::{Cell, Flags};
+use crate::term::Term;
+
+impl Term {
+    /// Render the visible grid as plain text, one line per row with trailing blanks trimmed.
+    pub fn render_text(&self) -> String {
+        let cols = self.grid().num_cols().0;
+        (0..self.grid().num_lines().0)
+            .map(|line| {
+                let row = &self.grid()[Line(line)];
+                let mut text = String::new();
+                for col in 0..cols {
+                    let cell = row[Column(col)];
+                    if !cell.flags.contains(Flags::WIDE_CHAR_SPACER) {
+                        text.push_str(cell.as_str().as_str());
+                    }
+                }
+                text.trim_end().to_owned()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Render the visible grid as plain text like `render_text`, but join lines connected by a
+    /// soft wrap (the last column's `Flags::WRAPLINE`, set by `Handler::input` when a line fills
+    /// without a hard newline) into one logical line instead of breaking on every physical row,
+    /// trimming trailing spaces once a logical line is complete rather than per physical row -
+    /// so a long shell command or URL that happened to wrap at the pane's width pastes back as
+    /// one line instead of being split mid-word.
+    pub fn render_text_unwrapped(&self) -> String {
+        let cols = self.grid().num_cols().0;
+        let mut out = String::new();
+        let mut line = String::new();
+
+        for row_index in 0..self.grid().num_lines().0 {
+            let row = &self.grid()[Line(row_index)];
+            let wrapped = row[Column(cols - 1)].flags.contains(Flags::WRAPLINE);
+            for col in 0..cols {
+                let cell = row[Column(col)];
+                if !cell.flags.contains(Flags::WIDE_CHAR_SPACER) {
+                    line.push_str(cell.as_str().as_str());
+                }
+            }
+            if !wrapped {
+                out.push_str(line.trim_end());
+                out.push('\n');
+                line.clear();
+            }
+        }
+        if !line.is_empty() {
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+        out.pop(); // drop the trailing newline, matching render_text's no-trailing-newline join
+
+        out
+    }
+
+    /// Render the visible grid as a byte stream of SGR-escaped text, reconstructing each cell's
+    /// colors and attributes well enough that `cat`-ing it back out reproduces the screen.
+    ///
+    /// Colors are re-emitted in whichever form the cell already stores them (a named color as
+    /// `30`-`37`/`90`-`97`, an indexed color as `38;5;N`, a truecolor `Rgb` as `38;2;R;G;B`)
+    /// rather than resolved to a specific palette, since `terminal-emulator` itself never
+    /// resolves `Color` to a concrete value - that only happens in `mux`'s own renderer, which
+    /// applies the user's configured palette and `color_mode`.
+    pub fn render_ansi(&self) -> String {
+        let cols = self.grid().num_cols().0;
+        let mut out = String::new();
+        let mut state = SgrState::default();
+
+        for line in 0..self.grid().num_lines().0 {
+            let row = &self.grid()[Line(line)];
+            for col in 0..cols {
+                let cell = row[Column(col)];
+                if cell.flags.contains(Flags::WIDE_CHAR_SPACER) {
+                    continue;
+                }
+                let wanted = SgrState::for_cell(&cell);
+                if wanted != state {
+                    wanted.write_transition(&mut out, &state);
+                    state = wanted;
+                }
+                out.push_str(cell.as_str().as_str());
+            }
+            if state != SgrState::default() {
+                out.push_str("\x1b[0m");
+                state = SgrState::default();
+            }
+            out.push('\n');
+        }
+        out.pop(); // drop the trailing newline, matching render_text's no-trailing-newline join
+
+        out
+    }
+
+    /// Render the visible grid as a standalone HTML fragment: a `<pre>` block whose cells with
+    /// non-default colors or attributes are wrapped in `<span style="...">`. Cells whose fg/bg
+    /// are the theme's plain `Color::Named(NamedColor::Foreground/Background)` get no span at
+    /// all, so the fragment inherits whatever text/background color the page embedding it uses.
+    ///
+    /// Cells carrying a hyperlink - set by a real OSC 8 sequence or synthesized by
+    /// `apply_synthetic_hyperlinks` - are additionally wrapped in `<a href="...">`, so a pane with
+    /// auto-hyperlinking enabled exports clickable links even though nothing the program did was
+    /// itself OSC 8.
+    pub fn render_html(&self) -> String {
+        let cols = self.grid().num_cols().0;
+        let mut out = String::from("<pre>\n");
+
+        for line in 0..self.grid().num_lines().0 {
+            let row = &self.grid()[Line(line)];
+            let mut state = SgrState::default();
+            let mut open = false;
+            let mut link: Option<Box<str>> = None;
+            for col in 0..cols {
+                let cell = row[Column(col)];
+                if cell.flags.contains(Flags::WIDE_CHAR_SPACER) {
+                    continue;
+                }
+
+                let wanted_link = cell.hyperlink();
+                if wanted_link != link {
+                    if link.is_some() {
+                        out.push_str("</a>");
+                    }
+                    if let Some(ref uri) = wanted_link {
+                        out.push_str("<a href=\"");
+                        html_escape(uri, &mut out);
+                        out.push_str("\">");
+                    }
+                    link = wanted_link;
+                }
+
+                let wanted = SgrState::for_cell(&cell);
+                if wanted != state {
+                    if open {
+                        out.push_str("</span>");
+                    }
+                    if let Some(style) = wanted.css_style() {
+                        out.push_str("<span style=\"");
+                        out.push_str(&style);
+                        out.push_str("\">");
+                        open = true;
+                    } else {
+                        open = false;
+                    }
+                    state = wanted;
+                }
+                html_escape(cell.as_str().as_str(), &mut out);
+            }
+            if open {
+                out.push_str("</span>");
+            }
+            if link.is_some() {
+                out.push_str("</a>");
+            }
+            out.push('\n');
+        }
+        out.push_str("</pre>");
+
+        out
+    }
+}
+
+fn html_escape(text: &str, out: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            _ => out.push(c),
+        }
+    }
+}
+
+/// The subset of a cell's rendering state that `render_ansi`/`render_html` need to diff runs of
+/// cells against, so a span/escape is only emitted when something actually changes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct SgrState {
+    fg: Swatch,
+    bg: Swatch,
+    flags: Flags,
+}
+
+impl Default for SgrState {
+    fn default() -> Self {
+        SgrState {
+            fg: Swatch::Default,
+            bg: Swatch::Default,
+            flags: Flags::empty(),
+        }
+    }
+}
+
+impl SgrState {
+    fn for_cell(cell: &Cell) -> Self {
+        SgrState {
+            fg: Swatch::from_color(cell.fg),
+            bg: Swatch::from_color(cell.bg),
+            flags: cell.flags
+                & (Flags::BOLD
+                    | Flags::DIM
+                    | Flags::ITALIC
+                    | Flags::UNDERLINE
+                    | Flags::INVERSE
+                    | Flags::STRIKEOUT
+                    | Flags::HIDDEN),
+        }
+    }
+
+    /// Write whatever escape sequence takes the terminal from `from` to `self`. Always resets
+    /// first rather than computing a minimal diff: cells change attributes often enough in
+    /// practice that a full reset per run is simpler and no less correct.
+    fn write_transition(&self, out: &mut String, from: &SgrState) {
+        let _ = from;
+        let mut codes = Vec::new();
+        if self.flags.contains(Flags::BOLD) {
+            codes.push("1".to_owned());
+        }
+        if self.flags.contains(Flags::DIM) {
+            codes.push("2".to_owned());
+        }
+        if self.flags.contains(Flags::ITALIC) {
+            codes.push("3".to_owned());
+        }
+        if self.flags.contains(Flags::UNDERLINE) {
+            codes.push("4".to_owned());
+        }
+        if self.flags.contains(Flags::INVERSE) {
+            codes.push("7".to_owned());
+        }
+        if self.flags.contains(Flags::HIDDEN) {
+            codes.push("8".to_owned());
+        }
+        if self.flags.contains(Flags::STRIKEOUT) {
+            codes.push("9".to_owned());
+        }
+        codes.extend(self.fg.sgr_codes(false));
+        codes.extend(self.bg.sgr_codes(true));
+
+        out.push_str("\x1b[0");
+        for code in codes {
+            out.push(';');
+            out.push_str(&code);
+        }
+        out.push('m');
+    }
+
+    fn css_style(&self) -> Option<String> {
+        let mut style = String::new();
+        if let Some(hex) = self.fg.css_hex() {
+            style.push_str("color:");
+            style.push_str(&hex);
+            style.push(';');
+        }
+        if let Some(hex) = self.bg.css_hex() {
+            style.push_str("background-color:");
+            style.push_str(&hex);
+            style.push(';');
+        }
+        if self.flags.contains(Flags::BOLD) {
+            style.push_str("font-weight:bold;");
+        }
+        if self.flags.contains(Flags::DIM) {
+            style.push_str("opacity:0.7;");
+        }
+        if self.flags.contains(Flags::ITALIC) {
+            style.push_str("font-style:italic;");
+        }
+        if self.flags.contains(Flags::UNDERLINE) {
+            style.push_str("text-decoration:underline;");
+        }
+        if self.flags.contains(Flags::STRIKEOUT) {
+            style.push_str("text-decoration:line-through;");
+        }
+        if self.flags.contains(Flags::HIDDEN) {
+            style.push_str("visibility:hidden;");
+        }
+
+        if style.is_empty() {
+            None
+        } else {
+            Some(style)
+        }
+    }
+}
+
+/// A cell's foreground or background, reduced to exactly the cases a renderer can act on: one of
+/// the 16 standard colors, a 256-color palette index, a truecolor value, or "no override" (the
+/// theme's own plain foreground/background, or anything else this exporter has no opinion on).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+enum Swatch {
+    #[default]
+    Default,
+    Standard(u8),
+    Indexed(u8),
+    Rgb(Rgb),
+}
+
+impl Swatch {
+    fn from_color(color: Color) -> Self {
+        match color {
+            Color::Spec(rgb) => Swatch::Rgb(rgb),
+            Color::Indexed(index) => Swatch::Indexed(index),
+            Color::Named(NamedColor::Black) => Swatch::Standard(0),
+            Color::Named(NamedColor::Red) => Swatch::Standard(1),
+            Color::Named(NamedColor::Green) => Swatch::Standard(2),
+            Color::Named(NamedColor::Yellow) => Swatch::Standard(3),
+            Color::Named(NamedColor::Blue) => Swatch::Standard(4),
+            Color::Named(NamedColor::Magenta) => Swatch::Standard(5),
+            Color::Named(NamedColor::Cyan) => Swatch::Standard(6),
+            Color::Named(NamedColor::White) => Swatch::Standard(7),
+            Color::Named(NamedColor::BrightBlack) => Swatch::Standard(8),
+            Color::Named(NamedColor::BrightRed) => Swatch::Standard(9),
+            Color::Named(NamedColor::BrightGreen) => Swatch::Standard(10),
+            Color::Named(NamedColor::BrightYellow) => Swatch::Standard(11),
+            Color::Named(NamedColor::BrightBlue) => Swatch::Standard(12),
+            Color::Named(NamedColor::BrightMagenta) => Swatch::Standard(13),
+            Color::Named(NamedColor::BrightCyan) => Swatch::Standard(14),
+            Color::Named(NamedColor::BrightWhite) => Swatch::Standard(15),
+            // Dim variants only ever show up paired with `Flags::DIM`, which already carries the
+            // "dim" half of the meaning (SGR 2); re-emitting the non-dim base color is enough to
+            // reconstruct the rest.
+            Color::Named(NamedColor::DimBlack) => Swatch::Standard(0),
+            Color::Named(NamedColor::DimRed) => Swatch::Standard(1),
+            Color::Named(NamedColor::DimGreen) => Swatch::Standard(2),
+            Color::Named(NamedColor::DimYellow) => Swatch::Standard(3),
+            Color::Named(NamedColor::DimBlue) => Swatch::Standard(4),
+            Color::Named(NamedColor::DimMagenta) => Swatch::Standard(5),
+            Color::Named(NamedColor::DimCyan) => Swatch::Standard(6),
+            Color::Named(NamedColor::DimWhite) => Swatch::Standard(7),
+            // The theme's own plain foreground/background and everything derived from them
+            // (cursor colors, `BrightForeground`, `DimForeground`) have no fixed color of their
+            // own to report; leave them as "no override".
+            Color::Named(
+                NamedColor::Foreground
+                | NamedColor::Background
+                | NamedColor::CursorText
+                | NamedColor::Cursor
+                | NamedColor::BrightForeground
+                | NamedColor::DimForeground,
+            ) => Swatch::Default,
+        }
+    }
+
+    fn sgr_codes(self, background: bool) -> Vec<String> {
+        match self {
+            Swatch::Default => Vec::new(),
+            Swatch::Standard(index) if index < 8 => {
+                vec![((if background { 40 } else { 30 }) + index).to_string()]
+            }
+            Swatch::Standard(index) => {
+                vec![((if background { 100 } else { 90 }) + (index - 8)).to_string()]
+            }
+            Swatch::Indexed(index) => {
+                vec![(if background { "48" } else { "38" }).to_owned(), "5".to_owned(), index.to_string()]
+            }
+            Swatch::Rgb(rgb) => vec![
+                (if background { "48" } else { "38" }).to_owned(),
+                "2".to_owned(),
+                rgb.r.to_string(),
+                rgb.g.to_string(),
+                rgb.b.to_string(),
+            ],
+        }
+    }
+
+    fn css_hex(self) -> Option<String> {
+        match self {
+            Swatch::Default => None,
+            Swatch::Standard(index) => Some(standard_hex(index).to_owned()),
+            Swatch::Indexed(index) => Some(indexed_hex(index)),
+            Swatch::Rgb(rgb) => Some(format!("#{:02x}{:02x}{:02x}", rgb.r, rgb.g, rgb.b)),
+        }
+    }
+}
+
+/// The conventional 16-color ANSI palette (the same hex values most terminal emulators ship as
+/// their default theme), used only for HTML export - `render_ansi` re-emits the original SGR
+/// color index instead of resolving it, so a reader's own terminal theme still applies.
+fn standard_hex(index: u8) -> &'static str {
+    const COLORS: [&str; 16] = [
+        "#000000", "#cd0000", "#00cd00", "#cdcd00", "#0000ee", "#cd00cd", "#00cdcd", "#e5e5e5",
+        "#7f7f7f", "#ff0000", "#00ff00", "#ffff00", "#5c5cff", "#ff00ff", "#00ffff", "#ffffff",
+    ];
+    COLORS[index as usize]
+}
+
+/// The standard xterm 256-color palette: 0-15 the same 16 ANSI colors, 16-231 a 6x6x6 color
+/// cube, 232-255 a grayscale ramp.
+fn indexed_hex(index: u8) -> String {
+    if index < 16 {
+        return standard_hex(index).to_owned();
+    }
+    if index < 232 {
+        let index = index - 16;
+        let levels = [0u8, 95, 135, 175, 215, 255];
+        let r = levels[(index / 36) as usize];
+        let g = levels[((index / 6) % 6) as usize];
+        let b = levels[(index % 6) as usize];
+        return format!("#{:02x}{:02x}{:02x}", r, g, b);
+    }
+    let level = 8 + (index - 232) * 10;
+    format!("#{:02x}{:02x}{:02x}", level, level, level)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ansi::Processor;
+    use crate::term::SizeInfo;
+
+    fn term() -> Term {
+        Term::new(SizeInfo {
+            width: 80.0,
+            height: 24.0,
+            cell_width: 1.0,
+            cell_height: 1.0,
+            padding_x: 0.0,
+            padding_y: 0.0,
+            dpr: 1.0,
+        })
+        .unwrap()
+    }
+
+    fn feed(term: &mut Term, input: &str) {
+        let mut processor = Processor::new();
+        let mut sink = Vec::new();
+        for byte in input.bytes() {
+            processor.advance(term, byte, &mut sink);
+        }
+    }
+
+    #[test]
+    fn render_text_trims_trailing_blanks_per_line() {
+        let mut term = term();
+        feed(&mut term, "hi\r\nbye");
+        let text = term.render_text();
+        assert_eq!(text.lines().next().unwrap(), "hi");
+        assert_eq!(text.lines().nth(1).unwrap(), "bye");
+    }
+
+    #[test]
+    fn render_text_unwrapped_joins_soft_wrapped_lines() {
+        let mut term = term();
+        // 80-column pane; write 81 characters with no CR/LF so the 81st soft-wraps onto the next
+        // row (setting `WRAPLINE` on the first), followed by a hard newline and one more line.
+        feed(&mut term, &"x".repeat(80));
+        feed(&mut term, "z\r\ny");
+        let text = term.render_text_unwrapped();
+        let mut lines = text.lines();
+        assert_eq!(lines.next().unwrap(), format!("{}z", "x".repeat(80)));
+        assert_eq!(lines.next().unwrap(), "y");
+    }
+
+    #[test]
+    fn render_ansi_reconstructs_sgr_for_colored_text() {
+        let mut term = term();
+        feed(&mut term, "\x1b[31mred\x1b[0m");
+        let ansi = term.render_ansi();
+        assert!(ansi.contains("\x1b[0;31mred"));
+        assert!(ansi.contains("\x1b[0m"));
+    }
+
+    #[test]
+    fn render_html_wraps_colored_runs_in_spans_and_escapes_entities() {
+        let mut term = term();
+        feed(&mut term, "\x1b[32m<ok>\x1b[0m");
+        let html = term.render_html();
+        assert!(html.contains("color:#00cd00"));
+        assert!(html.contains("&lt;ok&gt;"));
+    }
+
+    #[test]
+    fn render_html_wraps_synthetic_hyperlinks_in_anchors() {
+        let mut term = term();
+        feed(&mut term, "see https://example.com/path for details");
+        term.set_auto_hyperlink(true);
+        term.apply_synthetic_hyperlinks();
+
+        let html = term.render_html();
+        assert!(html.contains("<a href=\"https://example.com/path\">https://example.com/path</a>"));
+    }
+}
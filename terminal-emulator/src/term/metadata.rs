@@ -0,0 +1,138 @@
+//! A side channel for small key-value annotations attached to a cell or a line, independent of
+//! `Cell` itself.
+//!
+//! Unlike a real OSC 8 hyperlink or a DECSCUSR cursor style, this metadata is never produced by
+//! anything the pty sends - it's written by whatever sits downstream of `Term` (a filter scanning
+//! `render_text` output, a plugin correlating lines against `git blame`, ...) and read back by a
+//! renderer that wants to act on it, e.g. shading a line by its commit age or showing a tooltip
+//! for a flagged cell. `Term` itself never interprets the keys or values; it's just storage.
+//!
+//! Addressing uses `Selection`'s coordinates (`index::Point<usize>`/a bare buffer line number,
+//! both absolute rather than viewport-relative), so an annotation survives scrolling the display.
+//! Unlike a selection, though, entries are never moved when new output scrolls the screen: buffer
+//! line 0 is always the bottom row, so once output pushes rows up, an entry stays at its
+//! coordinate and belongs to whichever row now sits there.
+
+use crate::index;
+use crate::term::Term;
+
+/// A small, ordered key-value map, interned so `Term` only ever stores a handle to one.
+type MetadataMap = Box<[(Box<str>, Box<str>)]>;
+
+/// Process-wide, append-only interner for metadata maps, mirroring `cell::hyperlinks`: distinct
+/// annotations are typically reused across many cells and lines (every line a given plugin flags
+/// "modified" points at the same pairs), so interning keeps `cell_metadata`/`line_metadata` down
+/// to one handle per entry instead of a clone of the map.
+mod intern {
+    use std::sync::{Mutex, OnceLock};
+
+    use super::MetadataMap;
+
+    fn table() -> &'static Mutex<Vec<MetadataMap>> {
+        static TABLE: OnceLock<Mutex<Vec<MetadataMap>>> = OnceLock::new();
+        TABLE.get_or_init(|| Mutex::new(Vec::new()))
+    }
+
+    /// Intern `pairs`, returning a handle `lookup` can later exchange for an equal slice.
+    pub fn intern(pairs: &[(Box<str>, Box<str>)]) -> u32 {
+        let mut table = table().lock().unwrap();
+        if let Some(index) = table.iter().position(|existing| &**existing == pairs) {
+            return index as u32;
+        }
+        table.push(pairs.into());
+        (table.len() - 1) as u32
+    }
+
+    pub fn lookup(handle: u32) -> MetadataMap {
+        table().lock().unwrap()[handle as usize].clone()
+    }
+}
+
+impl Term {
+    /// Attach `pairs` as metadata on the cell at `point` (absolute buffer coordinates, the same
+    /// addressing `Selection` and `Grid::iter_from` use), replacing whatever was there before.
+    /// An empty slice clears the entry instead of interning an empty map.
+    pub fn set_cell_metadata(&mut self, point: index::Point<usize>, pairs: &[(&str, &str)]) {
+        if pairs.is_empty() {
+            self.cell_metadata.remove(&point);
+            return;
+        }
+        self.cell_metadata.insert(point, intern_pairs(pairs));
+    }
+
+    /// The metadata attached to the cell at `point`, if any, oldest-set key first.
+    pub fn cell_metadata(&self, point: index::Point<usize>) -> Option<MetadataMap> {
+        self.cell_metadata.get(&point).copied().map(intern::lookup)
+    }
+
+    /// Attach `pairs` as metadata on buffer line `line` (absolute, not viewport-relative),
+    /// replacing whatever was there before. An empty slice clears the entry.
+    pub fn set_line_metadata(&mut self, line: usize, pairs: &[(&str, &str)]) {
+        if pairs.is_empty() {
+            self.line_metadata.remove(&line);
+            return;
+        }
+        self.line_metadata.insert(line, intern_pairs(pairs));
+    }
+
+    /// The metadata attached to buffer line `line`, if any, oldest-set key first.
+    pub fn line_metadata(&self, line: usize) -> Option<MetadataMap> {
+        self.line_metadata.get(&line).copied().map(intern::lookup)
+    }
+}
+
+fn intern_pairs(pairs: &[(&str, &str)]) -> u32 {
+    let owned: Vec<(Box<str>, Box<str>)> = pairs
+        .iter()
+        .map(|(key, value)| ((*key).into(), (*value).into()))
+        .collect();
+    intern::intern(&owned)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::index::{Column, Line, Point};
+    use crate::term::SizeInfo;
+    use crate::term::Term;
+
+    fn term() -> Term {
+        Term::new(SizeInfo {
+            width: 80.0,
+            height: 24.0,
+            cell_width: 1.0,
+            cell_height: 1.0,
+            padding_x: 0.0,
+            padding_y: 0.0,
+            dpr: 1.0,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn cell_metadata_round_trips_and_clears() {
+        let mut term = term();
+        let point = Point::new(Line(0), Column(0)).into();
+
+        assert_eq!(term.cell_metadata(point), None);
+
+        term.set_cell_metadata(point, &[("blame", "abc123"), ("author", "jack")]);
+        let metadata = term.cell_metadata(point).unwrap();
+        assert_eq!(&*metadata, &[("blame".into(), "abc123".into()), ("author".into(), "jack".into())][..]);
+
+        term.set_cell_metadata(point, &[]);
+        assert_eq!(term.cell_metadata(point), None);
+    }
+
+    #[test]
+    fn line_metadata_is_independent_of_cell_metadata() {
+        let mut term = term();
+        let point = Point::new(Line(0), Column(0)).into();
+
+        term.set_line_metadata(0, &[("blame", "def456")]);
+        assert_eq!(term.cell_metadata(point), None);
+        assert_eq!(
+            &*term.line_metadata(0).unwrap(),
+            &[("blame".into(), "def456".into())][..]
+        );
+    }
+}
@@ -15,7 +15,9 @@
 //! Exports the `Term` type which is a high-level API for the Grid
 use std::cmp::min;
 use std::ops::{Index, IndexMut, Range};
-use std::time::{Duration, Instant};
+#[cfg(not(feature = "wasm"))]
+use std::time::Instant;
+use std::time::Duration;
 use std::{io, ptr};
 
 use arraydeque::ArrayDeque;
@@ -31,8 +33,16 @@ use crate::grid::{
 use crate::index;
 use crate::selection::{self, Locations, Selection};
 use crate::term::cell::{Cell, LineLength, MAX_CELL_LEN};
+use crate::url::{UrlMatch, UrlMatcher};
 
 pub mod cell;
+mod export;
+mod metadata;
+pub mod scrollback;
+pub mod search;
+
+#[cfg(test)]
+mod conformance;
 
 /// A type that can expand a given point to a region
 ///
@@ -414,7 +424,7 @@ impl<'a> Iterator for RenderableCellsIter<'a> {
             let bg = cell.bg;
 
             return Some(RenderableCell {
-                contents: cell.contents,
+                contents: cell.as_str(),
                 line: cell.line,
                 column: cell.column,
                 flags: cell.flags,
@@ -444,7 +454,8 @@ pub mod mode {
             const FOCUS_IN_OUT        = 0b00_1000_0000_0000;
             const ALT_SCREEN          = 0b01_0000_0000_0000;
             const MOUSE_DRAG          = 0b10_0000_0000_0000;
-            const ANY                 = 0b11_1111_1111_1111;
+            const GRAPHEME_CLUSTERING = 0b100_0000_0000_0000;
+            const ANY                 = 0b111_1111_1111_1111;
             const NONE                = 0;
         }
     }
@@ -535,6 +546,15 @@ pub struct Cursor {
 
     /// Currently configured graphic character sets
     charsets: Charsets,
+
+    /// Shape set via DECSCUSR (CSI Ps SP q); `None` falls back to `Term::default_cursor_style`.
+    /// Kept on `Cursor` itself, alongside `template`/`charsets`, so DECSC/DECRC save and restore
+    /// it the same way they already do the rest of the cursor's attributes.
+    style: Option<CursorStyle>,
+
+    /// Blink state set via DECSCUSR alongside `style`; `None` falls back to
+    /// `Term::default_cursor_blinking`.
+    blinking: Option<bool>,
 }
 
 impl Cursor {
@@ -543,12 +563,45 @@ impl Cursor {
     }
 }
 
+/// Wall-clock instant behind `VisualBell`'s ringing state.
+///
+/// `std::time::Instant::now()` unconditionally panics on `wasm32-unknown-unknown` (there's no
+/// host clock for libstd to read), so a wasm32 build reads `Date.now()` through `js-sys` instead.
+/// Both arms only ever need to be subtracted from one another to get an elapsed `Duration`, so
+/// this doesn't need to be `Instant` itself, just something that can do that one thing.
+#[cfg(not(feature = "wasm"))]
+type BellInstant = Instant;
+
+#[cfg(feature = "wasm")]
+type BellInstant = f64;
+
+#[cfg(not(feature = "wasm"))]
+fn bell_now() -> BellInstant {
+    Instant::now()
+}
+
+#[cfg(feature = "wasm")]
+fn bell_now() -> BellInstant {
+    js_sys::Date::now()
+}
+
+#[cfg(not(feature = "wasm"))]
+fn bell_elapsed(earlier: BellInstant) -> Duration {
+    Instant::now().duration_since(earlier)
+}
+
+#[cfg(feature = "wasm")]
+fn bell_elapsed(earlier: BellInstant) -> Duration {
+    Duration::from_millis((js_sys::Date::now() - earlier).max(0.0) as u64)
+}
+
+#[derive(Clone)]
 pub struct VisualBell {
     /// Visual bell duration
     duration: Duration,
 
     /// The last time the visual bell rang, if at all
-    start_time: Option<Instant>,
+    start_time: Option<BellInstant>,
 }
 
 impl VisualBell {
@@ -561,8 +614,7 @@ impl VisualBell {
 
     /// Ring the visual bell, and return its intensity.
     pub fn ring(&mut self) -> f64 {
-        let now = Instant::now();
-        self.start_time = Some(now);
+        self.start_time = Some(bell_now());
         0.0
     }
 
@@ -576,7 +628,7 @@ impl VisualBell {
     pub fn completed(&mut self) -> bool {
         match self.start_time {
             Some(earlier) => {
-                if Instant::now().duration_since(earlier) >= self.duration {
+                if bell_elapsed(earlier) >= self.duration {
                     self.start_time = None;
                 }
                 false
@@ -586,6 +638,11 @@ impl VisualBell {
     }
 }
 
+/// Snapshotting a `Term` (`Term::clone`) duplicates the grid and every piece of cursor/mode state,
+/// but leaves process-wide interner handles (cell hyperlinks, `term::metadata`) as shared indices
+/// into those interners rather than cloning their backing tables, so a clone is cheap and still
+/// resolves to the same values.
+#[derive(Clone)]
 pub struct Term {
     /// The grid
     grid: Grid<Cell>,
@@ -602,6 +659,21 @@ pub struct Term {
     /// Would be nice to avoid the allocation...
     next_title: Option<String>,
 
+    /// The window title most recently set via OSC 0/2, independent of whether `next_title` has
+    /// been polled yet - this is what `title()` reports and what `push_title`/`pop_title`
+    /// (XTWINOPS 22/23) save and restore.
+    title: String,
+
+    /// Window titles saved by `push_title` (XTWINOPS 22), most recently pushed last.
+    title_stack: Vec<String>,
+
+    /// Text set via OSC 52 (`set_clipboard`), buffered here until `take_clipboard` reads it.
+    pending_clipboard: Option<String>,
+
+    /// Desktop notification requested via OSC 9/777, buffered here until `take_notification`
+    /// reads it. `None` title means OSC 9, which never carries one.
+    pending_notification: Option<(Option<String>, String)>,
+
     /// Got a request to set the mouse cursor; it's buffered here until the next draw
     next_mouse_cursor: Option<MouseCursor>,
 
@@ -643,12 +715,12 @@ pub struct Term {
 
     semantic_escape_chars: String,
 
-    /// Current style of the cursor
-    cursor_style: Option<CursorStyle>,
-
-    /// Default style for resetting the cursor
+    /// Style to fall back to when `self.cursor.style` is `None`.
     default_cursor_style: CursorStyle,
 
+    /// Blink state to fall back to when `self.cursor.blinking` is `None`.
+    default_cursor_blinking: bool,
+
     dynamic_title: bool,
 
     /// Number of spaces in one tab
@@ -659,8 +731,73 @@ pub struct Term {
 
     /// Hint that Alacritty should be closed
     should_exit: bool,
+
+    /// Working directory last reported by the shell via OSC 7, if any.
+    cwd: Option<String>,
+
+    /// Characters written past the right margin while DECAWM (line wrap) is off, for whichever
+    /// line the cursor was pinned to when they arrived. Kept only for the most recently-written
+    /// line rather than per scrollback row, since there's no stable row identity to key an entry
+    /// by once it scrolls off; this still covers the common log-viewing case of a single
+    /// continuously-overwritten status line running wider than the pane.
+    overflow_line: Option<(index::Line, String)>,
+
+    /// OSC 133 shell-integration marks, oldest first, capped at `MAX_PROMPT_MARKS` entries.
+    ///
+    /// Like `overflow_line`, positions are recorded in the grid's current line addressing, which
+    /// only stays meaningful until the line scrolls relative to where it was when recorded. That's
+    /// enough to jump among prompts still close to the viewport and to grab the output of the
+    /// command that just finished, which covers the common uses of shell integration; bookmarks
+    /// that survive arbitrary scrolling would need buffer-absolute addressing like `Selection`
+    /// uses, which is more machinery than this pulls in.
+    prompt_marks: Vec<(index::Line, ansi::SemanticPromptMark)>,
+
+    /// Patterns used by `visible_urls` to find clickable text in the grid.
+    url_matcher: UrlMatcher,
+
+    /// Whether `apply_synthetic_hyperlinks` should tag `visible_urls` matches with a hyperlink,
+    /// set via `set_auto_hyperlink`. Off by default: most frontends already do their own
+    /// click-to-open via `visible_urls` and don't need it duplicated onto cells as well.
+    auto_hyperlink: bool,
+
+    /// Whether East Asian "ambiguous width" characters (see UAX #11) are drawn as double-width,
+    /// set via `set_ambiguous_wide` from `Config::ambiguous_wide_chars`. CJK locales widely
+    /// expect them to take two cells; everyone else expects one, so this has to be a per-pane
+    /// choice rather than something `unicode-width` can decide on its own.
+    ambiguous_wide: bool,
+
+    /// Kitty keyboard protocol progressive-enhancement stack, pushed/popped/set by `CSI u`
+    /// variants (see `ansi::Handler::push_keyboard_mode` and friends). The top entry (if any) is
+    /// the currently active flag set; an empty stack means legacy key encoding.
+    keyboard_mode_stack: Vec<u8>,
+
+    /// xterm's `modifyOtherKeys` level, set via `CSI > 4 ; level m`. `0` (the default) is legacy
+    /// encoding; `1` reports modified keys that would otherwise be ambiguous or lost, `2` reports
+    /// every modified key. See `ansi::Handler::set_modify_other_keys`.
+    modify_other_keys: u8,
+
+    /// Per-cell metadata set by `set_cell_metadata`, keyed by absolute buffer coordinates (the
+    /// same addressing `Selection` and `Grid::iter_from` use). See `term::metadata`.
+    cell_metadata: std::collections::HashMap<index::Point<usize>, u32>,
+
+    /// Per-line metadata set by `set_line_metadata`, keyed by absolute buffer line. See
+    /// `term::metadata`.
+    line_metadata: std::collections::HashMap<usize, u32>,
 }
 
+/// Upper bound on how deep the kitty keyboard protocol's enhancement stack can grow; well above
+/// what any app pushes in practice (kitty itself documents nesting a handful of levels), just
+/// enough to keep a misbehaving application from growing it without bound.
+const MAX_KEYBOARD_MODE_STACK: usize = 16;
+
+/// Upper bound on how many OSC 133 prompt marks `Term` remembers at once.
+const MAX_PROMPT_MARKS: usize = 64;
+
+/// Upper bound on how deep the XTWINOPS title stack can grow; same rationale as
+/// `MAX_KEYBOARD_MODE_STACK` - generous for real use, just enough to stop an application from
+/// growing it without bound.
+const MAX_TITLE_STACK: usize = 16;
+
 /// Terminal size info
 #[derive(Debug, Copy, Clone)]
 pub struct SizeInfo {
@@ -717,6 +854,29 @@ impl SizeInfo {
     }
 }
 
+/// Errors constructing a `Term`.
+#[derive(Debug)]
+pub enum TermError {
+    /// `SizeInfo` left no room for even one column or one line once padding was subtracted out.
+    /// `Term::resize` clamps this case instead (there's an existing grid and cursor position
+    /// worth preserving), but a brand new `Term` has nothing to fall back to, so this is
+    /// surfaced to the caller rather than silently picking an arbitrary size.
+    DegenerateSize,
+}
+
+impl std::fmt::Display for TermError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TermError::DegenerateSize => write!(
+                f,
+                "terminal size leaves no room for at least one column and one line"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TermError {}
+
 impl Term {
     pub fn selection(&self) -> &Option<Selection> {
         &self.grid.selection
@@ -731,22 +891,348 @@ impl Term {
         self.next_title.take()
     }
 
+    /// The window title most recently set via OSC 0/2 (or restored by `pop_title`), unlike
+    /// `get_next_title` this doesn't consume it - safe to call on every draw.
+    #[inline]
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Text set via OSC 52 since the last call, if any, clearing it.
+    #[inline]
+    pub fn take_clipboard(&mut self) -> Option<String> {
+        self.pending_clipboard.take()
+    }
+
+    /// Check whether the terminal has rung the bell since the last call, clearing the flag.
+    #[inline]
+    pub fn take_bell(&mut self) -> bool {
+        self.next_is_urgent.take().unwrap_or(false)
+    }
+
+    /// Desktop notification requested via OSC 9/777 since the last call, if any, clearing it.
+    #[inline]
+    pub fn take_notification(&mut self) -> Option<(Option<String>, String)> {
+        self.pending_notification.take()
+    }
+
+    /// Working directory last reported by the shell via OSC 7, if any.
+    #[inline]
+    pub fn cwd(&self) -> Option<&str> {
+        self.cwd.as_deref()
+    }
+
+    /// Text captured off the right edge of the cursor's current line while DECAWM was off, if
+    /// any, for copy/search to reach content that would otherwise just have been dropped.
+    #[inline]
+    pub fn overflow(&self) -> Option<&str> {
+        match self.overflow_line {
+            Some((line, ref text)) if line == self.cursor.point.line => Some(text.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Append a character overflowing past the right margin to the current line's overflow
+    /// buffer, starting a fresh one if the cursor moved to a different line since the last call.
+    fn capture_overflow(&mut self, c: char) {
+        let line = self.cursor.point.line;
+        match self.overflow_line {
+            Some((overflow_line, ref mut text)) if overflow_line == line => text.push(c),
+            _ => self.overflow_line = Some((line, c.to_string())),
+        }
+    }
+
+    /// Nearest prompt start at or before `from`, for jumping backward through recent prompts.
+    pub fn previous_prompt(&self, from: index::Line) -> Option<index::Line> {
+        self.prompt_marks
+            .iter()
+            .filter(|(line, mark)| *mark == ansi::SemanticPromptMark::PromptStart && *line < from)
+            .map(|(line, _)| *line)
+            .max()
+    }
+
+    /// Nearest prompt start after `from`, for jumping forward through recent prompts.
+    pub fn next_prompt(&self, from: index::Line) -> Option<index::Line> {
+        self.prompt_marks
+            .iter()
+            .filter(|(line, mark)| *mark == ansi::SemanticPromptMark::PromptStart && *line > from)
+            .map(|(line, _)| *line)
+            .min()
+    }
+
+    /// Selection spanning the most recently finished command's output, from its OSC 133 `C`
+    /// (output start) mark to its `D` (command finished) mark, if both have been recorded.
+    pub fn last_command_output_selection(&self) -> Option<Selection> {
+        let output_start = self
+            .prompt_marks
+            .iter()
+            .rev()
+            .find(|(_, mark)| *mark == ansi::SemanticPromptMark::OutputStart)
+            .map(|(line, _)| *line)?;
+        let output_end = self
+            .prompt_marks
+            .iter()
+            .rev()
+            .find(|(line, mark)| {
+                matches!(mark, ansi::SemanticPromptMark::CommandFinished { .. }) && *line >= output_start
+            })
+            .map(|(line, _)| *line)?;
+
+        let start = self.grid.visible_to_buffer(index::Point {
+            line: output_start,
+            col: index::Column(0),
+        });
+        let end = self.grid.visible_to_buffer(index::Point {
+            line: output_end,
+            col: self.grid.num_cols() - index::Column(1),
+        });
+
+        let mut selection = Selection::lines(start);
+        selection.update(end, index::Side::Right);
+        Some(selection)
+    }
+
+    /// Replace the default URL/path patterns used by `visible_urls` with a custom set.
+    pub fn set_url_patterns(&mut self, patterns: Vec<regex::Regex>) {
+        self.url_matcher = UrlMatcher::new(patterns);
+    }
+
+    /// Set whether East Asian "ambiguous width" characters (see UAX #11) are treated as
+    /// double-width when computing cursor advance for `input`.
+    pub fn set_ambiguous_wide(&mut self, wide: bool) {
+        self.ambiguous_wide = wide;
+    }
+
+    /// Replace the characters that stop a `Selection::semantic` expansion (`semantic_search_left`/
+    /// `semantic_search_right`), e.g. double-clicking a word in a pane. The default (empty string)
+    /// treats no character as a boundary other than the edge of a non-wrapped line, so a semantic
+    /// selection runs to the end of the visible line; adding whitespace and punctuation here makes
+    /// double-click stop at word boundaries the way most terminal emulators do out of the box.
+    pub fn set_semantic_escape_chars(&mut self, chars: &str) {
+        self.semantic_escape_chars = chars.to_owned();
+    }
+
+    /// Currently active kitty keyboard protocol enhancement flags, i.e. the top of
+    /// `keyboard_mode_stack`, or `0` (legacy key encoding) if the application never pushed any.
+    pub fn keyboard_mode_flags(&self) -> u8 {
+        self.keyboard_mode_stack.last().copied().unwrap_or(0)
+    }
+
+    /// Currently active `modifyOtherKeys` level, set by `ansi::Handler::set_modify_other_keys`.
+    pub fn modify_other_keys(&self) -> u8 {
+        self.modify_other_keys
+    }
+
+    /// Width of `c` in cells, per `Term::ambiguous_wide`.
+    fn char_width(&self, c: char) -> Option<usize> {
+        if self.ambiguous_wide {
+            c.width_cjk()
+        } else {
+            c.width()
+        }
+    }
+
+    /// Column of the cell immediately before the cursor that actually holds content, skipping
+    /// back over a `WIDE_CHAR_SPACER` to the wide cell it belongs to.
+    fn last_real_cell_col(&self) -> index::Column {
+        let col = index::Column(self.cursor.point.col.0 - 1);
+        let line = self.cursor.point.line;
+        if self.grid[line][col].flags.contains(cell::Flags::WIDE_CHAR_SPACER) {
+            index::Column(col.0.saturating_sub(1))
+        } else {
+            col
+        }
+    }
+
+    /// If `c` continues an extended grapheme cluster started by the previous cell (an emoji ZWJ
+    /// sequence, a variation selector, a skin-tone modifier, or the second half of a regional
+    /// indicator flag pair), merge it into that cell and report `true` so the caller skips the
+    /// normal single-character `input` handling.
+    ///
+    /// This is a deliberately narrow, lookahead-free heuristic rather than full UAX #29
+    /// segmentation: `Handler::input` is called one codepoint at a time with no way to peek
+    /// ahead, so there's nowhere to buffer a cluster-in-progress without adding flush-on-every-
+    /// other-`Handler`-method bookkeeping. Covering exactly the sequences `CSI ?2027h` is meant
+    /// to advertise support for keeps this self-contained.
+    fn merge_grapheme_cluster(&mut self, c: char) -> bool {
+        let prev_col = self.last_real_cell_col();
+        let line = self.cursor.point.line;
+        let prev_cell = &self.grid[line][prev_col];
+        let prev_last_char = prev_cell.last_char();
+
+        let is_continuation = is_zwj(c)
+            || is_variation_selector(c)
+            || is_emoji_modifier(c)
+            || is_zwj(prev_last_char)
+            || (is_regional_indicator(c) && is_regional_indicator(prev_cell.first_char()));
+
+        if !is_continuation {
+            return false;
+        }
+
+        let num_cols = self.grid.num_cols();
+
+        self.grid[line][prev_col].push_extra(c);
+
+        if self.grid[line][prev_col].flags.contains(cell::Flags::WIDE_CHAR) {
+            // Already a wide cell with its spacer claimed; just append the content.
+            return true;
+        }
+
+        self.grid[line][prev_col].flags.insert(cell::Flags::WIDE_CHAR);
+
+        if prev_col + 1 < num_cols {
+            let spacer_col = prev_col + 1;
+            let spacer = &mut self.grid[line][spacer_col];
+            *spacer = self.cursor.template;
+            spacer.flags.insert(cell::Flags::WIDE_CHAR_SPACER);
+
+            // The continuation character landed one column before the cursor (it replaced what
+            // would have been a fresh cell); claim that column as the spacer and advance past it,
+            // same as the freshly-written wide-char path below does.
+            if spacer_col == self.cursor.point.col {
+                if (self.cursor.point.col + 1) < num_cols {
+                    self.cursor.point.col += 1;
+                } else {
+                    self.input_needs_wrap = true;
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Plain-text URL/path matches currently visible in the grid, joining wrapped rows into a
+    /// single logical line first so a match isn't missed at a wrap boundary.
+    pub fn visible_urls(&self) -> Vec<UrlMatch> {
+        let mut matches = Vec::new();
+        let mut text = String::new();
+        let mut positions: Vec<index::Point> = Vec::new();
+        let last_col = self.grid.num_cols() - index::Column(1);
+
+        for row in 0..self.grid.num_lines().0 {
+            let line = index::Line(row);
+            let grid_line = &self.grid[line];
+
+            for col in index::Range::from(index::Column(0)..self.grid.num_cols()) {
+                let cell = grid_line[col];
+                if cell.flags.contains(cell::Flags::WIDE_CHAR_SPACER) {
+                    continue;
+                }
+                for c in cell.as_str().chars() {
+                    text.push(c);
+                    positions.push(index::Point::new(line, col));
+                }
+            }
+
+            if !grid_line[last_col].flags.contains(cell::Flags::WRAPLINE) {
+                matches.extend(self.url_matcher.find_in_line(&text, &positions));
+                text.clear();
+                positions.clear();
+            }
+        }
+
+        if !text.is_empty() {
+            matches.extend(self.url_matcher.find_in_line(&text, &positions));
+        }
+
+        matches
+    }
+
+    /// Whether `apply_synthetic_hyperlinks` currently does anything when called.
+    #[inline]
+    pub fn auto_hyperlink(&self) -> bool {
+        self.auto_hyperlink
+    }
+
+    pub fn set_auto_hyperlink(&mut self, auto_hyperlink: bool) {
+        self.auto_hyperlink = auto_hyperlink;
+    }
+
+    /// If `auto_hyperlink` is enabled, tag every cell in each `visible_urls` match with a
+    /// synthetic hyperlink pointing at the matched text, so a client that only reads cells (e.g.
+    /// `render_html`) sees clickable links even though the program never emitted real OSC 8.
+    /// A no-op otherwise.
+    pub fn apply_synthetic_hyperlinks(&mut self) {
+        if !self.auto_hyperlink {
+            return;
+        }
+
+        let num_cols = self.grid.num_cols();
+        for url_match in self.visible_urls() {
+            let uri = url_match.text;
+            let mut point = url_match.start;
+            loop {
+                self.grid[point.line][point.col].set_hyperlink(Some(&uri));
+                if point == url_match.end {
+                    break;
+                }
+                if point.col + 1 >= num_cols {
+                    point.col = index::Column(0);
+                    point.line += 1;
+                } else {
+                    point.col += 1;
+                }
+            }
+        }
+    }
+
     pub fn scroll_display(&mut self, scroll: Scroll) {
         self.grid.scroll_display(scroll);
         self.dirty = true;
     }
 
+    /// Jump the viewport so buffer-absolute line `line` (see `scrollback_lines`, where `0` is the
+    /// most recent row) becomes the bottom-most visible row, clamped to `scroll_limit` the same
+    /// way `Scroll::Top`/`Lines` already are. `scroll_display` only moves the viewport relative to
+    /// wherever it already is; this is for jumping straight to a specific line a caller like
+    /// `:filter-pane` found by index rather than by scrolling past everything in between.
+    pub fn scroll_to_buffer_line(&mut self, line: usize) {
+        self.grid.scroll_to_line(line);
+        self.dirty = true;
+    }
+
+    /// Whether new output is allowed to move the viewport to the bottom. Disabling this lets a
+    /// pane stay scrolled up while its process keeps producing output, instead of being yanked
+    /// back down on every character; see `set_auto_scroll`.
+    #[inline]
+    pub fn auto_scroll(&self) -> bool {
+        self.auto_scroll
+    }
+
+    pub fn set_auto_scroll(&mut self, auto_scroll: bool) {
+        self.auto_scroll = auto_scroll;
+    }
+
+    /// Jump the viewport to the bottom and resume following new output, undoing a prior
+    /// `set_auto_scroll(false)`.
+    pub fn jump_to_bottom_and_follow(&mut self) {
+        self.auto_scroll = true;
+        self.scroll_display(Scroll::Bottom);
+    }
+
     #[inline]
     pub fn get_next_mouse_cursor(&mut self) -> Option<MouseCursor> {
         self.next_mouse_cursor.take()
     }
 
-    pub fn new(size: SizeInfo) -> Term {
+    pub fn new(size: SizeInfo) -> Result<Term, TermError> {
+        Term::with_scrollback(size, 1024)
+    }
+
+    /// Like `new`, but with an explicit scrollback size (in lines) instead of the default.
+    pub fn with_scrollback(size: SizeInfo, history_size: usize) -> Result<Term, TermError> {
         let num_cols = size.cols();
         let num_lines = size.lines();
 
+        // Unlike `resize`, there's no existing grid or cursor position here worth falling back
+        // to, so a size that leaves no room for even one column or line is reported rather than
+        // silently clamped.
+        if num_cols < index::Column(1) || num_lines < index::Line(1) {
+            return Err(TermError::DegenerateSize);
+        }
+
         let semantic_escape_chars = "".to_owned();
-        let history_size = 1024; // TODO
         let default_cursor_style = ansi::CursorStyle::Block;
         let dynamic_title = true;
         let auto_scroll = true;
@@ -763,8 +1249,12 @@ impl Term {
 
         let scroll_region = index::Line(0)..grid.num_lines();
 
-        Term {
+        Ok(Term {
             next_title: None,
+            title: String::new(),
+            title_stack: Vec::new(),
+            pending_clipboard: None,
+            pending_notification: None,
             next_mouse_cursor: None,
             dirty: false,
             visual_bell: VisualBell::new(),
@@ -782,13 +1272,23 @@ impl Term {
             scroll_region,
             size_info: size,
             semantic_escape_chars,
-            cursor_style: None,
             default_cursor_style,
+            default_cursor_blinking: true,
             dynamic_title,
             tabspaces,
             auto_scroll,
             should_exit: false,
-        }
+            cwd: None,
+            overflow_line: None,
+            prompt_marks: Vec::new(),
+            url_matcher: UrlMatcher::default(),
+            auto_hyperlink: false,
+            ambiguous_wide: false,
+            keyboard_mode_stack: Vec::new(),
+            modify_other_keys: 0,
+            cell_metadata: std::collections::HashMap::new(),
+            line_metadata: std::collections::HashMap::new(),
+        })
     }
 
     #[inline]
@@ -853,7 +1353,7 @@ impl Term {
                             // Skip over whitespace until next tab-stop once a tab was found
                             if tabs[col] {
                                 tab_mode = false;
-                            } else if cell.contents.as_str() == " " {
+                            } else if cell.as_str().as_str() == " " {
                                 continue;
                             }
                         }
@@ -862,7 +1362,7 @@ impl Term {
                             self.push_str(cell.as_str().as_str());
                         }
 
-                        if cell.contents.as_str() == "\t" {
+                        if cell.as_str().as_str() == "\t" {
                             tab_mode = true;
                         }
                     }
@@ -974,11 +1474,18 @@ impl Term {
             .and_then(|s| s.to_span(self, alt_screen))
             .map(|span| span.to_locations());
 
-        let cursor = self.cursor_style.unwrap_or(self.default_cursor_style);
+        let cursor = self.cursor.style.unwrap_or(self.default_cursor_style);
 
         RenderableCellsIter::new(&self.grid, &self.cursor.point, self.mode, selection, cursor)
     }
 
+    /// Grow or shrink how many scrollback lines the primary grid keeps, without touching the
+    /// visible viewport. The alt screen never has scrollback (see `with_scrollback`'s `alt`
+    /// grid, always built with a history size of `0`), so there's nothing to resize there.
+    pub fn set_scrollback_capacity(&mut self, history_size: usize) {
+        self.grid.update_history(history_size, &self.cursor.template);
+    }
+
     /// Resize terminal to new dimensions
     pub fn resize(&mut self, size: &SizeInfo) {
         debug!("Resizing terminal");
@@ -1085,6 +1592,69 @@ impl Term {
         &self.cursor
     }
 
+    /// Resolved cursor shape, taking the DECSCUSR-set style if any, and
+    /// falling back to the terminal's default otherwise.
+    #[inline]
+    pub fn cursor_style(&self) -> CursorStyle {
+        self.cursor.style.unwrap_or(self.default_cursor_style)
+    }
+
+    /// Resolved cursor blink state, taking the DECSCUSR-set value if any, and
+    /// falling back to the terminal's default otherwise.
+    #[inline]
+    pub fn cursor_blinking(&self) -> bool {
+        self.cursor.blinking.unwrap_or(self.default_cursor_blinking)
+    }
+
+    /// Reconstruct the SGR parameter string (without the leading CSI or trailing `m`) for the
+    /// resolved DECRQSS `m` query, from the cursor template's current attributes.
+    fn sgr_report(&self) -> String {
+        let flags = self.cursor.template.flags;
+        let mut params = vec!["0".to_owned()];
+        if flags.contains(cell::Flags::BOLD) {
+            params.push("1".to_owned());
+        }
+        if flags.contains(cell::Flags::DIM) {
+            params.push("2".to_owned());
+        }
+        if flags.contains(cell::Flags::ITALIC) {
+            params.push("3".to_owned());
+        }
+        if flags.contains(cell::Flags::UNDERLINE) {
+            params.push("4".to_owned());
+        }
+        if flags.contains(cell::Flags::INVERSE) {
+            params.push("7".to_owned());
+        }
+        if flags.contains(cell::Flags::HIDDEN) {
+            params.push("8".to_owned());
+        }
+        if flags.contains(cell::Flags::STRIKEOUT) {
+            params.push("9".to_owned());
+        }
+        push_sgr_color(&mut params, self.cursor.template.fg, true);
+        push_sgr_color(&mut params, self.cursor.template.bg, false);
+
+        let mut report = params.join(";");
+        report.push('m');
+        report
+    }
+
+    /// Reconstruct the resolved DECSCUSR parameter for the DECRQSS ` q` query. DECSCUSR has no
+    /// code of its own for the hollow-block cursor `mux` shows while unfocused, so that's
+    /// reported as whichever block code matches its blink state.
+    fn cursor_style_report(&self) -> String {
+        let code = match (self.cursor_style(), self.cursor_blinking()) {
+            (CursorStyle::Block, true) | (CursorStyle::HollowBlock, true) => 1,
+            (CursorStyle::Block, false) | (CursorStyle::HollowBlock, false) => 2,
+            (CursorStyle::Underline, true) => 3,
+            (CursorStyle::Underline, false) => 4,
+            (CursorStyle::Beam, true) => 5,
+            (CursorStyle::Beam, false) => 6,
+        };
+        format!("{} q", code)
+    }
+
     pub fn swap_alt(&mut self) {
         if self.alt {
             let template = &self.cursor.template;
@@ -1173,10 +1743,26 @@ impl ansi::Handler for Term {
     #[inline]
     fn set_title(&mut self, title: &str) {
         if self.dynamic_title {
+            self.title = title.to_owned();
             self.next_title = Some(title.to_owned());
         }
     }
 
+    /// OSC 7: remember the shell's current working directory
+    #[inline]
+    fn set_cwd(&mut self, uri: &str) {
+        self.cwd = Some(parse_cwd_uri(uri));
+    }
+
+    /// OSC 133: record a shell integration mark at the cursor's current line
+    #[inline]
+    fn semantic_prompt_mark(&mut self, mark: ansi::SemanticPromptMark) {
+        self.prompt_marks.push((self.cursor.point.line, mark));
+        if self.prompt_marks.len() > MAX_PROMPT_MARKS {
+            self.prompt_marks.remove(0);
+        }
+    }
+
     /// Set the mouse cursor
     #[inline]
     fn set_mouse_cursor(&mut self, cursor: MouseCursor) {
@@ -1186,7 +1772,13 @@ impl ansi::Handler for Term {
     #[inline]
     fn set_cursor_style(&mut self, style: Option<CursorStyle>) {
         trace!("Setting cursor style {:?}", style);
-        self.cursor_style = style;
+        self.cursor.style = style;
+    }
+
+    #[inline]
+    fn set_cursor_blinking(&mut self, blinking: Option<bool>) {
+        trace!("Setting cursor blinking {:?}", blinking);
+        self.cursor.blinking = blinking;
     }
 
     /// A character to be displayed
@@ -1199,6 +1791,7 @@ impl ansi::Handler for Term {
 
         if self.input_needs_wrap {
             if !self.mode.contains(mode::TermMode::LINE_WRAP) {
+                self.capture_overflow(c);
                 return;
             }
 
@@ -1224,8 +1817,20 @@ impl ansi::Handler for Term {
             self.input_needs_wrap = false;
         }
 
-        // Number of cells the char will occupy
-        if let Some(width) = c.width() {
+        if self.mode.contains(mode::TermMode::GRAPHEME_CLUSTERING)
+            && self.cursor.point.col.0 > 0
+            && self.merge_grapheme_cluster(c)
+        {
+            return;
+        }
+
+        // Number of cells the char will occupy.
+        //
+        // This comes from whatever Unicode version `unicode-width` ships (currently 15.1); there
+        // is no published crate version with Unicode 16 tables yet, and pinning a specific
+        // version per pane would mean vendoring and maintaining a full UAX #11 width table per
+        // supported version ourselves, which is well beyond what this single call site needs.
+        if let Some(width) = self.char_width(c) {
             let num_cols = self.grid.num_cols();
 
             // If in insert mode, first shift cells to the right.
@@ -1246,13 +1851,15 @@ impl ansi::Handler for Term {
 
             // Handle zero-width characters
             if width == 0 {
-                let col = self.cursor.point.col.0.saturating_sub(1);
+                let mut col = self.cursor.point.col.0.saturating_sub(1);
                 let line = self.cursor.point.line;
                 if self.grid[line][index::Column(col)]
                     .flags
                     .contains(cell::Flags::WIDE_CHAR_SPACER)
                 {
-                    drop(col.saturating_sub(1));
+                    // Landed on the second half of a wide char; the combining mark belongs on
+                    // the cell that actually holds the base character, one column further back.
+                    col = col.saturating_sub(1);
                 }
                 self.grid[line][index::Column(col)].push_extra(c);
                 return;
@@ -1317,8 +1924,21 @@ impl ansi::Handler for Term {
         let source = self.cursor.point.col;
         let destination = self.cursor.point.col + count;
         let num_cells = (self.size_info.cols() - destination).0;
+        let line_idx = self.cursor.point.line;
+
+        // If the insertion point falls in the middle of a wide character, the reset loop below
+        // clears the spacer half itself, but the wide half just before `source` is left outside
+        // that range and needs its stale flag cleared separately.
+        if source.0 > 0 && self.grid[line_idx][source]
+            .flags
+            .contains(cell::Flags::WIDE_CHAR_SPACER)
+        {
+            self.grid[line_idx][source - index::Column(1)]
+                .flags
+                .remove(cell::Flags::WIDE_CHAR);
+        }
 
-        let line = &mut self.grid[self.cursor.point.line];
+        let line = &mut self.grid[line_idx];
 
         unsafe {
             let src = line[source..].as_ptr();
@@ -1333,6 +1953,13 @@ impl ansi::Handler for Term {
         for c in &mut line[source..destination] {
             c.reset(&template);
         }
+
+        // A wide character shifted right may have had its spacer pushed off the end of the row;
+        // clear its own flag so it doesn't keep claiming a second column that no longer exists.
+        let last_col = self.grid.num_cols() - index::Column(1);
+        self.grid[line_idx][last_col]
+            .flags
+            .remove(cell::Flags::WIDE_CHAR);
     }
 
     #[inline]
@@ -1369,6 +1996,45 @@ impl ansi::Handler for Term {
         };
     }
 
+    fn report_termcap<W: io::Write>(&mut self, writer: &mut W, name: &str) {
+        trace!("XTGETTCAP query: {:?}", name);
+        let value = hex_decode(name)
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .and_then(|decoded_name| known_termcap_value(&decoded_name));
+
+        match value {
+            Some(value) => {
+                let _ = write!(writer, "\x1bP1+r{}={}\x1b\\", name, hex_encode(value.as_bytes()));
+            }
+            None => {
+                let _ = write!(writer, "\x1bP0+r{}\x1b\\", name);
+            }
+        }
+    }
+
+    fn report_setting<W: io::Write>(&mut self, writer: &mut W, request: &str) {
+        trace!("DECRQSS query: {:?}", request);
+        let value = match request {
+            "m" => Some(self.sgr_report()),
+            "r" => Some(format!(
+                "{};{}r",
+                self.scroll_region.start.0 + 1,
+                self.scroll_region.end.0
+            )),
+            " q" => Some(self.cursor_style_report()),
+            _ => None,
+        };
+
+        match value {
+            Some(value) => {
+                let _ = write!(writer, "\x1bP1$r{}\x1b\\", value);
+            }
+            None => {
+                let _ = write!(writer, "\x1bP0$r\x1b\\");
+            }
+        }
+    }
+
     #[inline]
     fn move_forward(&mut self, cols: index::Column) {
         trace!("Moving forward: {}", cols);
@@ -1405,7 +2071,7 @@ impl ansi::Handler for Term {
             count -= 1;
 
             let cell = &mut self.grid[&self.cursor.point];
-            if cell.contents.as_str() == " " {
+            if cell.as_str().as_str() == " " {
                 cell.set_char(self.cursor.charsets[self.active_charset].map('\t'));
             }
 
@@ -1506,6 +2172,13 @@ impl ansi::Handler for Term {
         self.tabs[column] = true;
     }
 
+    #[inline]
+    fn set_tabs_every_eight_columns(&mut self) {
+        trace!("Resetting tabstops to every 8 columns");
+        self.tabspaces = 8;
+        self.tabs = TabStops::new(self.grid.num_cols(), self.tabspaces);
+    }
+
     #[inline]
     fn scroll_up(&mut self, lines: index::Line) {
         let origin = self.scroll_region.start;
@@ -1547,8 +2220,28 @@ impl ansi::Handler for Term {
         );
         let start = self.cursor.point.col;
         let end = min(start + count, self.grid.num_cols());
+        let line = self.cursor.point.line;
+
+        // If either edge of the erased span falls in the middle of a wide character, clear the
+        // stale flag on whichever half survives outside the span so it doesn't keep claiming a
+        // column it no longer shares content with.
+        if start.0 > 0 && self.grid[line][start].flags.contains(cell::Flags::WIDE_CHAR_SPACER) {
+            self.grid[line][start - index::Column(1)]
+                .flags
+                .remove(cell::Flags::WIDE_CHAR);
+        }
+        if end.0 > 0
+            && end < self.grid.num_cols()
+            && self.grid[line][end - index::Column(1)]
+                .flags
+                .contains(cell::Flags::WIDE_CHAR)
+        {
+            self.grid[line][end]
+                .flags
+                .remove(cell::Flags::WIDE_CHAR_SPACER);
+        }
 
-        let row = &mut self.grid[self.cursor.point.line];
+        let row = &mut self.grid[line];
         let template = self.cursor.template; // Cleared cells have current background color set
         for c in &mut row[start..end] {
             c.reset(&template);
@@ -1562,9 +2255,32 @@ impl ansi::Handler for Term {
 
         let start = self.cursor.point.col;
         let end = min(start + count, self.grid.num_cols() - 1);
+        let line_idx = self.cursor.point.line;
+
+        // If the deleted span splits a wide character at either edge, clear the stale flag on
+        // whichever half survives so the shift below doesn't leave an orphaned spacer or a wide
+        // cell missing its other half.
+        if start.0 > 0 && self.grid[line_idx][start]
+            .flags
+            .contains(cell::Flags::WIDE_CHAR_SPACER)
+        {
+            self.grid[line_idx][start - index::Column(1)]
+                .flags
+                .remove(cell::Flags::WIDE_CHAR);
+        }
+        if end.0 > 0
+            && self.grid[line_idx][end - index::Column(1)]
+                .flags
+                .contains(cell::Flags::WIDE_CHAR)
+        {
+            self.grid[line_idx][end]
+                .flags
+                .remove(cell::Flags::WIDE_CHAR_SPACER);
+        }
+
         let n = (self.size_info.cols() - end).0;
 
-        let line = &mut self.grid[self.cursor.point.line];
+        let line = &mut self.grid[line_idx];
 
         unsafe {
             let src = line[end..].as_ptr();
@@ -1679,7 +2395,20 @@ impl ansi::Handler for Term {
                         .each(|cell| cell.reset(&template));
                 }
             }
-            ansi::ClearMode::All => self.grid.region_mut(..).each(|c| c.reset(&template)),
+            ansi::ClearMode::All => {
+                // A shell's `clear`/Ctrl+L sends this on the primary screen; scroll what's
+                // currently visible into history instead of discarding it, so the output isn't
+                // lost. Full-screen apps (vim, less, ...) redraw via repeated ED2s on the alt
+                // screen specifically so clears like that don't touch scrollback, so leave those
+                // alone.
+                if self.mode.contains(TermMode::ALT_SCREEN) {
+                    self.grid.region_mut(..).each(|c| c.reset(&template));
+                } else {
+                    let num_lines = self.grid.num_lines();
+                    self.grid
+                        .scroll_up(&(index::Line(0)..num_lines), num_lines, &template);
+                }
+            }
             ansi::ClearMode::Above => {
                 // If clearing more than one line
                 if self.cursor.point.line > index::Line(1) {
@@ -1700,33 +2429,108 @@ impl ansi::Handler for Term {
     }
 
     #[inline]
-    fn clear_tabs(&mut self, mode: ansi::TabulationClearMode) {
-        trace!("Clearing tabs: {:?}", mode);
+    fn selective_clear_line(&mut self, mode: ansi::LineClearMode) {
+        trace!("Selectively clearing line: {:?}", mode);
+        let mut template = self.cursor.template;
+        template.flags ^= template.flags;
+
+        let col = self.cursor.point.col;
+        let erase = |cell: &mut Cell| {
+            if !cell.flags.contains(cell::Flags::PROTECTED) {
+                cell.reset(&template);
+            }
+        };
+
         match mode {
-            ansi::TabulationClearMode::Current => {
-                let column = self.cursor.point.col;
-                self.tabs[column] = false;
+            ansi::LineClearMode::Right => {
+                let row = &mut self.grid[self.cursor.point.line];
+                row[col..].iter_mut().for_each(erase);
             }
-            ansi::TabulationClearMode::All => {
-                self.tabs.clear_all();
+            ansi::LineClearMode::Left => {
+                let row = &mut self.grid[self.cursor.point.line];
+                row[..=col].iter_mut().for_each(erase);
+            }
+            ansi::LineClearMode::All => {
+                let row = &mut self.grid[self.cursor.point.line];
+                row[..].iter_mut().for_each(erase);
             }
         }
     }
 
-    // Reset all important fields in the term struct
     #[inline]
-    fn reset_state(&mut self) {
-        self.input_needs_wrap = false;
-        self.next_title = None;
-        self.next_mouse_cursor = None;
-        self.alt = false;
-        self.cursor = Default::default();
-        self.active_charset = Default::default();
-        self.mode = Default::default();
-        self.next_is_urgent = None;
-        self.cursor_save = Default::default();
-        self.cursor_save_alt = Default::default();
-        self.cursor_style = None;
+    fn selective_clear_screen(&mut self, mode: ansi::ClearMode) {
+        trace!("Selectively clearing screen: {:?}", mode);
+        let mut template = self.cursor.template;
+        template.flags ^= template.flags;
+
+        let erase = |cell: &mut Cell| {
+            if !cell.flags.contains(cell::Flags::PROTECTED) {
+                cell.reset(&template);
+            }
+        };
+
+        match mode {
+            ansi::ClearMode::Below => {
+                self.grid[self.cursor.point.line][self.cursor.point.col..]
+                    .iter_mut()
+                    .for_each(erase);
+                if self.cursor.point.line < self.grid.num_lines() - 1 {
+                    self.grid
+                        .region_mut((self.cursor.point.line + 1)..)
+                        .each(erase);
+                }
+            }
+            ansi::ClearMode::All => {
+                self.grid.region_mut(..).each(erase);
+            }
+            ansi::ClearMode::Above => {
+                if self.cursor.point.line > index::Line(1) {
+                    self.grid
+                        .region_mut(..self.cursor.point.line)
+                        .each(erase);
+                }
+                let end = min(self.cursor.point.col + 1, self.grid.num_cols());
+                self.grid[self.cursor.point.line][..end]
+                    .iter_mut()
+                    .for_each(erase);
+            }
+            // Selective erase leaves existing content (and any protection on it) alone; there's
+            // nothing for protection to apply to once it's moved into history.
+            ansi::ClearMode::Saved => self.grid.clear_history(),
+        }
+    }
+
+    #[inline]
+    fn clear_tabs(&mut self, mode: ansi::TabulationClearMode) {
+        trace!("Clearing tabs: {:?}", mode);
+        match mode {
+            ansi::TabulationClearMode::Current => {
+                let column = self.cursor.point.col;
+                self.tabs[column] = false;
+            }
+            ansi::TabulationClearMode::All => {
+                self.tabs.clear_all();
+            }
+        }
+    }
+
+    // Reset all important fields in the term struct
+    #[inline]
+    fn reset_state(&mut self) {
+        self.input_needs_wrap = false;
+        self.next_title = None;
+        self.pending_clipboard = None;
+        self.pending_notification = None;
+        self.next_mouse_cursor = None;
+        self.alt = false;
+        self.cursor = Default::default();
+        self.active_charset = Default::default();
+        self.mode = Default::default();
+        self.next_is_urgent = None;
+        self.cursor_save = Default::default();
+        self.cursor_save_alt = Default::default();
+        self.tabspaces = 8;
+        self.tabs = TabStops::new(self.grid.num_cols(), self.tabspaces);
         self.grid.clear_history();
         self.grid.region_mut(..).each(|c| c.reset(&Cell::default()));
     }
@@ -1808,10 +2612,14 @@ impl ansi::Handler for Term {
             ansi::Mode::BracketedPaste => self.mode.insert(mode::TermMode::BRACKETED_PASTE),
             ansi::Mode::SgrMouse => self.mode.insert(mode::TermMode::SGR_MOUSE),
             ansi::Mode::LineWrap => self.mode.insert(mode::TermMode::LINE_WRAP),
+            ansi::Mode::DecApplicationKeypad => self.mode.insert(mode::TermMode::APP_KEYPAD),
             ansi::Mode::LineFeedNewLine => self.mode.insert(mode::TermMode::LINE_FEED_NEW_LINE),
             ansi::Mode::Origin => self.mode.insert(mode::TermMode::ORIGIN),
             ansi::Mode::DECCOLM => self.deccolm(),
             ansi::Mode::Insert => self.mode.insert(mode::TermMode::INSERT), // heh
+            ansi::Mode::GraphemeClustering => {
+                self.mode.insert(mode::TermMode::GRAPHEME_CLUSTERING)
+            }
             ansi::Mode::BlinkingCursor => {
                 trace!("... unimplemented mode");
             }
@@ -1848,16 +2656,73 @@ impl ansi::Handler for Term {
             ansi::Mode::BracketedPaste => self.mode.remove(mode::TermMode::BRACKETED_PASTE),
             ansi::Mode::SgrMouse => self.mode.remove(mode::TermMode::SGR_MOUSE),
             ansi::Mode::LineWrap => self.mode.remove(mode::TermMode::LINE_WRAP),
+            ansi::Mode::DecApplicationKeypad => self.mode.remove(mode::TermMode::APP_KEYPAD),
             ansi::Mode::LineFeedNewLine => self.mode.remove(mode::TermMode::LINE_FEED_NEW_LINE),
             ansi::Mode::Origin => self.mode.remove(mode::TermMode::ORIGIN),
             ansi::Mode::DECCOLM => self.deccolm(),
             ansi::Mode::Insert => self.mode.remove(mode::TermMode::INSERT),
+            ansi::Mode::GraphemeClustering => {
+                self.mode.remove(mode::TermMode::GRAPHEME_CLUSTERING)
+            }
             ansi::Mode::BlinkingCursor => {
                 trace!("... unimplemented mode");
             }
         }
     }
 
+    #[inline]
+    fn push_keyboard_mode(&mut self, flags: u8) {
+        if self.keyboard_mode_stack.len() >= MAX_KEYBOARD_MODE_STACK {
+            self.keyboard_mode_stack.remove(0);
+        }
+        self.keyboard_mode_stack.push(flags);
+    }
+
+    #[inline]
+    fn pop_keyboard_mode(&mut self, count: usize) {
+        let new_len = self.keyboard_mode_stack.len().saturating_sub(count);
+        self.keyboard_mode_stack.truncate(new_len);
+    }
+
+    #[inline]
+    fn push_title(&mut self) {
+        if self.title_stack.len() >= MAX_TITLE_STACK {
+            self.title_stack.remove(0);
+        }
+        self.title_stack.push(self.title.clone());
+    }
+
+    #[inline]
+    fn pop_title(&mut self) {
+        if let Some(title) = self.title_stack.pop() {
+            self.set_title(&title);
+        }
+    }
+
+    #[inline]
+    fn set_keyboard_mode(&mut self, flags: u8, mode: ansi::KeyboardModeOp) {
+        let current = self.keyboard_mode_flags();
+        let new_flags = match mode {
+            ansi::KeyboardModeOp::Set => flags,
+            ansi::KeyboardModeOp::Add => current | flags,
+            ansi::KeyboardModeOp::Remove => current & !flags,
+        };
+        match self.keyboard_mode_stack.last_mut() {
+            Some(top) => *top = new_flags,
+            None => self.keyboard_mode_stack.push(new_flags),
+        }
+    }
+
+    #[inline]
+    fn report_keyboard_mode<W: io::Write>(&mut self, writer: &mut W) {
+        let _ = write!(writer, "\x1b[?{}u", self.keyboard_mode_flags());
+    }
+
+    #[inline]
+    fn set_modify_other_keys(&mut self, level: u8) {
+        self.modify_other_keys = level;
+    }
+
     #[inline]
     fn set_scrolling_region(&mut self, region: Range<index::Line>) {
         trace!("Setting scrolling region: {:?}", region);
@@ -1892,8 +2757,13 @@ impl ansi::Handler for Term {
 
     /// Set the clipboard
     #[inline]
-    fn set_clipboard(&mut self, _string: &str) {
-        // TODO
+    fn set_clipboard(&mut self, string: &str) {
+        self.pending_clipboard = Some(string.to_owned());
+    }
+
+    #[inline]
+    fn notify(&mut self, title: Option<&str>, body: &str) {
+        self.pending_notification = Some((title.map(str::to_owned), body.to_owned()));
     }
 
     #[inline]
@@ -1904,8 +2774,140 @@ impl ansi::Handler for Term {
 
         self.grid.region_mut(..).each(|c| c.reset(&template));
     }
+
+    #[inline]
+    fn set_character_protection(&mut self, protected: bool) {
+        trace!("Setting character protection: {}", protected);
+        if protected {
+            self.cursor.template.flags.insert(cell::Flags::PROTECTED);
+        } else {
+            self.cursor.template.flags.remove(cell::Flags::PROTECTED);
+        }
+    }
+
+    fn copy_rectangle(
+        &mut self,
+        top: index::Line,
+        left: index::Column,
+        bottom: index::Line,
+        right: index::Column,
+        dest_top: index::Line,
+        dest_left: index::Column,
+    ) {
+        trace!(
+            "Copying rectangle {:?}..={:?}, {:?}..={:?} to {:?}, {:?}",
+            top, bottom, left, right, dest_top, dest_left
+        );
+        let bottom = min(bottom, self.grid.num_lines() - 1);
+        let right = min(right, self.grid.num_cols() - 1);
+        if top > bottom || left > right {
+            return;
+        }
+
+        // Snapshot the source rows first so a destination overlapping the source doesn't read
+        // cells this same copy already overwrote.
+        let rows: Vec<Vec<Cell>> = (top.0..=bottom.0)
+            .map(|line| self.grid[index::Line(line)][left..right + 1].to_vec())
+            .collect();
+
+        for (row_offset, row) in rows.into_iter().enumerate() {
+            let dest_line = dest_top + row_offset;
+            if dest_line >= self.grid.num_lines() {
+                break;
+            }
+            for (col_offset, cell) in row.into_iter().enumerate() {
+                let dest_col = dest_left + col_offset;
+                if dest_col >= self.grid.num_cols() {
+                    break;
+                }
+                self.grid[dest_line][dest_col] = cell;
+            }
+        }
+    }
+
+    fn fill_rectangle(
+        &mut self,
+        c: char,
+        top: index::Line,
+        left: index::Column,
+        bottom: index::Line,
+        right: index::Column,
+    ) {
+        trace!(
+            "Filling rectangle {:?}..={:?}, {:?}..={:?} with {:?}",
+            top, bottom, left, right, c
+        );
+        let bottom = min(bottom, self.grid.num_lines() - 1);
+        let right = min(right, self.grid.num_cols() - 1);
+        if top > bottom || left > right {
+            return;
+        }
+        let mut template = self.cursor.template;
+        template.set_char(c);
+
+        for line in top.0..=bottom.0 {
+            for cell in &mut self.grid[index::Line(line)][left..right + 1] {
+                *cell = template;
+            }
+        }
+    }
+
+    fn erase_rectangle(
+        &mut self,
+        top: index::Line,
+        left: index::Column,
+        bottom: index::Line,
+        right: index::Column,
+    ) {
+        trace!(
+            "Erasing rectangle {:?}..={:?}, {:?}..={:?}",
+            top, bottom, left, right
+        );
+        let bottom = min(bottom, self.grid.num_lines() - 1);
+        let right = min(right, self.grid.num_cols() - 1);
+        if top > bottom || left > right {
+            return;
+        }
+        let mut template = self.cursor.template;
+        template.flags ^= template.flags;
+
+        for line in top.0..=bottom.0 {
+            for cell in &mut self.grid[index::Line(line)][left..right + 1] {
+                cell.reset(&template);
+            }
+        }
+    }
+
+    fn selective_erase_rectangle(
+        &mut self,
+        top: index::Line,
+        left: index::Column,
+        bottom: index::Line,
+        right: index::Column,
+    ) {
+        trace!(
+            "Selectively erasing rectangle {:?}..={:?}, {:?}..={:?}",
+            top, bottom, left, right
+        );
+        let bottom = min(bottom, self.grid.num_lines() - 1);
+        let right = min(right, self.grid.num_cols() - 1);
+        if top > bottom || left > right {
+            return;
+        }
+        let mut template = self.cursor.template;
+        template.flags ^= template.flags;
+
+        for line in top.0..=bottom.0 {
+            for cell in &mut self.grid[index::Line(line)][left..right + 1] {
+                if !cell.flags.contains(cell::Flags::PROTECTED) {
+                    cell.reset(&template);
+                }
+            }
+        }
+    }
 }
 
+#[derive(Clone)]
 struct TabStops {
     tabs: Vec<bool>,
 }
@@ -1940,9 +2942,137 @@ impl IndexMut<index::Column> for TabStops {
     }
 }
 
+/// Extract the filesystem path from an OSC 7 `file://host/path` URI, percent-decoding it.
+///
+/// The host component (if any) is discarded; shells set it to their own hostname, which isn't
+/// meaningful to a local pane. Malformed input (no `file://` scheme) is passed through as-is, on
+/// the assumption that a shell misbehaving here still meant to report *some* path.
+fn parse_cwd_uri(uri: &str) -> String {
+    let without_scheme = uri.strip_prefix("file://").unwrap_or(uri);
+    let path = match without_scheme.find('/') {
+        Some(index) => &without_scheme[index..],
+        None => without_scheme,
+    };
+    percent_decode(path)
+}
+
+/// Decode `%XX` percent-escapes over `s`'s raw bytes, never re-slicing the `&str` itself: `s` is
+/// attacker/program-controlled (an OSC 7 cwd URI), and a naive `&s[i + 1..i + 3]` panics on a
+/// `str` that's valid UTF-8 but has a multi-byte character starting right after a literal `%`.
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hi = (bytes[i + 1] as char).to_digit(16);
+            let lo = (bytes[i + 2] as char).to_digit(16);
+            if let (Some(hi), Some(lo)) = (hi, lo) {
+                decoded.push((hi * 16 + lo) as u8);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// `U+200D ZERO WIDTH JOINER`, which chains emoji into a single glyph (person + ZWJ + heart +
+/// ZWJ + person, etc).
+fn is_zwj(c: char) -> bool {
+    c == '\u{200d}'
+}
+
+/// `U+FE0E`/`U+FE0F` text/emoji presentation selectors.
+fn is_variation_selector(c: char) -> bool {
+    c == '\u{fe0e}' || c == '\u{fe0f}'
+}
+
+/// `U+1F3FB`..=`U+1F3FF` Fitzpatrick skin-tone modifiers.
+fn is_emoji_modifier(c: char) -> bool {
+    ('\u{1f3fb}'..='\u{1f3ff}').contains(&c)
+}
+
+/// `U+1F1E6`..=`U+1F1FF` regional indicator symbols, which combine in pairs to form flag emoji.
+fn is_regional_indicator(c: char) -> bool {
+    ('\u{1f1e6}'..='\u{1f1ff}').contains(&c)
+}
+
+/// The handful of termcap/terminfo capabilities XTGETTCAP can answer for without a real
+/// terminfo database to consult: what `mux`'s own SGR parsing actually supports.
+fn known_termcap_value(name: &str) -> Option<String> {
+    match name {
+        "Co" | "colors" => Some("256".to_owned()),
+        "RGB" => Some("8".to_owned()),
+        _ => None,
+    }
+}
+
+/// Append the SGR parameter(s) selecting `color` to `params`, for `sgr_report`. Named defaults
+/// (`Color::Named(NamedColor::Foreground/Background)`) need no code at all - SGR 0 already resets
+/// to them - so they contribute nothing.
+fn push_sgr_color(params: &mut Vec<String>, color: Color, foreground: bool) {
+    match color {
+        Color::Named(NamedColor::Foreground) | Color::Named(NamedColor::Background) => {}
+        Color::Named(named) if (named as usize) < 8 => {
+            let base = if foreground { 30 } else { 40 };
+            params.push((base + named as usize).to_string());
+        }
+        Color::Named(named) if (named as usize) < 16 => {
+            let base = if foreground { 90 } else { 100 };
+            params.push((base + named as usize - 8).to_string());
+        }
+        // The remaining `NamedColor` variants (`Cursor`, `Dim*`, ...) are never actually stored
+        // in a cell template - only ever produced from the 16 above by `to_bright`/`to_dim` at
+        // render time - so there's nothing to report for them here.
+        Color::Named(_) => {}
+        Color::Indexed(index) => {
+            params.push(if foreground { "38".to_owned() } else { "48".to_owned() });
+            params.push("5".to_owned());
+            params.push(index.to_string());
+        }
+        Color::Spec(rgb) => {
+            params.push(if foreground { "38".to_owned() } else { "48".to_owned() });
+            params.push("2".to_owned());
+            params.push(rgb.r.to_string());
+            params.push(rgb.g.to_string());
+            params.push(rgb.b.to_string());
+        }
+    }
+}
+
+/// Encode `bytes` as lowercase hex, the encoding XTGETTCAP uses for both capability names and
+/// values.
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Decode a lowercase- or uppercase-hex string, as produced by `hex_encode`. `None` on a
+/// malformed (odd-length or non-hex) query rather than guessing at a partial answer.
+///
+/// Works over `s`'s raw bytes rather than re-slicing the `&str`: a DCS `+`-query payload is
+/// attacker/program-controlled, and a naive `&s[i..i + 2]` panics on a `str` that happens to be
+/// valid UTF-8 but contains a multi-byte character straddling a 2-byte step.
+fn hex_decode(s: &str) -> Option<Vec<u8>> {
+    let bytes = s.as_bytes();
+    if bytes.len() % 2 != 0 {
+        return None;
+    }
+    bytes
+        .chunks_exact(2)
+        .map(|pair| {
+            let hi = (pair[0] as char).to_digit(16)?;
+            let lo = (pair[1] as char).to_digit(16)?;
+            Some((hi * 16 + lo) as u8)
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{Cell, SizeInfo, Term};
+    use super::{mode, Cell, SizeInfo, Term, TermError};
     use crate::term::cell;
 
     use crate::ansi::{self, CharsetIndex, Handler, StandardCharset};
@@ -1951,6 +3081,23 @@ mod tests {
     use crate::selection::Selection;
     use std::mem;
 
+    #[test]
+    fn new_rejects_a_size_with_no_room_for_a_grid() {
+        let size = SizeInfo {
+            width: 10.0,
+            height: 51.0,
+            cell_width: 3.0,
+            cell_height: 3.0,
+            padding_x: 10.0,
+            padding_y: 0.0,
+            dpr: 1.0,
+        };
+        match Term::new(size) {
+            Err(TermError::DegenerateSize) => {}
+            other => panic!("expected DegenerateSize, got {:?}", other.map(|_| ())),
+        }
+    }
+
     #[test]
     fn semantic_selection_works() {
         let size = SizeInfo {
@@ -1962,7 +3109,7 @@ mod tests {
             padding_y: 0.0,
             dpr: 1.0,
         };
-        let mut term = Term::new(size);
+        let mut term = Term::new(size).unwrap();
         let mut grid: Grid<Cell> = Grid::new(index::Line(3), index::Column(5), 0, Cell::default());
         for i in 0..5 {
             for j in 0..2 {
@@ -2017,7 +3164,7 @@ mod tests {
             padding_y: 0.0,
             dpr: 1.0,
         };
-        let mut term = Term::new(size);
+        let mut term = Term::new(size).unwrap();
         let mut grid: Grid<Cell> = Grid::new(index::Line(1), index::Column(5), 0, Cell::default());
         for i in 0..5 {
             grid[index::Line(0)][index::Column(i)].set_char('a');
@@ -2045,7 +3192,7 @@ mod tests {
             padding_y: 0.0,
             dpr: 1.0,
         };
-        let mut term = Term::new(size);
+        let mut term = Term::new(size).unwrap();
         let mut grid: Grid<Cell> = Grid::new(index::Line(3), index::Column(3), 0, Cell::default());
         for l in 0..3 {
             if l != 1 {
@@ -2086,7 +3233,7 @@ mod tests {
             padding_y: 0.0,
             dpr: 1.0,
         };
-        let mut term = Term::new(size);
+        let mut term = Term::new(size).unwrap();
         let cursor = index::Point::new(index::Line(0), index::Column(0));
         term.configure_charset(
             CharsetIndex::G0,
@@ -2108,7 +3255,7 @@ mod tests {
             padding_y: 0.0,
             dpr: 1.0,
         };
-        let mut term: Term = Term::new(size);
+        let mut term: Term = Term::new(size).unwrap();
 
         // Add one line of scrollback
         term.grid.scroll_up(
@@ -2125,6 +3272,1047 @@ mod tests {
         scrolled_grid.scroll_display(Scroll::Top);
         assert_eq!(term.grid, scrolled_grid);
     }
+
+    #[test]
+    fn clear_screen_preserves_scrollback_on_primary() {
+        let size = SizeInfo {
+            width: 21.0,
+            height: 51.0,
+            cell_width: 3.0,
+            cell_height: 3.0,
+            padding_x: 0.0,
+            padding_y: 0.0,
+            dpr: 1.0,
+        };
+        let mut term: Term = Term::new(size).unwrap();
+        assert_eq!(term.grid.scroll_limit(), 0);
+
+        term.clear_screen(ansi::ClearMode::All);
+
+        // The whole visible screen should have scrolled into history rather than been dropped.
+        assert_eq!(term.grid.scroll_limit(), *term.grid.num_lines());
+    }
+
+    #[test]
+    fn clear_screen_does_not_touch_scrollback_on_alt() {
+        let size = SizeInfo {
+            width: 21.0,
+            height: 51.0,
+            cell_width: 3.0,
+            cell_height: 3.0,
+            padding_x: 0.0,
+            padding_y: 0.0,
+            dpr: 1.0,
+        };
+        let mut term: Term = Term::new(size).unwrap();
+        term.set_mode(ansi::Mode::from_primitive(true, 1049).unwrap());
+
+        term.clear_screen(ansi::ClearMode::All);
+
+        assert_eq!(term.grid.scroll_limit(), 0);
+    }
+
+    #[test]
+    fn take_bell_clears_after_reading() {
+        let size = SizeInfo {
+            width: 21.0,
+            height: 51.0,
+            cell_width: 3.0,
+            cell_height: 3.0,
+            padding_x: 0.0,
+            padding_y: 0.0,
+            dpr: 1.0,
+        };
+        let mut term: Term = Term::new(size).unwrap();
+        assert!(!term.take_bell());
+
+        term.bell();
+
+        assert!(term.take_bell());
+        assert!(!term.take_bell());
+    }
+
+    #[test]
+    fn take_clipboard_clears_after_reading() {
+        let size = SizeInfo {
+            width: 21.0,
+            height: 51.0,
+            cell_width: 3.0,
+            cell_height: 3.0,
+            padding_x: 0.0,
+            padding_y: 0.0,
+            dpr: 1.0,
+        };
+        let mut term: Term = Term::new(size).unwrap();
+        assert_eq!(term.take_clipboard(), None);
+
+        term.set_clipboard("copied text");
+
+        assert_eq!(term.take_clipboard(), Some("copied text".to_owned()));
+        assert_eq!(term.take_clipboard(), None);
+    }
+
+    #[test]
+    fn take_notification_clears_after_reading() {
+        let size = SizeInfo {
+            width: 21.0,
+            height: 51.0,
+            cell_width: 3.0,
+            cell_height: 3.0,
+            padding_x: 0.0,
+            padding_y: 0.0,
+            dpr: 1.0,
+        };
+        let mut term: Term = Term::new(size).unwrap();
+        assert_eq!(term.take_notification(), None);
+
+        term.notify(None, "build finished");
+        assert_eq!(
+            term.take_notification(),
+            Some((None, "build finished".to_owned()))
+        );
+        assert_eq!(term.take_notification(), None);
+
+        term.notify(Some("mux"), "build finished");
+        assert_eq!(
+            term.take_notification(),
+            Some((Some("mux".to_owned()), "build finished".to_owned()))
+        );
+    }
+
+    #[test]
+    fn decnkm_sets_same_mode_as_deckpam() {
+        let size = SizeInfo {
+            width: 21.0,
+            height: 51.0,
+            cell_width: 3.0,
+            cell_height: 3.0,
+            padding_x: 0.0,
+            padding_y: 0.0,
+            dpr: 1.0,
+        };
+        let mut term: Term = Term::new(size).unwrap();
+        assert!(!term.mode.contains(super::mode::TermMode::APP_KEYPAD));
+
+        term.set_mode(ansi::Mode::from_primitive(true, 66).unwrap());
+        assert!(term.mode.contains(super::mode::TermMode::APP_KEYPAD));
+
+        term.unset_mode(ansi::Mode::from_primitive(true, 66).unwrap());
+        assert!(!term.mode.contains(super::mode::TermMode::APP_KEYPAD));
+    }
+
+    #[test]
+    fn set_cwd_strips_scheme_and_host() {
+        let size = SizeInfo {
+            width: 21.0,
+            height: 51.0,
+            cell_width: 3.0,
+            cell_height: 3.0,
+            padding_x: 0.0,
+            padding_y: 0.0,
+            dpr: 1.0,
+        };
+        let mut term: Term = Term::new(size).unwrap();
+        assert_eq!(term.cwd(), None);
+
+        term.set_cwd("file://myhost/home/user/some%20dir");
+        assert_eq!(term.cwd(), Some("/home/user/some dir"));
+    }
+
+    #[test]
+    fn set_cwd_does_not_panic_on_a_multi_byte_character_after_a_percent_sign() {
+        let size = SizeInfo {
+            width: 21.0,
+            height: 51.0,
+            cell_width: 3.0,
+            cell_height: 3.0,
+            padding_x: 0.0,
+            padding_y: 0.0,
+            dpr: 1.0,
+        };
+        let mut term: Term = Term::new(size).unwrap();
+
+        // "%a\u{20ac}" isn't a valid `%XX` escape (the euro sign's first byte isn't a hex
+        // digit), so this is passed through unchanged rather than decoded - the point of the
+        // test is that it doesn't panic by re-slicing the multi-byte character mid-codepoint.
+        term.set_cwd("file://myhost/home/user/x%a\u{20ac}");
+        assert_eq!(term.cwd(), Some("/home/user/x%a\u{20ac}"));
+    }
+
+    #[test]
+    fn overflow_captures_input_past_right_margin_when_wrap_disabled() {
+        let size = SizeInfo {
+            width: 3.0,
+            height: 1.0,
+            cell_width: 1.0,
+            cell_height: 1.0,
+            padding_x: 0.0,
+            padding_y: 0.0,
+            dpr: 1.0,
+        };
+        let mut term: Term = Term::new(size).unwrap();
+        term.unset_mode(ansi::Mode::LineWrap);
+
+        for c in "abc".chars() {
+            term.input(c);
+        }
+        assert_eq!(term.overflow(), None);
+
+        term.input('d');
+        term.input('e');
+        assert_eq!(term.overflow(), Some("de"));
+    }
+
+    #[test]
+    fn prompt_marks_support_jumping_and_output_selection() {
+        let size = SizeInfo {
+            width: 21.0,
+            height: 51.0,
+            cell_width: 3.0,
+            cell_height: 3.0,
+            padding_x: 0.0,
+            padding_y: 0.0,
+            dpr: 1.0,
+        };
+        let mut term: Term = Term::new(size).unwrap();
+
+        assert_eq!(term.previous_prompt(index::Line(10)), None);
+        assert_eq!(term.next_prompt(index::Line(0)), None);
+        assert!(term.last_command_output_selection().is_none());
+
+        term.goto(index::Line(1), index::Column(0));
+        term.semantic_prompt_mark(ansi::SemanticPromptMark::PromptStart);
+        term.goto(index::Line(2), index::Column(0));
+        term.semantic_prompt_mark(ansi::SemanticPromptMark::CommandStart);
+        term.goto(index::Line(3), index::Column(0));
+        term.semantic_prompt_mark(ansi::SemanticPromptMark::OutputStart);
+        term.goto(index::Line(5), index::Column(0));
+        term.semantic_prompt_mark(ansi::SemanticPromptMark::CommandFinished { exit_code: Some(0) });
+
+        assert_eq!(term.previous_prompt(index::Line(5)), Some(index::Line(1)));
+        assert_eq!(term.next_prompt(index::Line(0)), Some(index::Line(1)));
+        assert_eq!(term.next_prompt(index::Line(1)), None);
+
+        let output_start = term.grid.visible_to_buffer(index::Point {
+            line: index::Line(3),
+            col: index::Column(0),
+        });
+        let output_end = term.grid.visible_to_buffer(index::Point {
+            line: index::Line(5),
+            col: index::Column(0),
+        });
+
+        let selection = term.last_command_output_selection().unwrap();
+        let locations = selection.to_span(&term, false).unwrap().to_locations();
+        assert_eq!(locations.start.line, output_end.line.min(output_start.line));
+        assert_eq!(locations.end.line, output_end.line.max(output_start.line));
+    }
+
+    #[test]
+    fn visible_urls_spans_wrapped_lines() {
+        let size = SizeInfo {
+            width: 30.0,
+            height: 30.0,
+            cell_width: 3.0,
+            cell_height: 3.0,
+            padding_x: 0.0,
+            padding_y: 0.0,
+            dpr: 1.0,
+        };
+        let mut term: Term = Term::new(size).unwrap();
+
+        for c in "see https://example.com/a/b for docs".chars() {
+            term.input(c);
+        }
+
+        let matches = term.visible_urls();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].text, "https://example.com/a/b");
+        assert!(matches[0].end.line > matches[0].start.line);
+    }
+
+    #[test]
+    fn csi_2027_toggles_grapheme_clustering_mode() {
+        let size = SizeInfo {
+            width: 21.0,
+            height: 51.0,
+            cell_width: 3.0,
+            cell_height: 3.0,
+            padding_x: 0.0,
+            padding_y: 0.0,
+            dpr: 1.0,
+        };
+        let mut term: Term = Term::new(size).unwrap();
+        assert!(!term.mode.contains(mode::TermMode::GRAPHEME_CLUSTERING));
+
+        term.set_mode(ansi::Mode::GraphemeClustering);
+        assert!(term.mode.contains(mode::TermMode::GRAPHEME_CLUSTERING));
+
+        term.unset_mode(ansi::Mode::GraphemeClustering);
+        assert!(!term.mode.contains(mode::TermMode::GRAPHEME_CLUSTERING));
+    }
+
+    #[test]
+    fn ambiguous_wide_affects_cursor_advance() {
+        let size = SizeInfo {
+            width: 3.0,
+            height: 1.0,
+            cell_width: 1.0,
+            cell_height: 1.0,
+            padding_x: 0.0,
+            padding_y: 0.0,
+            dpr: 1.0,
+        };
+        // U+00A7 SECTION SIGN is East Asian "ambiguous" width: narrow by default, wide when
+        // `ambiguous_wide` is set.
+        let ambiguous = '\u{a7}';
+
+        let mut term: Term = Term::new(size).unwrap();
+        term.input(ambiguous);
+        assert_eq!(term.cursor.point.col, index::Column(1));
+
+        let mut term: Term = Term::new(size).unwrap();
+        term.set_ambiguous_wide(true);
+        term.input(ambiguous);
+        assert_eq!(term.cursor.point.col, index::Column(2));
+        assert!(term.grid()[index::Line(0)][index::Column(1)]
+            .flags
+            .contains(cell::Flags::WIDE_CHAR_SPACER));
+    }
+
+    #[test]
+    fn grapheme_clustering_merges_zwj_emoji_sequence_into_one_wide_cell() {
+        let size = SizeInfo {
+            width: 21.0,
+            height: 51.0,
+            cell_width: 3.0,
+            cell_height: 3.0,
+            padding_x: 0.0,
+            padding_y: 0.0,
+            dpr: 1.0,
+        };
+        let mut term: Term = Term::new(size).unwrap();
+        term.set_mode(ansi::Mode::GraphemeClustering);
+
+        // Family emoji: man + ZWJ + woman + ZWJ + girl.
+        for c in "\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467}".chars() {
+            term.input(c);
+        }
+
+        let start = index::Point::new(index::Line(0), index::Column(0));
+        assert_eq!(
+            &*term.grid()[&start].as_str(),
+            "\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467}"
+        );
+        assert!(term.grid()[&start].flags.contains(cell::Flags::WIDE_CHAR));
+        assert!(term.grid()[index::Line(0)][index::Column(1)]
+            .flags
+            .contains(cell::Flags::WIDE_CHAR_SPACER));
+        assert_eq!(term.cursor.point.col, index::Column(2));
+    }
+
+    #[test]
+    fn grapheme_clustering_merges_regional_indicator_flag_pair() {
+        let size = SizeInfo {
+            width: 21.0,
+            height: 51.0,
+            cell_width: 3.0,
+            cell_height: 3.0,
+            padding_x: 0.0,
+            padding_y: 0.0,
+            dpr: 1.0,
+        };
+        let mut term: Term = Term::new(size).unwrap();
+        term.set_mode(ansi::Mode::GraphemeClustering);
+
+        // Regional indicators U+1F1FA U+1F1F8 spell the US flag.
+        for c in "\u{1f1fa}\u{1f1f8}".chars() {
+            term.input(c);
+        }
+
+        let start = index::Point::new(index::Line(0), index::Column(0));
+        assert_eq!(&*term.grid()[&start].as_str(), "\u{1f1fa}\u{1f1f8}");
+        assert!(term.grid()[&start].flags.contains(cell::Flags::WIDE_CHAR));
+        assert_eq!(term.cursor.point.col, index::Column(2));
+    }
+
+    fn wide_char_test_term() -> Term {
+        let size = SizeInfo {
+            width: 21.0,
+            height: 51.0,
+            cell_width: 3.0,
+            cell_height: 3.0,
+            padding_x: 0.0,
+            padding_y: 0.0,
+            dpr: 1.0,
+        };
+        Term::new(size).unwrap()
+    }
+
+    #[test]
+    fn zero_width_char_landing_on_a_spacer_combines_onto_the_wide_cell() {
+        let mut term = wide_char_test_term();
+        // U+1F600 GRINNING FACE (wide) followed by U+FE0F VARIATION SELECTOR-16 (zero-width),
+        // which the cursor advance leaves sitting right after the char's own spacer column.
+        term.input('\u{1f600}');
+        term.input('\u{fe0f}');
+
+        let start = index::Point::new(index::Line(0), index::Column(0));
+        assert_eq!(&*term.grid()[&start].as_str(), "\u{1f600}\u{fe0f}");
+        assert!(term.grid()[index::Line(0)][index::Column(1)]
+            .flags
+            .contains(cell::Flags::WIDE_CHAR_SPACER));
+    }
+
+    #[test]
+    fn erase_chars_on_a_spacer_clears_the_wide_flag_on_its_wide_cell() {
+        let mut term = wide_char_test_term();
+        term.input('\u{1f600}');
+        assert_eq!(term.cursor.point.col, index::Column(2));
+
+        // Erase just the spacer column, splitting the wide char in half.
+        term.goto_col(index::Column(1));
+        term.erase_chars(index::Column(1));
+
+        assert!(!term.grid()[index::Line(0)][index::Column(0)]
+            .flags
+            .contains(cell::Flags::WIDE_CHAR));
+        assert!(!term.grid()[index::Line(0)][index::Column(1)]
+            .flags
+            .contains(cell::Flags::WIDE_CHAR_SPACER));
+    }
+
+    #[test]
+    fn erase_chars_on_a_wide_cell_clears_the_spacer_flag_past_the_erased_span() {
+        let mut term = wide_char_test_term();
+        term.input('\u{1f600}');
+
+        // Erase just the wide half; its spacer at column 1 survives the erase untouched.
+        term.goto_col(index::Column(0));
+        term.erase_chars(index::Column(1));
+
+        assert!(!term.grid()[index::Line(0)][index::Column(1)]
+            .flags
+            .contains(cell::Flags::WIDE_CHAR_SPACER));
+    }
+
+    #[test]
+    fn delete_chars_starting_on_a_spacer_clears_the_wide_flag_behind_it() {
+        let mut term = wide_char_test_term();
+        term.input('\u{1f600}');
+        term.input('x');
+
+        // Delete starting at the spacer column; the wide char at column 0 loses its other half.
+        term.goto_col(index::Column(1));
+        term.delete_chars(index::Column(1));
+
+        assert!(!term.grid()[index::Line(0)][index::Column(0)]
+            .flags
+            .contains(cell::Flags::WIDE_CHAR));
+        assert_eq!(
+            &*term.grid()[index::Line(0)][index::Column(1)].as_str(),
+            "x"
+        );
+    }
+
+    #[test]
+    fn delete_chars_pulling_a_spacer_into_place_clears_its_orphaned_flag() {
+        let mut term = wide_char_test_term();
+        term.input('x');
+        term.input('\u{1f600}');
+
+        // Deleting the leading "x" shifts the wide char's spacer into column 0, where it would
+        // otherwise have no wide cell in front of it to belong to.
+        term.goto_col(index::Column(0));
+        term.delete_chars(index::Column(1));
+
+        assert!(!term.grid()[index::Line(0)][index::Column(0)]
+            .flags
+            .contains(cell::Flags::WIDE_CHAR_SPACER));
+    }
+
+    #[test]
+    fn insert_blank_at_a_spacer_clears_the_wide_flag_behind_it() {
+        let mut term = wide_char_test_term();
+        term.input('\u{1f600}');
+
+        // Insert a blank right at the spacer column, shifting it away from its wide char.
+        term.goto_col(index::Column(1));
+        term.insert_blank(index::Column(1));
+
+        assert!(!term.grid()[index::Line(0)][index::Column(0)]
+            .flags
+            .contains(cell::Flags::WIDE_CHAR));
+    }
+
+    #[test]
+    fn insert_blank_pushing_a_wide_char_off_the_row_clears_its_flag() {
+        let mut term = wide_char_test_term();
+        // Fill the row up to the last column with a wide char, so inserting a blank pushes its
+        // spacer off the end of the grid.
+        let num_cols = term.grid.num_cols();
+        term.goto_col(num_cols - index::Column(2));
+        term.input('\u{1f600}');
+
+        term.goto_col(index::Column(0));
+        term.insert_blank(index::Column(1));
+
+        let last_col = num_cols - index::Column(1);
+        assert!(!term.grid()[index::Line(0)][last_col]
+            .flags
+            .contains(cell::Flags::WIDE_CHAR));
+    }
+
+    #[test]
+    fn decsc_decrc_round_trips_cursor_style_and_blink() {
+        let size = SizeInfo {
+            width: 21.0,
+            height: 51.0,
+            cell_width: 3.0,
+            cell_height: 3.0,
+            padding_x: 0.0,
+            padding_y: 0.0,
+            dpr: 1.0,
+        };
+        let mut term: Term = Term::new(size).unwrap();
+
+        term.set_cursor_style(Some(ansi::CursorStyle::Underline));
+        term.set_cursor_blinking(Some(false));
+        term.save_cursor_position();
+
+        term.set_cursor_style(Some(ansi::CursorStyle::Beam));
+        term.set_cursor_blinking(Some(true));
+        assert_eq!(term.cursor_style(), ansi::CursorStyle::Beam);
+        assert!(term.cursor_blinking());
+
+        term.restore_cursor_position();
+        assert_eq!(term.cursor_style(), ansi::CursorStyle::Underline);
+        assert!(!term.cursor_blinking());
+    }
+
+    #[test]
+    fn push_title_and_pop_title_round_trip_through_the_stack() {
+        let size = SizeInfo {
+            width: 21.0,
+            height: 51.0,
+            cell_width: 3.0,
+            cell_height: 3.0,
+            padding_x: 0.0,
+            padding_y: 0.0,
+            dpr: 1.0,
+        };
+        let mut term: Term = Term::new(size).unwrap();
+
+        term.set_title("first");
+        term.push_title();
+        term.set_title("second");
+        assert_eq!(term.title(), "second");
+
+        term.pop_title();
+        assert_eq!(term.title(), "first");
+
+        // Popping an empty stack is a no-op rather than clearing the title.
+        term.pop_title();
+        assert_eq!(term.title(), "first");
+    }
+
+    #[test]
+    fn disabling_auto_scroll_keeps_the_viewport_put_while_scrolled_up() {
+        let size = SizeInfo {
+            width: 21.0,
+            height: 51.0,
+            cell_width: 3.0,
+            cell_height: 3.0,
+            padding_x: 0.0,
+            padding_y: 0.0,
+            dpr: 1.0,
+        };
+        let mut term: Term = Term::new(size).unwrap();
+
+        // Push enough lines into scrollback that there's somewhere to scroll up to.
+        for _ in 0..(*term.grid.num_lines() * 2) {
+            term.linefeed();
+        }
+
+        term.scroll_display(Scroll::Top);
+        let scrolled_offset = term.grid().display_offset();
+        assert_ne!(scrolled_offset, 0);
+
+        term.set_auto_scroll(false);
+        term.input('x');
+        assert_eq!(term.grid().display_offset(), scrolled_offset);
+
+        term.jump_to_bottom_and_follow();
+        assert!(term.auto_scroll());
+        assert_eq!(term.grid().display_offset(), 0);
+    }
+
+    #[test]
+    fn apply_synthetic_hyperlinks_is_a_noop_unless_enabled() {
+        let size = SizeInfo {
+            width: 21.0,
+            height: 51.0,
+            cell_width: 3.0,
+            cell_height: 3.0,
+            padding_x: 0.0,
+            padding_y: 0.0,
+            dpr: 1.0,
+        };
+        let mut term: Term = Term::new(size).unwrap();
+        for c in "http://x".chars() {
+            term.input(c);
+        }
+
+        assert!(!term.auto_hyperlink());
+        term.apply_synthetic_hyperlinks();
+        assert_eq!(
+            term.grid()[index::Line(0)][index::Column(0)].hyperlink(),
+            None
+        );
+
+        term.set_auto_hyperlink(true);
+        term.apply_synthetic_hyperlinks();
+        assert_eq!(
+            term.grid()[index::Line(0)][index::Column(0)]
+                .hyperlink()
+                .as_deref(),
+            Some("http://x")
+        );
+    }
+
+    #[test]
+    fn kitty_keyboard_mode_stack_push_set_pop() {
+        let size = SizeInfo {
+            width: 21.0,
+            height: 51.0,
+            cell_width: 3.0,
+            cell_height: 3.0,
+            padding_x: 0.0,
+            padding_y: 0.0,
+            dpr: 1.0,
+        };
+        let mut term: Term = Term::new(size).unwrap();
+        assert_eq!(term.keyboard_mode_flags(), 0);
+
+        term.push_keyboard_mode(1);
+        assert_eq!(term.keyboard_mode_flags(), 1);
+
+        term.set_keyboard_mode(0b10, ansi::KeyboardModeOp::Add);
+        assert_eq!(term.keyboard_mode_flags(), 0b11);
+
+        term.set_keyboard_mode(1, ansi::KeyboardModeOp::Remove);
+        assert_eq!(term.keyboard_mode_flags(), 0b10);
+
+        term.pop_keyboard_mode(1);
+        assert_eq!(term.keyboard_mode_flags(), 0);
+    }
+
+    #[test]
+    fn decst8c_resets_tabstops_to_every_eight_columns() {
+        let size = SizeInfo {
+            width: 21.0,
+            height: 51.0,
+            cell_width: 1.0,
+            cell_height: 3.0,
+            padding_x: 0.0,
+            padding_y: 0.0,
+            dpr: 1.0,
+        };
+        let mut term: Term = Term::new(size).unwrap();
+        assert_eq!(term.tabspaces, 4);
+
+        term.set_tabs_every_eight_columns();
+        assert_eq!(term.tabspaces, 8);
+        assert!(term.tabs[index::Column(8)]);
+        assert!(!term.tabs[index::Column(4)]);
+    }
+
+    #[test]
+    fn tbc_clears_current_and_all_tabstops() {
+        let size = SizeInfo {
+            width: 21.0,
+            height: 51.0,
+            cell_width: 1.0,
+            cell_height: 3.0,
+            padding_x: 0.0,
+            padding_y: 0.0,
+            dpr: 1.0,
+        };
+        let mut term: Term = Term::new(size).unwrap();
+
+        term.cursor.point.col = index::Column(4);
+        assert!(term.tabs[index::Column(4)]);
+        term.clear_tabs(ansi::TabulationClearMode::Current);
+        assert!(!term.tabs[index::Column(4)]);
+
+        assert!(term.tabs[index::Column(8)]);
+        term.clear_tabs(ansi::TabulationClearMode::All);
+        assert!(!term.tabs[index::Column(8)]);
+    }
+
+    #[test]
+    fn ris_resets_tabstops_to_every_eight_columns() {
+        let size = SizeInfo {
+            width: 21.0,
+            height: 51.0,
+            cell_width: 1.0,
+            cell_height: 3.0,
+            padding_x: 0.0,
+            padding_y: 0.0,
+            dpr: 1.0,
+        };
+        let mut term: Term = Term::new(size).unwrap();
+        term.clear_tabs(ansi::TabulationClearMode::All);
+        assert!(!term.tabs[index::Column(4)]);
+
+        term.reset_state();
+        assert_eq!(term.tabspaces, 8);
+        assert!(term.tabs[index::Column(8)]);
+        assert!(!term.tabs[index::Column(4)]);
+    }
+
+    #[test]
+    fn scrollback_lines_covers_history_the_viewport_has_already_scrolled_past() {
+        let size = SizeInfo {
+            width: 20.0,
+            height: 2.0,
+            cell_width: 1.0,
+            cell_height: 1.0,
+            padding_x: 0.0,
+            padding_y: 0.0,
+            dpr: 1.0,
+        };
+        let mut term: Term = Term::new(size).unwrap();
+        assert_eq!(term.grid().num_lines(), index::Line(2));
+
+        for (i, text) in ["line0", "line1", "line2", "line3", "line4", "line5"]
+            .iter()
+            .enumerate()
+        {
+            for c in text.chars() {
+                term.input(c);
+            }
+            if i < 5 {
+                term.linefeed();
+                term.carriage_return();
+            }
+        }
+
+        // Only "line4"/"line5" are still on screen; render_text (viewport-only) can't see the rest.
+        assert_eq!(term.render_text(), "line4\nline5");
+
+        let lines = term.scrollback_lines();
+        let texts = lines.iter().map(|l| l.text.as_str()).collect::<Vec<_>>();
+        assert_eq!(texts, vec!["line0", "line1", "line2", "line3", "line4", "line5"]);
+    }
+
+    #[test]
+    fn set_scrollback_capacity_drops_history_past_the_new_limit() {
+        let size = SizeInfo {
+            width: 20.0,
+            height: 2.0,
+            cell_width: 1.0,
+            cell_height: 1.0,
+            padding_x: 0.0,
+            padding_y: 0.0,
+            dpr: 1.0,
+        };
+        let mut term: Term = Term::with_scrollback(size, 8).unwrap();
+
+        for (i, text) in ["line0", "line1", "line2", "line3", "line4", "line5"]
+            .iter()
+            .enumerate()
+        {
+            for c in text.chars() {
+                term.input(c);
+            }
+            if i < 5 {
+                term.linefeed();
+                term.carriage_return();
+            }
+        }
+
+        term.set_scrollback_capacity(2);
+
+        // `scrollback_lines` covers the viewport (2 lines) plus up to 2 lines of history now
+        // that the capacity's been lowered - down from covering the whole 6-line history before.
+        let lines = term.scrollback_lines();
+        let texts = lines.iter().map(|l| l.text.as_str()).collect::<Vec<_>>();
+        assert_eq!(texts, vec!["line2", "line3", "line4", "line5"]);
+    }
+
+    #[test]
+    fn scroll_to_buffer_line_moves_the_matched_line_into_view() {
+        let size = SizeInfo {
+            width: 20.0,
+            height: 2.0,
+            cell_width: 1.0,
+            cell_height: 1.0,
+            padding_x: 0.0,
+            padding_y: 0.0,
+            dpr: 1.0,
+        };
+        let mut term: Term = Term::new(size).unwrap();
+
+        for (i, text) in ["line0", "line1", "line2", "line3", "line4", "line5"]
+            .iter()
+            .enumerate()
+        {
+            for c in text.chars() {
+                term.input(c);
+            }
+            if i < 5 {
+                term.linefeed();
+                term.carriage_return();
+            }
+        }
+
+        let line0 = term
+            .scrollback_lines()
+            .into_iter()
+            .find(|l| l.text == "line0")
+            .unwrap();
+        assert_eq!(term.grid().display_offset(), 0);
+
+        term.scroll_to_buffer_line(line0.buffer_line);
+        assert_eq!(term.grid().display_offset(), line0.buffer_line.min(term.grid().scroll_limit()));
+        assert_ne!(term.grid().display_offset(), 0);
+    }
+
+    fn rectangle_test_term() -> Term {
+        let size = SizeInfo {
+            width: 5.0,
+            height: 4.0,
+            cell_width: 1.0,
+            cell_height: 1.0,
+            padding_x: 0.0,
+            padding_y: 0.0,
+            dpr: 1.0,
+        };
+        Term::new(size).unwrap()
+    }
+
+    #[test]
+    fn decfra_fills_only_the_requested_rectangle() {
+        let mut term = rectangle_test_term();
+        term.fill_rectangle(
+            'x',
+            index::Line(1),
+            index::Column(1),
+            index::Line(2),
+            index::Column(3),
+        );
+        assert_eq!(term.render_text(), "\n xxx\n xxx\n");
+    }
+
+    #[test]
+    fn decera_erases_the_requested_rectangle_unconditionally() {
+        let mut term = rectangle_test_term();
+        term.fill_rectangle(
+            'x',
+            index::Line(0),
+            index::Column(0),
+            index::Line(3),
+            index::Column(4),
+        );
+        term.set_character_protection(true);
+        term.erase_rectangle(
+            index::Line(1),
+            index::Column(1),
+            index::Line(2),
+            index::Column(3),
+        );
+        assert_eq!(term.render_text(), "xxxxx\nx   x\nx   x\nxxxxx");
+    }
+
+    #[test]
+    fn decsera_leaves_protected_cells_alone() {
+        let mut term = rectangle_test_term();
+        term.fill_rectangle(
+            'x',
+            index::Line(0),
+            index::Column(0),
+            index::Line(3),
+            index::Column(4),
+        );
+        term.set_character_protection(true);
+        term.fill_rectangle(
+            'p',
+            index::Line(1),
+            index::Column(1),
+            index::Line(1),
+            index::Column(1),
+        );
+        term.set_character_protection(false);
+
+        term.selective_erase_rectangle(
+            index::Line(0),
+            index::Column(0),
+            index::Line(3),
+            index::Column(4),
+        );
+        assert_eq!(term.render_text(), "\n p\n\n");
+    }
+
+    #[test]
+    fn deccra_copies_a_rectangle_to_a_new_position() {
+        let mut term = rectangle_test_term();
+        term.fill_rectangle(
+            'x',
+            index::Line(0),
+            index::Column(0),
+            index::Line(0),
+            index::Column(2),
+        );
+        term.copy_rectangle(
+            index::Line(0),
+            index::Column(0),
+            index::Line(0),
+            index::Column(2),
+            index::Line(2),
+            index::Column(1),
+        );
+        assert_eq!(term.render_text(), "xxx\n\n xxx\n");
+    }
+
+    #[test]
+    fn decsel_leaves_protected_cells_on_the_current_line_alone() {
+        let mut term = rectangle_test_term();
+        term.fill_rectangle(
+            'x',
+            index::Line(0),
+            index::Column(0),
+            index::Line(0),
+            index::Column(4),
+        );
+        term.set_character_protection(true);
+        term.fill_rectangle(
+            'p',
+            index::Line(0),
+            index::Column(2),
+            index::Line(0),
+            index::Column(2),
+        );
+        term.set_character_protection(false);
+
+        term.goto(index::Line(0), index::Column(0));
+        term.selective_clear_line(ansi::LineClearMode::All);
+        assert_eq!(term.render_text(), "  p\n\n\n");
+    }
+
+    #[test]
+    fn decsed_leaves_protected_cells_anywhere_on_screen_alone() {
+        let mut term = rectangle_test_term();
+        term.fill_rectangle(
+            'x',
+            index::Line(0),
+            index::Column(0),
+            index::Line(3),
+            index::Column(4),
+        );
+        term.set_character_protection(true);
+        term.fill_rectangle(
+            'p',
+            index::Line(2),
+            index::Column(1),
+            index::Line(2),
+            index::Column(1),
+        );
+        term.set_character_protection(false);
+
+        term.goto(index::Line(0), index::Column(0));
+        term.selective_clear_screen(ansi::ClearMode::All);
+        assert_eq!(term.render_text(), "\n\n p\n");
+    }
+
+    #[test]
+    fn hex_encode_decode_round_trip() {
+        let bytes = b"Co".to_vec();
+        let encoded = super::hex_encode(&bytes);
+        assert_eq!(encoded, "436f");
+        assert_eq!(super::hex_decode(&encoded), Some(bytes));
+    }
+
+    #[test]
+    fn hex_decode_rejects_malformed_input() {
+        assert_eq!(super::hex_decode("abc"), None);
+        assert_eq!(super::hex_decode("zz"), None);
+    }
+
+    #[test]
+    fn hex_decode_does_not_panic_on_a_multi_byte_but_even_length_input() {
+        // Not valid hex (€ isn't a hex digit), but even-length in bytes - the point of the test
+        // is that rejecting it doesn't re-slice the `&str` mid-codepoint and panic.
+        assert_eq!(super::hex_decode("a\u{20ac}"), None);
+    }
+
+    #[test]
+    fn xtgettcap_answers_a_known_capability() {
+        let mut term = rectangle_test_term();
+        let mut writer = Vec::new();
+        let name = super::hex_encode(b"Co");
+
+        term.report_termcap(&mut writer, &name);
+
+        let value = super::hex_encode(b"256");
+        assert_eq!(
+            writer,
+            format!("\x1bP1+r{}={}\x1b\\", name, value).into_bytes()
+        );
+    }
+
+    #[test]
+    fn xtgettcap_declines_an_unknown_capability() {
+        let mut term = rectangle_test_term();
+        let mut writer = Vec::new();
+        let name = super::hex_encode(b"xx");
+
+        term.report_termcap(&mut writer, &name);
+
+        assert_eq!(writer, format!("\x1bP0+r{}\x1b\\", name).into_bytes());
+    }
+
+    #[test]
+    fn decrqss_reports_the_current_scroll_region() {
+        let mut term = rectangle_test_term();
+        let mut writer = Vec::new();
+
+        term.report_setting(&mut writer, "r");
+
+        assert_eq!(writer, b"\x1bP1$r1;4r\x1b\\");
+    }
+
+    #[test]
+    fn decrqss_reports_the_current_cursor_style() {
+        let mut term = rectangle_test_term();
+        let mut writer = Vec::new();
+
+        term.report_setting(&mut writer, " q");
+
+        assert_eq!(writer, b"\x1bP1$r1 q\x1b\\");
+    }
+
+    #[test]
+    fn decrqss_reports_the_current_sgr_attributes() {
+        let mut term = rectangle_test_term();
+        term.terminal_attribute(ansi::Attr::Bold);
+        term.terminal_attribute(ansi::Attr::Underscore);
+        let mut writer = Vec::new();
+
+        term.report_setting(&mut writer, "m");
+
+        assert_eq!(writer, b"\x1bP1$r0;1;4m\x1b\\");
+    }
+
+    #[test]
+    fn decrqss_declines_an_unsupported_request() {
+        let mut term = rectangle_test_term();
+        let mut writer = Vec::new();
+
+        term.report_setting(&mut writer, "*x");
+
+        assert_eq!(writer, b"\x1bP0$r\x1b\\");
+    }
 }
 
 #[cfg(all(test, feature = "bench"))]
@@ -2182,7 +4370,7 @@ mod benches {
 
         let config = Config::default();
 
-        let mut terminal = Term::new(size);
+        let mut terminal = Term::new(size).unwrap();
         mem::swap(&mut terminal.grid, &mut grid);
 
         b.iter(|| {
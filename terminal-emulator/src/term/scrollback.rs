@@ -0,0 +1,65 @@
+//! Enumerate a pane's entire history as logical lines, not just the visible viewport `export`'s
+//! `render_text`/`render_text_unwrapped` and `search::find` deliberately stick to: `Grid`'s
+//! buffer-absolute `Index<usize>` (as opposed to its viewport-relative `Index<Line>`) already
+//! reaches every row `mux` still has in memory, scrollback included, so this is just that same
+//! row-to-text join `render_text_unwrapped` does, walked over the whole buffer instead of one
+//! screenful - for a caller like `:filter-pane` that wants to fuzzy-match across history rather
+//! than only what's currently on screen.
+
+use crate::index::Column;
+use crate::term::cell::Flags;
+use crate::term::Term;
+
+/// One logical (soft-wrap-joined) line somewhere in a pane's history.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScrollbackLine {
+    /// Buffer-absolute index (see `Grid`'s `Index<usize>`) of this line's bottom-most row, for
+    /// `Term::scroll_to_buffer_line` to jump back to - the bottom row rather than the top since
+    /// that's the one whose `Flags::WRAPLINE` is unset, marking where `text` actually ends.
+    pub buffer_line: usize,
+    pub text: String,
+}
+
+impl Term {
+    /// Every line in the pane's history, oldest first, soft-wrapped rows joined into one logical
+    /// line the same way `render_text_unwrapped` joins them for the viewport, trailing blanks
+    /// trimmed per logical line.
+    pub fn scrollback_lines(&self) -> Vec<ScrollbackLine> {
+        let cols = self.grid().num_cols().0;
+        // Not `grid().len()`: that includes rows `Grid` has pre-allocated ahead of
+        // `scroll_limit` to amortize future growth (see `Grid::increase_scroll_limit`), which are
+        // blank and were never actually written to.
+        let total = self.grid().scroll_limit() + self.grid().num_lines().0;
+        let mut lines = Vec::new();
+        let mut text = String::new();
+
+        // Buffer index 0 is the most recent row and larger indices are further back in history,
+        // so walking from `total - 1` down to `0` visits rows oldest-first.
+        for buffer_line in (0..total).rev() {
+            let row = &self.grid()[buffer_line];
+            let wrapped = row[Column(cols - 1)].flags.contains(Flags::WRAPLINE);
+            for col in 0..cols {
+                let cell = row[Column(col)];
+                if !cell.flags.contains(Flags::WIDE_CHAR_SPACER) {
+                    text.push_str(cell.as_str().as_str());
+                }
+            }
+            if !wrapped {
+                lines.push(ScrollbackLine {
+                    buffer_line,
+                    text: text.trim_end().to_owned(),
+                });
+                text.clear();
+            }
+        }
+
+        if !text.is_empty() {
+            lines.push(ScrollbackLine {
+                buffer_line: 0,
+                text: text.trim_end().to_owned(),
+            });
+        }
+
+        lines
+    }
+}
@@ -0,0 +1,214 @@
+//! Plain-text search across the visible grid, with an optional filter on cell style so a match
+//! has to land on e.g. bold/red text or a hyperlink, not just the pattern - jumping straight to
+//! an error a compiler printed in red, or a match another tool already highlighted, instead of
+//! scrolling past every other occurrence of the same substring.
+
+use regex::Regex;
+
+use crate::index::{self, Point};
+use crate::term::cell::{Cell, Flags};
+use crate::term::Term;
+
+/// A single search match found in the grid.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchMatch {
+    pub start: Point,
+    pub end: Point,
+    pub text: String,
+}
+
+/// Cell-style constraint a match's starting cell must satisfy, on top of the text pattern
+/// itself. The default filter accepts any cell, i.e. a plain text search.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StyleFilter {
+    /// Flags that must all be set, e.g. `Flags::BOLD`.
+    pub flags: Flags,
+    pub fg: Option<crate::ansi::Color>,
+    pub bg: Option<crate::ansi::Color>,
+    /// Require the cell to carry a hyperlink (real OSC 8 or synthetic), regardless of color.
+    pub hyperlink: bool,
+}
+
+impl Default for StyleFilter {
+    fn default() -> Self {
+        Self {
+            flags: Flags::empty(),
+            fg: None,
+            bg: None,
+            hyperlink: false,
+        }
+    }
+}
+
+impl StyleFilter {
+    fn matches(&self, cell: &Cell) -> bool {
+        if !cell.flags.contains(self.flags) {
+            return false;
+        }
+        if let Some(fg) = self.fg {
+            if fg != cell.fg {
+                return false;
+            }
+        }
+        if let Some(bg) = self.bg {
+            if bg != cell.bg {
+                return false;
+            }
+        }
+        !self.hyperlink || cell.hyperlink().is_some()
+    }
+}
+
+impl Term {
+    /// Matches of `pattern` across the currently visible grid, joining soft-wrapped rows first
+    /// the same way `visible_urls` does so a match isn't missed at a wrap boundary, keeping only
+    /// those whose first cell satisfies `filter`.
+    pub fn find(&self, pattern: &Regex, filter: &StyleFilter) -> Vec<SearchMatch> {
+        let mut matches = Vec::new();
+        let mut text = String::new();
+        let mut positions: Vec<Point> = Vec::new();
+        let last_col = self.grid().num_cols() - index::Column(1);
+
+        for row in 0..self.grid().num_lines().0 {
+            let line = index::Line(row);
+            let grid_line = &self.grid()[line];
+
+            for col in index::Range::from(index::Column(0)..self.grid().num_cols()) {
+                let cell = grid_line[col];
+                if cell.flags.contains(Flags::WIDE_CHAR_SPACER) {
+                    continue;
+                }
+                for c in cell.as_str().chars() {
+                    text.push(c);
+                    positions.push(Point::new(line, col));
+                }
+            }
+
+            if !grid_line[last_col].flags.contains(Flags::WRAPLINE) {
+                matches.extend(matches_in_line(pattern, filter, self, &text, &positions));
+                text.clear();
+                positions.clear();
+            }
+        }
+
+        if !text.is_empty() {
+            matches.extend(matches_in_line(pattern, filter, self, &text, &positions));
+        }
+
+        matches
+    }
+}
+
+fn matches_in_line(
+    pattern: &Regex,
+    filter: &StyleFilter,
+    term: &Term,
+    text: &str,
+    positions: &[Point],
+) -> Vec<SearchMatch> {
+    pattern
+        .find_iter(text)
+        .filter_map(|m| {
+            let start_char = text[..m.start()].chars().count();
+            let end_char = text[..m.end()].chars().count();
+            if end_char == 0 || end_char > positions.len() {
+                return None;
+            }
+            let start = positions[start_char];
+            if !filter.matches(&term.grid()[start.line][start.col]) {
+                return None;
+            }
+            Some(SearchMatch {
+                start,
+                end: positions[end_char - 1],
+                text: text[m.start()..m.end()].to_owned(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ansi::Processor;
+    use crate::term::SizeInfo;
+
+    fn term() -> Term {
+        Term::new(SizeInfo {
+            width: 80.0,
+            height: 24.0,
+            cell_width: 1.0,
+            cell_height: 1.0,
+            padding_x: 0.0,
+            padding_y: 0.0,
+            dpr: 1.0,
+        })
+        .unwrap()
+    }
+
+    fn feed(term: &mut Term, input: &str) {
+        let mut processor = Processor::new();
+        let mut sink = Vec::new();
+        for byte in input.bytes() {
+            processor.advance(term, byte, &mut sink);
+        }
+    }
+
+    #[test]
+    fn find_with_no_filter_matches_plain_text() {
+        let mut term = term();
+        feed(&mut term, "warning: unused variable\r\nerror: missing semicolon");
+        let matches = term.find(&Regex::new(r"error: \S+").unwrap(), &StyleFilter::default());
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].text, "error: missing");
+    }
+
+    #[test]
+    fn find_with_a_flag_filter_skips_unstyled_matches() {
+        let mut term = term();
+        feed(&mut term, "plain error here\r\n\x1b[1mbold error here\x1b[0m");
+        let filter = StyleFilter {
+            flags: Flags::BOLD,
+            ..StyleFilter::default()
+        };
+        let matches = term.find(&Regex::new(r"error").unwrap(), &filter);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].start.line, index::Line(1));
+    }
+
+    #[test]
+    fn find_with_a_color_filter_only_matches_that_foreground() {
+        let mut term = term();
+        feed(&mut term, "\x1b[31mred fail\x1b[0m\r\ngreen fail");
+        let filter = StyleFilter {
+            fg: Some(crate::ansi::Color::Named(crate::ansi::NamedColor::Red)),
+            ..StyleFilter::default()
+        };
+        let matches = term.find(&Regex::new(r"fail").unwrap(), &filter);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].start.line, index::Line(0));
+    }
+
+    #[test]
+    fn find_with_hyperlink_filter_only_matches_linked_cells() {
+        let mut term = term();
+        feed(&mut term, "see docs\r\nsee docs again");
+        term.grid_mut()[index::Line(0)][index::Column(0)].set_hyperlink(Some("http://example.com"));
+        let filter = StyleFilter {
+            hyperlink: true,
+            ..StyleFilter::default()
+        };
+        let matches = term.find(&Regex::new(r"see docs").unwrap(), &filter);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].start.line, index::Line(0));
+    }
+
+    #[test]
+    fn find_joins_soft_wrapped_lines_so_a_split_match_is_still_found() {
+        let mut term = term();
+        feed(&mut term, &"x".repeat(78));
+        feed(&mut term, "error");
+        let matches = term.find(&Regex::new(r"error").unwrap(), &StyleFilter::default());
+        assert_eq!(matches.len(), 1);
+    }
+}
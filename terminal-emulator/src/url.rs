@@ -0,0 +1,124 @@
+//! Plain-text URL/path detection across the visible grid, for click-to-open and hover
+//! underlining in a frontend.
+
+use regex::Regex;
+
+use crate::index::Point;
+
+/// A single URL/path match found in the grid.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UrlMatch {
+    pub start: Point,
+    pub end: Point,
+    pub text: String,
+}
+
+/// The set of patterns used to recognize clickable text.
+///
+/// Defaults to common URL schemes and absolute/home-relative filesystem paths; callers can
+/// supply their own patterns via `new` to match other conventions, e.g. ticket references.
+#[derive(Clone)]
+pub struct UrlMatcher {
+    patterns: Vec<Regex>,
+}
+
+impl Default for UrlMatcher {
+    fn default() -> UrlMatcher {
+        UrlMatcher {
+            patterns: vec![
+                Regex::new(r#"[a-zA-Z][a-zA-Z0-9+.-]*://[^\s<>"']+"#).unwrap(),
+                Regex::new(r"(?:~|\.{0,2})?/[\w.-]+(?:/[\w.-]+)+").unwrap(),
+            ],
+        }
+    }
+}
+
+impl UrlMatcher {
+    pub fn new(patterns: Vec<Regex>) -> UrlMatcher {
+        UrlMatcher { patterns }
+    }
+
+    /// Matches in a single logical (already-unwrapped) line of text, translated back to grid
+    /// points via `positions`, which must have one entry per `char` in `text`.
+    pub fn find_in_line(&self, text: &str, positions: &[Point]) -> Vec<UrlMatch> {
+        byte_ranges(&self.patterns, text)
+            .into_iter()
+            .filter_map(|(start, end)| {
+                let start_char = text[..start].chars().count();
+                let end_char = text[..end].chars().count();
+                if end_char == 0 || end_char > positions.len() {
+                    return None;
+                }
+                Some(UrlMatch {
+                    start: positions[start_char],
+                    end: positions[end_char - 1],
+                    text: text[start..end].to_owned(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Byte ranges of every match across all `patterns`, sorted and with overlaps from later
+/// patterns dropped in favor of whichever pattern matched first.
+fn byte_ranges(patterns: &[Regex], text: &str) -> Vec<(usize, usize)> {
+    let mut ranges: Vec<(usize, usize)> = patterns
+        .iter()
+        .flat_map(|pattern| pattern.find_iter(text).map(|m| (m.start(), m.end())))
+        .collect();
+    ranges.sort_by_key(|&(start, _)| start);
+
+    let mut deduped: Vec<(usize, usize)> = Vec::new();
+    for range in ranges {
+        if deduped.last().is_some_and(|&(_, end)| range.0 < end) {
+            continue;
+        }
+        deduped.push(range);
+    }
+    deduped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UrlMatcher;
+    use crate::index::{Column, Line, Point};
+
+    fn positions_for(text: &str, line: usize) -> Vec<Point> {
+        text.chars()
+            .enumerate()
+            .map(|(col, _)| Point::new(Line(line), Column(col)))
+            .collect()
+    }
+
+    #[test]
+    fn finds_url_in_line() {
+        let matcher = UrlMatcher::default();
+        let text = "see https://example.com/path for details";
+        let positions = positions_for(text, 0);
+
+        let matches = matcher.find_in_line(text, &positions);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].text, "https://example.com/path");
+        assert_eq!(matches[0].start, Point::new(Line(0), Column(4)));
+    }
+
+    #[test]
+    fn finds_absolute_path() {
+        let matcher = UrlMatcher::default();
+        let text = "edit /etc/nginx/nginx.conf now";
+        let positions = positions_for(text, 0);
+
+        let matches = matcher.find_in_line(text, &positions);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].text, "/etc/nginx/nginx.conf");
+    }
+
+    #[test]
+    fn ignores_plain_text() {
+        let matcher = UrlMatcher::default();
+        let text = "just some ordinary output";
+        let positions = positions_for(text, 0);
+
+        assert!(matcher.find_in_line(text, &positions).is_empty());
+    }
+}
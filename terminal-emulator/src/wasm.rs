@@ -0,0 +1,98 @@
+//! `wasm_bindgen` bindings exposing just enough of `Processor`/`Term` for a browser-based
+//! frontend to drive: feed it the bytes a pane produced, read back the visible grid as plain
+//! text, and resize it when the browser window changes. Gated behind the `wasm` feature so the
+//! rest of this crate (and every non-wasm consumer of it, i.e. `mux` itself) doesn't pick up the
+//! `wasm-bindgen`/`js-sys` dependencies or their wasm32-only `VisualBell` clock (see
+//! `term::bell_now`) for free.
+//!
+//! This only covers the emulator half of a browser client - decoding escape sequences into a
+//! cell grid. Where that grid's bytes come from (a WebSocket to some server relaying a pane's
+//! pty output) is a question for whatever embeds this, not something this module has an opinion
+//! on; `terminal-emulator` has never depended on how its input arrives; `mux` itself happens to
+//! read it from a pty, and has no server component for a browser client to talk to in the first
+//! place (see the README's "Out of scope" section).
+
+use wasm_bindgen::prelude::*;
+
+use crate::ansi::Processor;
+use crate::term::{SizeInfo, Term};
+
+/// One emulated screen: an escape-sequence processor and the grid it writes into, plus a byte
+/// sink for whatever the grid writes back (DECRQSS/XTGETTCAP replies, OSC 52 clipboard reads -
+/// see `Processor::advance`'s `writer` parameter), since this has no pty to write those back to.
+#[wasm_bindgen]
+pub struct Emulator {
+    processor: Processor,
+    term: Term,
+    responses: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl Emulator {
+    /// Create a new emulator with a `cols`x`lines` grid and no scrollback history beyond
+    /// `scrollback_lines`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(cols: usize, lines: usize, scrollback_lines: usize) -> Result<Emulator, JsValue> {
+        let term = Term::with_scrollback(size_info(cols, lines), scrollback_lines)
+            .map_err(|err| JsValue::from_str(&err.to_string()))?;
+
+        Ok(Emulator {
+            processor: Processor::new(),
+            term,
+            responses: Vec::new(),
+        })
+    }
+
+    /// Feed a chunk of a pane's raw output through the parser.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.processor
+                .advance(&mut self.term, byte, &mut self.responses);
+        }
+    }
+
+    /// Take (and clear) whatever the fed bytes caused the terminal to write back, e.g. a
+    /// DECRQSS reply - there's no pty on the other end of this emulator to deliver those to, so
+    /// the caller is responsible for routing them back to wherever `feed`'s bytes came from.
+    pub fn take_responses(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.responses)
+    }
+
+    /// Resize the grid to `cols`x`lines`, preserving as much of the existing screen as
+    /// `Term::resize` does for any other caller.
+    pub fn resize(&mut self, cols: usize, lines: usize) {
+        self.term.resize(&size_info(cols, lines));
+    }
+
+    /// The visible grid as plain text, one line per row with trailing blanks trimmed - see
+    /// `Term::render_text`.
+    pub fn render_text(&self) -> String {
+        self.term.render_text()
+    }
+
+    /// Row the cursor is on, 0-indexed from the top of the visible viewport.
+    pub fn cursor_line(&self) -> usize {
+        self.term.cursor().point.line.0
+    }
+
+    /// Column the cursor is on, 0-indexed from the left of the visible viewport.
+    pub fn cursor_col(&self) -> usize {
+        self.term.cursor().point.col.0
+    }
+}
+
+/// `SizeInfo` is pixel-based (it was designed for a GUI frontend measuring a window in pixels
+/// and a font in pixels-per-cell), so a cols/lines API fakes 1 pixel per cell the same way
+/// `mux`'s own `ui::ProcessState::from_settings` and `replay::REPLAY_SIZE` already do for their
+/// own non-pixel callers.
+fn size_info(cols: usize, lines: usize) -> SizeInfo {
+    SizeInfo {
+        width: cols as f32,
+        height: lines as f32,
+        cell_width: 1.0,
+        cell_height: 1.0,
+        padding_x: 0.0,
+        padding_y: 0.0,
+        dpr: 1.0,
+    }
+}